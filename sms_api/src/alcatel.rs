@@ -1,82 +1,336 @@
-use std::time::Duration;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
 
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use reqwest::{Client, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 
-use crate::{SmsError, SmsService};
+use crate::{
+    DeliveryReport, DeliveryStatus, Inbox, ModemStatus, ReceivedSms, SendReport, SimLock, SmsError,
+    SmsService, SmsStorage, MAX_SIGNAL_BARS,
+};
+
+const SMS_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const SMS_TYPE_READ: i8 = 0;
+const SMS_TYPE_UNREAD: i8 = 1;
+const SMS_TYPE_REPORT: i8 = 4;
+const REPORT_SWITCH_ON: i64 = 1;
+const DELETE_SINGLE_SMS: i64 = 2;
+const ROAMING: i64 = 0;
+const SIM_STATE_PIN_REQUIRED: i64 = 2;
+const SIM_STATE_PUK_REQUIRED: i64 = 3;
+const SIM_STATE_PUK_EXHAUSTED: i64 = 5;
+const SEND_STATUS_SUCCESS: i8 = 2;
+// Modem refused the sms because it is still sending previous one
+const SEND_STATUS_BUSY: i8 = 3;
+const SEND_STATUS_MEMORY_FULL: i8 = 4;
+const SEND_STATUS_FAILED: i8 = 5;
+const USSD_TYPE_REQUEST: i64 = 1;
+const USSD_SEND_STATE_SENDING: i64 = 1;
+const USSD_SEND_STATE_SUCCESS: i64 = 2;
+// Web interface obfuscates credentials and token with this key before sending them
+const ENCRYPTION_KEY: &[u8] = b"e5dl12XYVggihggafXWf0f2YSf2Xngd1";
+const VERIFICATION_KEY: &str = "KSDHSDFOGQ5WERYTUIQWERTYUISDFG1HJZXCVCXBN2GDSMNDHKVKFsVBNf";
+const TOKEN_HEADER: &str = "_TclRequestVerificationToken";
+const VERIFICATION_KEY_HEADER: &str = "_TclRequestVerificationKey";
 
 pub(crate) struct AlcatelSmsService {
     url: String,
     retry_count: usize,
     retry_delay: Duration,
+    credentials: Option<(String, String)>,
+    // Token of current login, reused until modem answers with 401
+    token: Mutex<Option<String>>,
+    pin: Option<String>,
+    delivery_reports: bool,
+    // Report setting is checked once per service, not before every sms
+    reports_enabled: AtomicBool,
     client: Client,
 }
 
 #[async_trait]
 impl SmsService for AlcatelSmsService {
-    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<(), SmsError> {
+    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError> {
+        if self.delivery_reports {
+            self.enable_delivery_reports().await?;
+        }
         self.send_all_sms(msg, phone_numbers).await
     }
+
+    async fn read_inbox(&self) -> Result<Inbox, SmsError> {
+        let mut inbox = vec![];
+        for contact in self.get_sms_contact_list().await? {
+            let phone = contact.phone_number.into_iter().next().unwrap_or_default();
+            for sms in self.get_sms_content_list(contact.contact_id).await? {
+                if sms.sms_type != SMS_TYPE_READ && sms.sms_type != SMS_TYPE_UNREAD {
+                    continue;
+                }
+                inbox.push(ReceivedSms {
+                    id: sms.sms_id,
+                    phone: phone.clone(),
+                    text: sms.sms_content,
+                    time: parse_sms_time(&sms.sms_time)?,
+                    unread: sms.sms_type == SMS_TYPE_UNREAD,
+                });
+            }
+        }
+        inbox.sort_by_key(|sms| std::cmp::Reverse(sms.time));
+        Ok(Inbox {
+            messages: inbox,
+            undecodable: vec![],
+        })
+    }
+
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError> {
+        let mut reports = vec![];
+        for contact in self.get_sms_contact_list().await? {
+            let phone = contact.phone_number.into_iter().next().unwrap_or_default();
+            for sms in self.get_sms_content_list(contact.contact_id).await? {
+                if sms.sms_type != SMS_TYPE_REPORT {
+                    continue;
+                }
+                // Modem stores report as message with text like "Message delivered"
+                let status = if sms.sms_content.to_lowercase().contains("fail") {
+                    DeliveryStatus::Failed
+                } else {
+                    DeliveryStatus::Delivered
+                };
+                reports.push(DeliveryReport {
+                    id: sms.sms_id,
+                    phone: phone.clone(),
+                    status,
+                    time: parse_sms_time(&sms.sms_time)?,
+                    provider: None,
+                    reference: None,
+                });
+            }
+        }
+        reports.sort_by_key(|report| report.time);
+        Ok(reports)
+    }
+
+    async fn delete_sms(&self, id: i64) -> Result<(), SmsError> {
+        for contact in self.get_sms_contact_list().await? {
+            let messages = self.get_sms_content_list(contact.contact_id).await?;
+            if messages.iter().any(|sms| sms.sms_id == id) {
+                return self
+                    .call_json_rpc::<_, serde_json::Value>(
+                        "DeleteSMS",
+                        "6.5",
+                        DeleteSmsParams {
+                            del_flag: DELETE_SINGLE_SMS,
+                            contact_id: contact.contact_id,
+                            sms_id: id,
+                        },
+                    )
+                    .await
+                    .map(|_| ());
+            }
+        }
+        Err(SmsError::UnknownError(format!(
+            "Could not find sms with id {}",
+            id
+        )))
+    }
+
+    async fn status(&self) -> Result<ModemStatus, SmsError> {
+        let system: SystemStatus = self.call_json_rpc("GetSystemStatus", "13.4", ()).await?;
+        let network: NetworkInfo = self.call_json_rpc("GetNetworkInfo", "4.1", ()).await?;
+        let sim: SimStatus = self.call_json_rpc("GetSimStatus", "2.1", ()).await?;
+        let storage: SmsStorageState = self.call_json_rpc("GetSMSStorageState", "6.4", ()).await?;
+        let operator = if network.spn_name.is_empty() {
+            network.network_name
+        } else {
+            network.spn_name
+        };
+        Ok(ModemStatus {
+            operator,
+            network_type: network_type_name(system.network_type).to_string(),
+            signal_bars: system.signal_strength.clamp(0, MAX_SIGNAL_BARS as i64) as u8,
+            roaming: network.roaming == Some(ROAMING),
+            sim_state: sim_state_name(sim.sim_state).to_string(),
+            pin_state: pin_state_name(sim.pin_state).to_string(),
+            sms_storage: Some(SmsStorage {
+                used: storage.used_count,
+                capacity: storage.max_count,
+            }),
+        })
+    }
+
+    async fn unlock(&self, pin: &str) -> Result<(), SmsError> {
+        match self.sim_lock().await? {
+            None => Ok(()),
+            Some(SimLock::PinRequired) => self.unlock_pin(pin).await,
+            Some(lock) => Err(SmsError::SimLocked(lock)),
+        }
+    }
+
+    async fn send_ussd(&self, code: &str) -> Result<String, SmsError> {
+        self.call_json_rpc::<_, serde_json::Value>(
+            "SendUSSD",
+            "8.1",
+            SendUssdParams {
+                content: code.to_string(),
+                ussd_type: USSD_TYPE_REQUEST,
+            },
+        )
+        .await?;
+        let reply = self.wait_for_ussd_reply().await;
+        // Session is left open by network when reply expects answer, it would block next code
+        self.call_json_rpc::<_, serde_json::Value>("SetUSSDEnd", "8.3", ())
+            .await?;
+        reply
+    }
 }
 
 impl AlcatelSmsService {
-    pub fn new(url: &str, retry_count: usize, retry_dealy: Duration) -> Result<Self, SmsError> {
+    pub fn new(
+        url: &str,
+        retry_count: usize,
+        retry_dealy: Duration,
+        credentials: Option<(String, String)>,
+        pin: Option<String>,
+        delivery_reports: bool,
+    ) -> Result<Self, SmsError> {
         Ok(Self {
             url: url.to_string(),
             retry_count,
             retry_delay: retry_dealy,
+            credentials,
+            token: Mutex::new(None),
+            pin,
+            delivery_reports,
+            reports_enabled: AtomicBool::new(false),
             client: create_client(url)?,
         })
     }
 
-    async fn send_all_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<(), SmsError> {
+    async fn send_all_sms(
+        &self,
+        msg: &str,
+        phone_numbers: &[&str],
+    ) -> Result<SendReport, SmsError> {
+        let mut report = SendReport::default();
         for phone in phone_numbers {
-            self.send_single_sms(msg, phone).await?;
+            if report.should_skip() {
+                report.skip(phone);
+                continue;
+            }
+            let result = self.send_single_sms(msg, phone).await;
+            report.add(phone, result);
         }
-        Ok(())
+        Ok(report)
     }
 
+    // Modem with locked SIM accepts sms and never sends it, so SIM is checked only after failure
     async fn send_single_sms(&self, msg: &str, phone: &str) -> Result<(), SmsError> {
+        let Err(error) = self.try_send_single_sms(msg, phone).await else {
+            return Ok(());
+        };
+        match (self.sim_lock().await, &self.pin) {
+            (Ok(Some(SimLock::PinRequired)), Some(pin)) => {
+                self.unlock_pin(pin).await?;
+                self.try_send_single_sms(msg, phone).await
+            }
+            (Ok(Some(lock)), _) => Err(SmsError::SimLocked(lock)),
+            _ => Err(error),
+        }
+    }
+
+    async fn try_send_single_sms(&self, msg: &str, phone: &str) -> Result<(), SmsError> {
         self.call_sms_send(&msg, &phone).await?;
-        self.wait_until_sent().await?;
+        self.wait_until_sent().await
+    }
+
+    // Settings are written back whole, so other values stay as they were
+    async fn enable_delivery_reports(&self) -> Result<(), SmsError> {
+        if self.reports_enabled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut settings: serde_json::Value =
+            self.call_json_rpc("GetSMSSettings", "6.5", ()).await?;
+        if settings["SMSReportSwitch"] != REPORT_SWITCH_ON {
+            settings["SMSReportSwitch"] = REPORT_SWITCH_ON.into();
+            self.call_json_rpc::<_, serde_json::Value>("SetSMSSettings", "6.6", settings)
+                .await?;
+        }
+        self.reports_enabled.store(true, Ordering::Relaxed);
         Ok(())
     }
 
+    async fn unlock_pin(&self, pin: &str) -> Result<(), SmsError> {
+        self.call_json_rpc::<_, serde_json::Value>(
+            "UnlockPin",
+            "2.2",
+            UnlockPinParams {
+                pin: pin.to_string(),
+            },
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn sim_lock(&self) -> Result<Option<SimLock>, SmsError> {
+        let sim: SimStatus = self.call_json_rpc("GetSimStatus", "2.1", ()).await?;
+        Ok(match sim.sim_state {
+            SIM_STATE_PIN_REQUIRED => Some(SimLock::PinRequired),
+            SIM_STATE_PUK_REQUIRED | SIM_STATE_PUK_EXHAUSTED => Some(SimLock::PukRequired),
+            _ => None,
+        })
+    }
+
+    // Some firmwares answer with empty body, only error in answer is checked
     async fn call_sms_send(&self, msg: &&str, phone: &&str) -> Result<(), SmsError> {
-        let res = self
-            .client
-            .post(format!("{}/jrd/webapi?api=SendSMS", self.url))
-            .json(&SendSmsRequest::new(
-                msg.to_string(),
-                vec![phone.to_string()],
-            ))
-            .send()
-            .await
-            .map_err(|e| SmsError::NetworkError(e.to_string()))?;
-        self.ensure_status_is_success(res).await?;
-        Ok(())
+        let body = self
+            .post(
+                "SendSMS",
+                &SendSmsRequest::new(msg.to_string(), vec![phone.to_string()]),
+            )
+            .await?
+            .text()
+            .await?;
+        match serde_json::from_str::<JsonRpcResponse<serde_json::Value>>(&body) {
+            Ok(response) if response.error.is_some() => {
+                json_rpc_result("SendSMS", response).map(|_| ())
+            }
+            _ => Ok(()),
+        }
     }
 
     async fn wait_until_sent(&self) -> Result<(), SmsError> {
         let mut current_try = 0;
         while current_try < self.retry_count {
-            let response = self
-                .client
-                .post(format!("{}/jrd/webapi?api=GetSendSMSResult", self.url))
-                .body(r#"{"jsonrpc":"2.0","method":"GetSendSMSResult","params":null,"id":"6.7"}"#)
-                .send()
-                .await
-                .map_err(|e| SmsError::NetworkError(e.to_string()))?;
+            let request = JsonRpcRequest {
+                id: "6.7".to_string(),
+                jsonrpc: "2.0".into(),
+                method: "GetSendSMSResult".to_string(),
+                params: (),
+            };
+            // Message is already accepted, failed check must not lead to sending it again
             let status_code = self
-                .ensure_status_is_success(response)
-                .await?
-                .json::<GetSendSmsResultResponse>()
-                .await?
-                .result
-                .send_status;
-            if status_code == 2 {
-                return Ok(());
+                .get_send_status(&request)
+                .await
+                .map_err(SmsError::into_unconfirmed)?;
+            match status_code {
+                SEND_STATUS_SUCCESS => return Ok(()),
+                SEND_STATUS_BUSY => {
+                    return Err(SmsError::ModemBusy(
+                        "Modem is still sending previous sms".into(),
+                    ))
+                }
+                SEND_STATUS_MEMORY_FULL | SEND_STATUS_FAILED => {
+                    return Err(SmsError::ModemError(format!(
+                        "Modem failed to send sms with status {}",
+                        status_code
+                    )))
+                }
+                _ => {}
             }
             tokio::time::sleep(self.retry_delay).await;
             current_try += 1;
@@ -86,14 +340,171 @@ impl AlcatelSmsService {
         ))
     }
 
+    async fn get_send_status(&self, request: &JsonRpcRequest<()>) -> Result<i8, SmsError> {
+        Ok(self
+            .post("GetSendSMSResult", request)
+            .await?
+            .json::<GetSendSmsResultResponse>()
+            .await?
+            .result
+            .send_status)
+    }
+
+    async fn wait_for_ussd_reply(&self) -> Result<String, SmsError> {
+        for _ in 0..self.retry_count {
+            let result: UssdSendResult = self.call_json_rpc("GetUSSDSendResult", "8.2", ()).await?;
+            match result.send_state {
+                USSD_SEND_STATE_SUCCESS => return Ok(result.content),
+                USSD_SEND_STATE_SENDING => tokio::time::sleep(self.retry_delay).await,
+                state => {
+                    return Err(SmsError::ModemError(format!(
+                        "USSD request failed with state {}",
+                        state
+                    )))
+                }
+            }
+        }
+        Err(SmsError::ModemError(
+            "Network didn't answer USSD request in time".into(),
+        ))
+    }
+
+    async fn get_sms_contact_list(&self) -> Result<Vec<SmsContact>, SmsError> {
+        let mut contacts = vec![];
+        let mut page = 0;
+        loop {
+            let result = self
+                .call_json_rpc::<_, SmsContactListResult>(
+                    "GetSMSContactList",
+                    "6.2",
+                    SmsContactListParams { page },
+                )
+                .await?;
+            contacts.extend(result.sms_contact_list);
+            page += 1;
+            if page >= result.total_page_count {
+                return Ok(contacts);
+            }
+        }
+    }
+
+    async fn get_sms_content_list(&self, contact_id: i64) -> Result<Vec<SmsContent>, SmsError> {
+        let mut messages = vec![];
+        let mut page = 0;
+        loop {
+            let result = self
+                .call_json_rpc::<_, SmsContentListResult>(
+                    "GetSMSContentList",
+                    "6.3",
+                    SmsContentListParams { page, contact_id },
+                )
+                .await?;
+            messages.extend(result.sms_content_list);
+            page += 1;
+            if page >= result.total_page_count {
+                return Ok(messages);
+            }
+        }
+    }
+
+    async fn call_json_rpc<P, R>(&self, method: &str, id: &str, params: P) -> Result<R, SmsError>
+    where
+        P: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        let request = JsonRpcRequest {
+            id: id.to_string(),
+            jsonrpc: "2.0".into(),
+            method: method.to_string(),
+            params,
+        };
+        let response = self
+            .post(method, &request)
+            .await?
+            .json::<JsonRpcResponse<R>>()
+            .await?;
+        json_rpc_result(method, response)
+    }
+
+    // Logs in when credentials are configured and there is no cached token, request rejected
+    // with 401 is repeated once after logging in again.
+    async fn post<B: Serialize>(&self, api: &str, body: &B) -> Result<Response, SmsError> {
+        let token = self.ensure_logged_in().await?;
+        let response = self.send_post(api, body, token.as_deref()).await?;
+        if response.status() != StatusCode::UNAUTHORIZED || self.credentials.is_none() {
+            return self.ensure_status_is_success(response).await;
+        }
+        *self.token.lock().unwrap() = None;
+        let token = self.ensure_logged_in().await?;
+        let response = self.send_post(api, body, token.as_deref()).await?;
+        self.ensure_status_is_success(response).await
+    }
+
+    async fn send_post<B: Serialize>(
+        &self,
+        api: &str,
+        body: &B,
+        token: Option<&str>,
+    ) -> Result<Response, SmsError> {
+        let mut request = self
+            .client
+            .post(format!("{}/jrd/webapi?api={}", self.url, api))
+            .json(body);
+        if let Some(token) = token {
+            request = request.header(TOKEN_HEADER, encrypt(token));
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| SmsError::NetworkError(e.to_string()))
+    }
+
+    async fn ensure_logged_in(&self) -> Result<Option<String>, SmsError> {
+        let Some((username, password)) = &self.credentials else {
+            return Ok(None);
+        };
+        if let Some(token) = self.token.lock().unwrap().clone() {
+            return Ok(Some(token));
+        }
+        let request = JsonRpcRequest {
+            id: "1.1".to_string(),
+            jsonrpc: "2.0".into(),
+            method: "Login".to_string(),
+            params: LoginParams {
+                user_name: encrypt(username),
+                password: encrypt(password),
+            },
+        };
+        let response = self.send_post("Login", &request, None).await?;
+        let response = self
+            .ensure_status_is_success(response)
+            .await?
+            .json::<JsonRpcResponse<LoginResult>>()
+            .await?;
+        let token = match json_rpc_result("Login", response)?.token {
+            serde_json::Value::String(token) => token,
+            token => token.to_string(),
+        };
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(Some(token))
+    }
+
     async fn ensure_status_is_success(&self, response: Response) -> Result<Response, SmsError> {
         let status = response.status();
         match status {
         StatusCode::OK => Ok(response),
-        StatusCode::UNAUTHORIZED => Err(SmsError::InvalidResponse(status, "Unauthorized".into())),
+        StatusCode::UNAUTHORIZED if self.credentials.is_some() => {
+            Err(SmsError::InvalidResponse(status, "Unauthorized, check username and password of Alcatel provider".into()))
+        }
+        StatusCode::UNAUTHORIZED => {
+            Err(SmsError::InvalidResponse(status, "Unauthorized, modem requires login, configure username and password of Alcatel provider".into()))
+        }
         StatusCode::NOT_FOUND => {
             Err(SmsError::InvalidResponse(status, format!("We could not find service under url {}, make sure usb modem is connected and service is running", self.url)))
         }
+        _ if status.is_server_error() => {
+            Err(SmsError::InvalidResponse(status, "Unexpected status code".to_string()))
+        }
         _ => {
             Err(SmsError::UnknownError(format!(
                 "Unexpected status code: {}",
@@ -104,9 +515,88 @@ impl AlcatelSmsService {
     }
 }
 
+fn json_rpc_result<R>(method: &str, response: JsonRpcResponse<R>) -> Result<R, SmsError> {
+    match (response.result, response.error) {
+        (Some(result), _) => Ok(result),
+        (None, Some(error)) => {
+            let message = format!(
+                "{} failed with code {}: {}",
+                method, error.code, error.message
+            );
+            // Firmwares differ in codes of busy state, but all of them name it in message
+            if error.message.to_lowercase().contains("busy") {
+                return Err(SmsError::ModemBusy(message));
+            }
+            Err(SmsError::UnknownError(message))
+        }
+        (None, None) => Err(SmsError::UnknownError(format!(
+            "{} returned neither result nor error",
+            method
+        ))),
+    }
+}
+
+fn network_type_name(network_type: i64) -> &'static str {
+    match network_type {
+        0 => "No service",
+        1 => "GPRS",
+        2 => "EDGE",
+        3 => "HSPA",
+        4 => "HSUPA",
+        5 => "UMTS",
+        6 => "HSPA+",
+        7 => "DC-HSPA+",
+        8 => "LTE",
+        9 => "LTE+",
+        _ => "Unknown",
+    }
+}
+
+fn sim_state_name(sim_state: i64) -> &'static str {
+    match sim_state {
+        0 => "No SIM",
+        1 => "Detected",
+        2 => "PIN required",
+        3 => "PUK required",
+        4 => "SIM locked",
+        5 => "PUK attempts exhausted",
+        6 => "Invalid SIM",
+        7 => "Ready",
+        11 => "Initializing",
+        _ => "Unknown",
+    }
+}
+
+fn pin_state_name(pin_state: i64) -> &'static str {
+    match pin_state {
+        1 => "Enabled, not verified",
+        2 => "Enabled, verified",
+        3 => "Disabled",
+        4 => "Blocked",
+        5 => "Permanently blocked",
+        _ => "Unknown",
+    }
+}
+
+fn encrypt(value: &str) -> String {
+    let mut encrypted = String::new();
+    for (index, byte) in value.bytes().enumerate() {
+        let key = ENCRYPTION_KEY[index % ENCRYPTION_KEY.len()];
+        encrypted.push(((key & 0xf0) | ((byte & 0x0f) ^ (key & 0x0f))) as char);
+        encrypted.push(((key & 0xf0) | ((byte >> 4) ^ (key & 0x0f))) as char);
+    }
+    encrypted
+}
+
+fn parse_sms_time(time: &str) -> Result<NaiveDateTime, SmsError> {
+    NaiveDateTime::parse_from_str(time, SMS_TIME_FORMAT)
+        .map_err(|e| SmsError::UnknownError(format!("Invalid sms time '{}': {}", time, e)))
+}
+
 fn create_client(url: &str) -> Result<Client, SmsError> {
     let mut default_headers = reqwest::header::HeaderMap::new();
     default_headers.insert("Referer", format!("{}/default.html", url).parse().unwrap());
+    default_headers.insert(VERIFICATION_KEY_HEADER, VERIFICATION_KEY.parse().unwrap());
 
     Client::builder()
         .timeout(Duration::from_secs(10))
@@ -164,3 +654,152 @@ struct SendStatus {
     #[serde(rename = "SendStatus")]
     pub send_status: i8,
 }
+
+#[derive(Serialize, Debug)]
+struct JsonRpcRequest<P> {
+    id: String,
+    jsonrpc: String,
+    method: String,
+    params: P,
+}
+
+#[derive(Deserialize, Debug)]
+struct JsonRpcResponse<R> {
+    result: Option<R>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct JsonRpcError {
+    code: serde_json::Value,
+    message: String,
+}
+
+#[derive(Serialize, Debug)]
+struct LoginParams {
+    #[serde(rename = "UserName")]
+    user_name: String,
+    #[serde(rename = "Password")]
+    password: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct LoginResult {
+    token: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct SystemStatus {
+    #[serde(rename = "NetworkType", default)]
+    network_type: i64,
+    #[serde(rename = "SignalStrength", default)]
+    signal_strength: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct NetworkInfo {
+    #[serde(rename = "NetworkName", default)]
+    network_name: String,
+    #[serde(rename = "SpnName", default)]
+    spn_name: String,
+    #[serde(rename = "Roaming", default)]
+    roaming: Option<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SimStatus {
+    #[serde(rename = "SIMState")]
+    sim_state: i64,
+    #[serde(rename = "PinState", default)]
+    pin_state: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct UnlockPinParams {
+    #[serde(rename = "Pin")]
+    pin: String,
+}
+
+#[derive(Serialize, Debug)]
+struct SendUssdParams {
+    #[serde(rename = "UssdContent")]
+    content: String,
+    #[serde(rename = "UssdType")]
+    ussd_type: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct UssdSendResult {
+    #[serde(rename = "SendState")]
+    send_state: i64,
+    #[serde(rename = "UssdContent", default)]
+    content: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsStorageState {
+    #[serde(rename = "TUseCount")]
+    used_count: u32,
+    #[serde(rename = "MaxCount")]
+    max_count: u32,
+}
+
+#[derive(Serialize, Debug)]
+struct SmsContactListParams {
+    #[serde(rename = "Page")]
+    page: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsContactListResult {
+    #[serde(rename = "SMSContactList", default)]
+    sms_contact_list: Vec<SmsContact>,
+    #[serde(rename = "TotalPageCount")]
+    total_page_count: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsContact {
+    #[serde(rename = "ContactId")]
+    contact_id: i64,
+    #[serde(rename = "PhoneNumber", default)]
+    phone_number: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct DeleteSmsParams {
+    #[serde(rename = "DelFlag")]
+    del_flag: i64,
+    #[serde(rename = "ContactId")]
+    contact_id: i64,
+    #[serde(rename = "SMSId")]
+    sms_id: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct SmsContentListParams {
+    #[serde(rename = "Page")]
+    page: i64,
+    #[serde(rename = "ContactId")]
+    contact_id: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsContentListResult {
+    #[serde(rename = "SMSContentList", default)]
+    sms_content_list: Vec<SmsContent>,
+    #[serde(rename = "TotalPageCount")]
+    total_page_count: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsContent {
+    #[serde(rename = "SMSId")]
+    sms_id: i64,
+    #[serde(rename = "SMSType")]
+    sms_type: i8,
+    #[serde(rename = "SMSTime")]
+    sms_time: String,
+    #[serde(rename = "SMSContent")]
+    sms_content: String,
+}
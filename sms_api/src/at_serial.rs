@@ -0,0 +1,601 @@
+use std::{
+    sync::atomic::{AtomicU8, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use chrono::Local;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_serial::SerialPortBuilderExt;
+
+use crate::{
+    pdu::{self, DeliverPdu, EncodedPdu, ReceivedPdu, StatusReportPdu},
+    DeliveryReport, DeliveryStatus, Inbox, ModemStatus, ReceivedSms, SendReport, SimLock, SmsError,
+    SmsService, SmsStorage, UndecodableSms,
+};
+
+const CTRL_Z: u8 = 0x1A;
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+// Message status of `AT+CMGL` in PDU mode: 0 received unread, 1 received read, 4 all
+const STATUS_UNREAD: &str = "0";
+const STATUS_READ: &str = "1";
+const LIST_ALL: &str = "4";
+// Registration status of `AT+CREG?`
+const REGISTERED_ROAMING: &str = "5";
+// Result of `+CUSD`: 0 no further action, 1 further action required
+const USSD_DONE: &str = "0";
+const USSD_ACTION_REQUIRED: &str = "1";
+const USSD_DCS_GSM7: u8 = 15;
+const USSD_DCS_UCS2: u8 = 72;
+// Status of delivery in SMS-STATUS-REPORT, values below are final success, values above
+// `REPORT_STILL_TRYING` are final failures
+const REPORT_DELIVERED: u8 = 0x1F;
+const REPORT_STILL_TRYING: u8 = 0x3F;
+const CME_SIM_BUSY: &str = "+CME ERROR: 14";
+const CMS_SIM_BUSY: &str = "+CMS ERROR: 314";
+const CMS_NETWORK_TIMEOUT: &str = "+CMS ERROR: 332";
+
+pub(crate) struct AtSerialSmsService {
+    device: String,
+    baud_rate: u32,
+    pin: Option<String>,
+    delivery_reports: bool,
+    // Reference of concatenated messages, starts at random value so parts of messages
+    // sent by separate runs are not mixed up by the recipient phone
+    next_reference: AtomicU8,
+}
+
+impl AtSerialSmsService {
+    pub fn new(device: &str, baud_rate: u32, pin: Option<String>, delivery_reports: bool) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u8)
+            .unwrap_or_default();
+        Self {
+            device: device.to_string(),
+            baud_rate,
+            pin,
+            delivery_reports,
+            next_reference: AtomicU8::new(seed),
+        }
+    }
+
+    async fn open(&self) -> Result<AtModem<tokio_serial::SerialStream>, SmsError> {
+        let mut modem = self.connect().await?;
+        modem.unlock(self.pin.as_deref()).await?;
+        modem.command("AT+CMGF=0").await?;
+        if self.delivery_reports {
+            // Status reports are stored in memory like received sms
+            modem.command("AT+CNMI=2,1,0,2,0").await?;
+        }
+        Ok(modem)
+    }
+
+    // Returns message reference of the last part, the only one with requested status report
+    async fn send_to(
+        &self,
+        modem: &mut AtModem<tokio_serial::SerialStream>,
+        msg: &str,
+        phone_number: &str,
+    ) -> Result<Option<u8>, SmsError> {
+        let reference = self.next_reference.fetch_add(1, Ordering::Relaxed);
+        let pdus = pdu::encode_submit(phone_number, msg, reference, self.delivery_reports)?;
+        let mut message_reference = None;
+        for (index, pdu) in pdus.iter().enumerate() {
+            let sent = modem.send_pdu(pdu).await;
+            // Recipient already got accepted parts, sending whole message again would repeat them
+            message_reference = if index == 0 {
+                sent?
+            } else {
+                sent.map_err(SmsError::into_unconfirmed)?
+            };
+        }
+        Ok(message_reference)
+    }
+
+    // Opens port without unlocking SIM, so state of locked modem can be checked
+    async fn connect(&self) -> Result<AtModem<tokio_serial::SerialStream>, SmsError> {
+        let port = tokio_serial::new(&self.device, self.baud_rate)
+            .open_native_async()
+            .map_err(|e| {
+                SmsError::NetworkError(format!(
+                    "Could not open serial device {}, Reason: {}",
+                    self.device, e
+                ))
+            })?;
+        let mut modem = AtModem::new(port);
+        modem.command("ATE0").await?;
+        Ok(modem)
+    }
+}
+
+#[async_trait]
+impl SmsService for AtSerialSmsService {
+    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError> {
+        let mut modem = self.open().await?;
+        let mut report = SendReport::default();
+        for phone_number in phone_numbers {
+            if report.should_skip() {
+                report.skip(phone_number);
+                continue;
+            }
+            let result = self.send_to(&mut modem, msg, phone_number).await;
+            report.add_referenced(phone_number, result);
+        }
+        Ok(report)
+    }
+
+    async fn read_inbox(&self) -> Result<Inbox, SmsError> {
+        let mut modem = self.open().await?;
+        let lines = modem.command(&format!("AT+CMGL={}", LIST_ALL)).await?;
+        let (messages, undecodable) = parse_message_list(&lines)?;
+        let parts = messages
+            .into_iter()
+            .filter_map(|message| match message.pdu {
+                ReceivedPdu::Deliver(pdu) => Some(MessagePart {
+                    id: message.id,
+                    unread: message.unread,
+                    pdu,
+                }),
+                ReceivedPdu::StatusReport(_) => None,
+            })
+            .collect();
+        let mut inbox = join_parts(parts);
+        inbox.sort_by_key(|sms| std::cmp::Reverse(sms.time));
+        Ok(Inbox {
+            messages: inbox,
+            undecodable,
+        })
+    }
+
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError> {
+        let mut modem = self.open().await?;
+        let lines = modem.command(&format!("AT+CMGL={}", LIST_ALL)).await?;
+        // Undecodable messages are listed by inbox, they can't be told from reports
+        let (messages, _) = parse_message_list(&lines)?;
+        let mut reports: Vec<DeliveryReport> = messages
+            .into_iter()
+            .filter_map(|message| match message.pdu {
+                ReceivedPdu::StatusReport(report) => Some(delivery_report(message.id, report)),
+                ReceivedPdu::Deliver(_) => None,
+            })
+            .collect();
+        reports.sort_by_key(|report| report.time);
+        Ok(reports)
+    }
+
+    async fn delete_sms(&self, id: i64) -> Result<(), SmsError> {
+        let mut modem = self.open().await?;
+        modem.command(&format!("AT+CMGD={}", id)).await.map(|_| ())
+    }
+
+    async fn status(&self) -> Result<ModemStatus, SmsError> {
+        let mut modem = self.connect().await?;
+        // Modem answers with error when there is no SIM inserted
+        let Ok(pin) = modem.command("AT+CPIN?").await else {
+            return Ok(ModemStatus {
+                operator: String::new(),
+                network_type: "No service".to_string(),
+                signal_bars: 0,
+                roaming: false,
+                sim_state: "No SIM".to_string(),
+                pin_state: "Unknown".to_string(),
+                sms_storage: None,
+            });
+        };
+        let pin = response_fields(&pin, "+CPIN:").remove(0);
+        let operator = response_fields(&modem.command("AT+COPS?").await?, "+COPS:");
+        let signal = response_fields(&modem.command("AT+CSQ").await?, "+CSQ:");
+        let registration = response_fields(&modem.command("AT+CREG?").await?, "+CREG:");
+        let (sim_state, pin_state) = match pin.as_str() {
+            "READY" => {
+                let lock = response_fields(&modem.command("AT+CLCK=\"SC\",2").await?, "+CLCK:");
+                let pin_state = if lock[0] == "1" {
+                    "Enabled, verified"
+                } else {
+                    "Disabled"
+                };
+                ("Ready", pin_state)
+            }
+            "SIM PIN" => ("PIN required", "Enabled, not verified"),
+            "SIM PUK" => ("PUK required", "Blocked"),
+            _ => ("Unknown", "Unknown"),
+        };
+        // SMS storage can't be read until SIM is unlocked
+        let sms_storage = if pin == "READY" {
+            let storage = response_fields(&modem.command("AT+CPMS?").await?, "+CPMS:");
+            let count = |index: usize| storage.get(index).and_then(|c| c.parse().ok());
+            count(1)
+                .zip(count(2))
+                .map(|(used, capacity)| SmsStorage { used, capacity })
+        } else {
+            None
+        };
+        Ok(ModemStatus {
+            operator: operator.get(2).cloned().unwrap_or_default(),
+            network_type: access_technology_name(operator.get(3).map(String::as_str)).to_string(),
+            signal_bars: signal_bars(signal[0].parse().unwrap_or(99)),
+            roaming: registration.get(1).map(String::as_str) == Some(REGISTERED_ROAMING),
+            sim_state: sim_state.to_string(),
+            pin_state: pin_state.to_string(),
+            sms_storage,
+        })
+    }
+
+    async fn unlock(&self, pin: &str) -> Result<(), SmsError> {
+        let mut modem = self.connect().await?;
+        modem.unlock(Some(pin)).await
+    }
+
+    async fn send_ussd(&self, code: &str) -> Result<String, SmsError> {
+        let mut modem = self.connect().await?;
+        modem.unlock(self.pin.as_deref()).await?;
+        modem.ussd(code).await
+    }
+}
+
+pub(crate) struct AtModem<S> {
+    port: S,
+    buffer: Vec<u8>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AtModem<S> {
+    pub fn new(port: S) -> Self {
+        Self {
+            port,
+            buffer: vec![],
+        }
+    }
+
+    // Sends command and returns lines of response without final `OK`
+    pub async fn command(&mut self, command: &str) -> Result<Vec<String>, SmsError> {
+        self.write(format!("{}\r", command).as_bytes()).await?;
+        self.read_response(command).await
+    }
+
+    pub async fn unlock(&mut self, pin: Option<&str>) -> Result<(), SmsError> {
+        // PIN is written into quoted command, anything else than digits could end it
+        if let Some(pin) = pin {
+            if !(4..=8).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+                return Err(SmsError::ModemError(
+                    "PIN must have from 4 to 8 digits".to_string(),
+                ));
+            }
+        }
+        let state = response_fields(&self.command("AT+CPIN?").await?, "+CPIN:").remove(0);
+        let lock = match state.as_str() {
+            "READY" => return Ok(()),
+            "SIM PIN" => SimLock::PinRequired,
+            "SIM PUK" => SimLock::PukRequired,
+            // Phone and network locks have their own codes, SIM PIN must not be tried there
+            _ => {
+                return Err(SmsError::ModemError(format!(
+                    "SIM is in unsupported state '{}'",
+                    state
+                )))
+            }
+        };
+        match (lock, pin) {
+            (SimLock::PinRequired, Some(pin)) => self
+                .command(&format!("AT+CPIN=\"{}\"", pin))
+                .await
+                .map(|_| ()),
+            (lock, _) => Err(SmsError::SimLocked(lock)),
+        }
+    }
+
+    // Sends PDU and returns message reference assigned by modem
+    pub async fn send_pdu(&mut self, pdu: &EncodedPdu) -> Result<Option<u8>, SmsError> {
+        let command = format!("AT+CMGS={}", pdu.tpdu_length);
+        self.write(format!("{}\r", command).as_bytes()).await?;
+        self.read_prompt(&command).await?;
+        let mut body = pdu.hex.clone().into_bytes();
+        body.push(CTRL_Z);
+        self.write(&body).await?;
+        let lines = self.read_response(&command).await?;
+        Ok(lines
+            .iter()
+            .find_map(|line| line.strip_prefix("+CMGS:"))
+            .and_then(|reference| reference.trim().parse().ok()))
+    }
+
+    // Network reply comes as unsolicited `+CUSD` line, usually after `OK` of the request
+    pub async fn ussd(&mut self, code: &str) -> Result<String, SmsError> {
+        // Quote or line end in the code would start another AT command
+        if code.is_empty()
+            || !code
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '*' || c == '#')
+        {
+            return Err(SmsError::ModemError(format!(
+                "Invalid USSD code '{}', only digits, * and # are allowed",
+                code
+            )));
+        }
+        let command = format!("AT+CUSD=1,\"{}\",{}", code, USSD_DCS_GSM7);
+        let mut lines = self.command(&command).await?;
+        let reply = loop {
+            if let Some(reply) = lines.iter().find_map(|line| line.strip_prefix("+CUSD:")) {
+                break split_fields(reply.trim());
+            }
+            let line = self.read_line().await?;
+            if is_error(&line) {
+                return Err(command_error(&command, &line));
+            }
+            lines = vec![line];
+        };
+        match reply[0].as_str() {
+            USSD_DONE => {}
+            USSD_ACTION_REQUIRED => {
+                self.command("AT+CUSD=2").await?;
+            }
+            result => {
+                return Err(SmsError::ModemError(format!(
+                    "USSD request failed with result {}",
+                    result
+                )))
+            }
+        }
+        let text = reply.get(1).cloned().unwrap_or_default();
+        match reply.get(2).and_then(|dcs| dcs.parse().ok()) {
+            Some(USSD_DCS_UCS2) => decode_ucs2_hex(&text),
+            _ => Ok(text),
+        }
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), SmsError> {
+        self.port
+            .write_all(bytes)
+            .await
+            .map_err(|e| SmsError::NetworkError(format!("Could not write to modem, {}", e)))
+    }
+
+    async fn read_response(&mut self, command: &str) -> Result<Vec<String>, SmsError> {
+        let mut lines = vec![];
+        loop {
+            let line = self.read_line().await?;
+            match line.as_str() {
+                "OK" => return Ok(lines),
+                line if is_error(line) => return Err(command_error(command, line)),
+                line if line == command => {}
+                _ => lines.push(line),
+            }
+        }
+    }
+
+    // Modem asks for message text with `> ` that is not followed by new line
+    async fn read_prompt(&mut self, command: &str) -> Result<(), SmsError> {
+        loop {
+            let start = self
+                .buffer
+                .iter()
+                .position(|b| !matches!(b, b'\r' | b'\n'))
+                .unwrap_or(self.buffer.len());
+            self.buffer.drain(..start);
+            if self.buffer.starts_with(b">") {
+                let end = if self.buffer.starts_with(b"> ") { 2 } else { 1 };
+                self.buffer.drain(..end);
+                return Ok(());
+            }
+            if self.buffer.contains(&b'\n') {
+                let line = self.read_line().await?;
+                if is_error(&line) {
+                    return Err(command_error(command, &line));
+                }
+                continue;
+            }
+            self.fill_buffer().await?;
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String, SmsError> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim_end_matches(['\r', '\n']);
+                if line.is_empty() {
+                    continue;
+                }
+                return Ok(line.to_string());
+            }
+            self.fill_buffer().await?;
+        }
+    }
+
+    async fn fill_buffer(&mut self) -> Result<(), SmsError> {
+        let mut chunk = [0u8; 256];
+        let read = tokio::time::timeout(RESPONSE_TIMEOUT, self.port.read(&mut chunk))
+            .await
+            .map_err(|_| SmsError::NetworkError("Modem did not respond in time".to_string()))?
+            .map_err(|e| SmsError::NetworkError(format!("Could not read from modem, {}", e)))?;
+        if read == 0 {
+            return Err(SmsError::NetworkError(
+                "Modem closed connection".to_string(),
+            ));
+        }
+        self.buffer.extend_from_slice(&chunk[..read]);
+        Ok(())
+    }
+}
+
+fn is_error(line: &str) -> bool {
+    line == "ERROR" || line.starts_with("+CMS ERROR") || line.starts_with("+CME ERROR")
+}
+
+// Busy SIM and network timeout are temporary, so such commands can be repeated
+fn command_error(command: &str, line: &str) -> SmsError {
+    let message = format!("Command {} failed with {}", command, line);
+    match line {
+        CME_SIM_BUSY | CMS_SIM_BUSY | CMS_NETWORK_TIMEOUT => SmsError::ModemBusy(message),
+        _ => SmsError::ModemError(message),
+    }
+}
+
+// Sender, reference and total number of parts identify concatenated message
+type ConcatenationKey = (String, u16, u8);
+
+struct ListedMessage {
+    id: i64,
+    unread: bool,
+    pdu: ReceivedPdu,
+}
+
+struct MessagePart {
+    id: i64,
+    unread: bool,
+    pdu: DeliverPdu,
+}
+
+// `+CMGL: 1,0,,33` followed by line with hex encoded PDU
+fn parse_message_list(
+    lines: &[String],
+) -> Result<(Vec<ListedMessage>, Vec<UndecodableSms>), SmsError> {
+    let mut messages = vec![];
+    let mut undecodable = vec![];
+    let mut lines = lines.iter();
+    while let Some(line) = lines.next() {
+        let Some(header) = line.strip_prefix("+CMGL:") else {
+            continue;
+        };
+        let fields: Vec<&str> = header.trim().split(',').collect();
+        let status = fields.get(1).copied().unwrap_or_default();
+        let pdu = lines
+            .next()
+            .ok_or_else(|| SmsError::ModemError(format!("Missing PDU of message '{}'", line)))?;
+        // Sent and stored messages are skipped
+        if status != STATUS_UNREAD && status != STATUS_READ {
+            continue;
+        }
+        let id = fields[0]
+            .parse()
+            .map_err(|_| SmsError::ModemError(format!("Invalid message index in '{}'", line)))?;
+        // One broken message must not hide the rest of inbox
+        match pdu::decode_received(pdu) {
+            Ok(pdu) => messages.push(ListedMessage {
+                id,
+                unread: status == STATUS_UNREAD,
+                pdu,
+            }),
+            Err(e) => undecodable.push(UndecodableSms {
+                id,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    Ok((messages, undecodable))
+}
+
+fn delivery_report(id: i64, report: StatusReportPdu) -> DeliveryReport {
+    let status = match report.status {
+        0..=REPORT_DELIVERED => DeliveryStatus::Delivered,
+        0x20..=REPORT_STILL_TRYING => DeliveryStatus::Pending,
+        _ => DeliveryStatus::Failed,
+    };
+    DeliveryReport {
+        id,
+        phone: report.recipient,
+        status,
+        time: report.discharge_time.with_timezone(&Local).naive_local(),
+        provider: None,
+        reference: Some(report.reference),
+    }
+}
+
+// Parts of concatenated message are joined in order of their sequence number, whole message
+// gets index of its first part. Parts that did not arrive yet are skipped.
+fn join_parts(parts: Vec<MessagePart>) -> Vec<ReceivedSms> {
+    let mut messages: Vec<(Option<ConcatenationKey>, Vec<MessagePart>)> = vec![];
+    for part in parts {
+        let key = part
+            .pdu
+            .concatenation
+            .as_ref()
+            .map(|c| (part.pdu.sender.clone(), c.reference, c.total));
+        match messages
+            .iter_mut()
+            .find(|(k, _)| key.is_some() && *k == key)
+        {
+            Some((_, message_parts)) => message_parts.push(part),
+            None => messages.push((key, vec![part])),
+        }
+    }
+    messages
+        .into_iter()
+        .map(|(_, mut parts)| {
+            parts.sort_by_key(|p| p.pdu.concatenation.as_ref().map(|c| c.sequence));
+            let first = &parts[0];
+            ReceivedSms {
+                id: first.id,
+                phone: first.pdu.sender.clone(),
+                text: parts.iter().map(|p| p.pdu.text.as_str()).collect(),
+                time: first.pdu.timestamp.with_timezone(&Local).naive_local(),
+                unread: parts.iter().any(|p| p.unread),
+            }
+        })
+        .collect()
+}
+
+// Fields of response line like `+COPS: 0,0,"Orange PL",7`, always at least one
+fn response_fields(lines: &[String], prefix: &str) -> Vec<String> {
+    lines
+        .iter()
+        .find_map(|line| line.strip_prefix(prefix))
+        .map(|value| split_fields(value.trim()))
+        .unwrap_or_else(|| vec![String::new()])
+}
+
+// Splits by commas outside of quotes and removes quotes
+fn split_fields(value: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+fn access_technology_name(access_technology: Option<&str>) -> &'static str {
+    match access_technology {
+        None => "No service",
+        Some("0") | Some("1") => "GSM",
+        Some("2") => "UMTS",
+        Some("3") => "EDGE",
+        Some("4") => "HSDPA",
+        Some("5") => "HSUPA",
+        Some("6") => "HSPA",
+        Some("7") => "LTE",
+        Some(_) => "Unknown",
+    }
+}
+
+fn decode_ucs2_hex(hex: &str) -> Result<String, SmsError> {
+    let units = (0..hex.len())
+        .step_by(4)
+        .map(|i| {
+            hex.get(i..i + 4)
+                .and_then(|unit| u16::from_str_radix(unit, 16).ok())
+                .ok_or_else(|| SmsError::ModemError(format!("Invalid UCS2 USSD reply {}", hex)))
+        })
+        .collect::<Result<Vec<u16>, SmsError>>()?;
+    String::from_utf16(&units)
+        .map_err(|_| SmsError::ModemError(format!("Invalid UCS2 USSD reply {}", hex)))
+}
+
+// `AT+CSQ` reports RSSI from 0 to 31, 99 means unknown
+fn signal_bars(rssi: u8) -> u8 {
+    match rssi {
+        2..=9 => 1,
+        10..=14 => 2,
+        15..=19 => 3,
+        20..=24 => 4,
+        25..=31 => 5,
+        _ => 0,
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt::Display;
+
+pub const GSM7_SINGLE_LIMIT: usize = 160;
+pub const GSM7_MULTIPART_LIMIT: usize = 153;
+pub const UCS2_SINGLE_LIMIT: usize = 70;
+pub const UCS2_MULTIPART_LIMIT: usize = 67;
+
+pub const GSM7_ESCAPE: u8 = 0x1B;
+
+// GSM 03.38 default alphabet, index is the septet value. 0x1B is escape to extension table.
+const GSM7_BASIC: [char; 128] = [
+    '@', '£', '$', '¥', 'è', 'é', 'ù', 'ì', 'ò', 'Ç', '\n', 'Ø', 'ø', '\r', 'Å', 'å', 'Δ', '_',
+    'Φ', 'Γ', 'Λ', 'Ω', 'Π', 'Ψ', 'Σ', 'Θ', 'Ξ', '\x1b', 'Æ', 'æ', 'ß', 'É', ' ', '!', '"', '#',
+    '¤', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/', '0', '1', '2', '3', '4', '5', '6',
+    '7', '8', '9', ':', ';', '<', '=', '>', '?', '¡', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I',
+    'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'Ä', 'Ö',
+    'Ñ', 'Ü', '§', '¿', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'ä', 'ö', 'ñ', 'ü', 'à',
+];
+
+const GSM7_EXTENSION: [(char, u8); 10] = [
+    ('\x0c', 0x0A),
+    ('^', 0x14),
+    ('{', 0x28),
+    ('}', 0x29),
+    ('\\', 0x2F),
+    ('[', 0x3C),
+    ('~', 0x3D),
+    (']', 0x3E),
+    ('|', 0x40),
+    ('€', 0x65),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsEncoding {
+    Gsm7,
+    Ucs2,
+}
+
+impl Display for SmsEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SmsEncoding::Gsm7 => write!(f, "GSM-7"),
+            SmsEncoding::Ucs2 => write!(f, "UCS-2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsParts {
+    pub encoding: SmsEncoding,
+    pub segments: usize,
+    // Septets for GSM-7, UTF-16 code units for UCS-2
+    pub units: usize,
+    // Characters that are not in GSM-7 alphabet, in order of appearance
+    pub ucs2_characters: Vec<char>,
+}
+
+pub fn gsm7_code(c: char) -> Option<u8> {
+    if c == '\x1b' {
+        return None;
+    }
+    GSM7_BASIC
+        .iter()
+        .position(|b| *b == c)
+        .map(|code| code as u8)
+}
+
+pub fn gsm7_extension_code(c: char) -> Option<u8> {
+    GSM7_EXTENSION
+        .iter()
+        .find(|(extension, _)| *extension == c)
+        .map(|(_, code)| *code)
+}
+
+pub fn gsm7_char(code: u8) -> Option<char> {
+    GSM7_BASIC
+        .get(code as usize)
+        .copied()
+        .filter(|_| code != GSM7_ESCAPE)
+}
+
+pub fn gsm7_extension_char(code: u8) -> Option<char> {
+    GSM7_EXTENSION
+        .iter()
+        .find(|(_, extension)| *extension == code)
+        .map(|(c, _)| *c)
+}
+
+pub fn is_gsm7(c: char) -> bool {
+    gsm7_code(c).is_some() || gsm7_extension_code(c).is_some()
+}
+
+pub fn analyze(text: &str) -> SmsParts {
+    let mut ucs2_characters: Vec<char> = vec![];
+    for c in text.chars().filter(|c| !is_gsm7(*c)) {
+        if !ucs2_characters.contains(&c) {
+            ucs2_characters.push(c);
+        }
+    }
+    let (encoding, costs, single_limit, multipart_limit): (_, Vec<usize>, _, _) =
+        if ucs2_characters.is_empty() {
+            (
+                SmsEncoding::Gsm7,
+                text.chars()
+                    .map(|c| if gsm7_code(c).is_some() { 1 } else { 2 })
+                    .collect(),
+                GSM7_SINGLE_LIMIT,
+                GSM7_MULTIPART_LIMIT,
+            )
+        } else {
+            (
+                SmsEncoding::Ucs2,
+                text.chars().map(char::len_utf16).collect(),
+                UCS2_SINGLE_LIMIT,
+                UCS2_MULTIPART_LIMIT,
+            )
+        };
+    let units = costs.iter().sum();
+    let segments = if units <= single_limit {
+        1
+    } else {
+        count_segments(&costs, multipart_limit)
+    };
+    SmsParts {
+        encoding,
+        segments,
+        units,
+        ucs2_characters,
+    }
+}
+
+// Escape sequences and surrogate pairs can't be split between parts,
+// so character that doesn't fit starts a new part.
+fn count_segments(costs: &[usize], limit: usize) -> usize {
+    let mut segments = 1;
+    let mut used = 0;
+    for cost in costs {
+        if used + cost > limit {
+            segments += 1;
+            used = 0;
+        }
+        used += cost;
+    }
+    segments
+}
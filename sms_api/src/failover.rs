@@ -0,0 +1,168 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    DeliveryReport, Inbox, ModemStatus, RecipientStatus, SendReport, SmsError, SmsService,
+};
+
+// Rate limited sends come one recipient at a time, health is not checked for each of them
+const HEALTH_CHECK_TTL: Duration = Duration::from_secs(30);
+
+struct Provider {
+    description: String,
+    service: Box<dyn SmsService>,
+    checked: Mutex<Option<(Instant, bool)>>,
+}
+
+impl Provider {
+    // Provider which doesn't answer status request is skipped
+    async fn is_healthy(&self) -> bool {
+        if let Some((at, healthy)) = *self.checked.lock().unwrap() {
+            if at.elapsed() < HEALTH_CHECK_TTL {
+                return healthy;
+            }
+        }
+        let healthy = self.service.status().await.is_ok();
+        self.set_health(healthy);
+        healthy
+    }
+
+    fn set_health(&self, healthy: bool) {
+        *self.checked.lock().unwrap() = Some((Instant::now(), healthy));
+    }
+}
+
+// Ordered providers, the first one is primary
+pub(crate) struct FailoverSmsService {
+    providers: Vec<Provider>,
+}
+
+impl FailoverSmsService {
+    pub fn new(providers: Vec<(String, Box<dyn SmsService>)>) -> Self {
+        Self {
+            providers: providers
+                .into_iter()
+                .map(|(description, service)| Provider {
+                    description,
+                    service,
+                    checked: Mutex::new(None),
+                })
+                .collect(),
+        }
+    }
+
+    fn primary(&self) -> &dyn SmsService {
+        self.providers[0].service.as_ref()
+    }
+}
+
+#[async_trait]
+impl SmsService for FailoverSmsService {
+    // The next provider gets only recipients failed with retryable error
+    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError> {
+        let mut report: Option<SendReport> = None;
+        let mut last_error = None;
+        for provider in &self.providers {
+            let pending = match &report {
+                Some(report) => report.retryable(),
+                None => (0..phone_numbers.len()).collect(),
+            };
+            if pending.is_empty() {
+                break;
+            }
+            if !provider.is_healthy().await {
+                continue;
+            }
+            let phones: Vec<&str> = pending.iter().map(|index| phone_numbers[*index]).collect();
+            match provider.service.send_sms(msg, &phones).await {
+                Ok(mut sent) => {
+                    if !sent.retryable().is_empty() {
+                        provider.set_health(false);
+                    }
+                    for result in sent
+                        .recipients
+                        .iter_mut()
+                        .filter(|r| !matches!(r.status, RecipientStatus::Skipped))
+                    {
+                        result.provider = Some(provider.description.clone());
+                    }
+                    match report.as_mut() {
+                        Some(report) => report.replace(&pending, sent),
+                        None => report = Some(sent),
+                    }
+                }
+                Err(e) if e.is_retryable() => {
+                    provider.set_health(false);
+                    last_error = Some(e);
+                }
+                Err(e) => match report {
+                    Some(_) => break,
+                    None => return Err(e),
+                },
+            }
+        }
+        report.ok_or_else(|| {
+            last_error.unwrap_or_else(|| {
+                SmsError::NetworkError("None of providers is reachable".into())
+            })
+        })
+    }
+
+    // Inbox, USSD and PIN belong to SIM of primary provider
+    async fn read_inbox(&self) -> Result<Inbox, SmsError> {
+        self.primary().read_inbox().await
+    }
+
+    async fn delete_sms(&self, id: i64) -> Result<(), SmsError> {
+        self.primary().delete_sms(id).await
+    }
+
+    // Reports come to SIM which sent the message, unreachable provider is left for next read
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError> {
+        let mut reports = vec![];
+        let mut errors = vec![];
+        for provider in &self.providers {
+            match provider.service.read_delivery_reports().await {
+                Ok(read) => reports.extend(read.into_iter().map(|report| DeliveryReport {
+                    provider: Some(provider.description.clone()),
+                    ..report
+                })),
+                Err(e) => errors.push(e),
+            }
+        }
+        if errors.len() == self.providers.len() {
+            return Err(errors.remove(0));
+        }
+        Ok(reports)
+    }
+
+    async fn delete_delivery_report(&self, report: &DeliveryReport) -> Result<(), SmsError> {
+        let provider = self
+            .providers
+            .iter()
+            .find(|p| report.provider.as_deref() == Some(p.description.as_str()))
+            .ok_or_else(|| {
+                SmsError::UnknownError(format!(
+                    "Delivery report {} doesn't belong to any provider",
+                    report.id
+                ))
+            })?;
+        provider.service.delete_sms(report.id).await
+    }
+
+    async fn status(&self) -> Result<ModemStatus, SmsError> {
+        self.primary().status().await
+    }
+
+    async fn unlock(&self, pin: &str) -> Result<(), SmsError> {
+        self.primary().unlock(pin).await
+    }
+
+    async fn send_ussd(&self, code: &str) -> Result<String, SmsError> {
+        self.primary().send_ussd(code).await
+    }
+}
@@ -0,0 +1,643 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::NaiveDateTime;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    DeliveryReport, Inbox, ModemStatus, ReceivedSms, SendReport, SimLock, SmsError, SmsService,
+    SmsStorage, MAX_SIGNAL_BARS,
+};
+
+const SMS_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const SMS_STAT_UNREAD: i64 = 0;
+const ROAMING: i64 = 1;
+const SIM_STATE_NO_SIM: i64 = 255;
+const SIM_STATE_READY: i64 = 257;
+const SIM_STATE_PIN_REQUIRED: i64 = 260;
+const SIM_STATE_PUK_REQUIRED: i64 = 261;
+const PIN_DISABLED: i64 = 258;
+const PIN_OPERATION_VERIFY: i64 = 0;
+const BOX_TYPE_INBOX: i64 = 1;
+const PAGE_SIZE: i64 = 50;
+const SEND_STATUS_CHECKS: usize = 10;
+const SEND_STATUS_DELAY: Duration = Duration::from_millis(500);
+// Password is sent as base64(sha256(username + base64(sha256(password)) + token))
+const PASSWORD_TYPE_SHA256: i64 = 4;
+const TOKEN_HEADER: &str = "__RequestVerificationToken";
+const ERROR_SYSTEM_BUSY: i64 = 100004;
+const LOGIN_TOKEN_HEADER: &str = "__RequestVerificationTokenone";
+
+pub(crate) struct HuaweiHilinkSmsService {
+    url: String,
+    credentials: Option<(String, String)>,
+    pin: Option<String>,
+    client: Client,
+}
+
+// Web interface of HiLink sticks requires session cookie and CSRF token on every request,
+// some firmwares rotate the token after each POST.
+struct Session {
+    cookie: String,
+    token: String,
+}
+
+#[async_trait]
+impl SmsService for HuaweiHilinkSmsService {
+    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError> {
+        let mut session = self.open_session().await?;
+        let mut report = SendReport::default();
+        for phone in phone_numbers {
+            if report.should_skip() {
+                report.skip(phone);
+                continue;
+            }
+            let result = self.send_single_sms(&mut session, msg, phone).await;
+            report.add(phone, result);
+        }
+        Ok(report)
+    }
+
+    async fn read_inbox(&self) -> Result<Inbox, SmsError> {
+        let mut session = self.open_session().await?;
+        let mut inbox = vec![];
+        let mut page = 1;
+        loop {
+            let result: SmsListResponse = self
+                .post(
+                    &mut session,
+                    "/api/sms/sms-list",
+                    &SmsListRequest::new(page),
+                )
+                .await?;
+            let received = result.messages.message.len();
+            for sms in result.messages.message {
+                inbox.push(ReceivedSms {
+                    id: sms.index,
+                    phone: sms.phone,
+                    text: sms.content,
+                    time: parse_sms_time(&sms.date)?,
+                    unread: sms.smstat == SMS_STAT_UNREAD,
+                });
+            }
+            if received == 0 || inbox.len() as i64 >= result.count {
+                break;
+            }
+            page += 1;
+        }
+        inbox.sort_by_key(|sms| std::cmp::Reverse(sms.time));
+        Ok(Inbox {
+            messages: inbox,
+            undecodable: vec![],
+        })
+    }
+
+    async fn delete_sms(&self, id: i64) -> Result<(), SmsError> {
+        let mut session = self.open_session().await?;
+        self.post::<_, String>(
+            &mut session,
+            "/api/sms/delete-sms",
+            &DeleteSmsRequest { index: id },
+        )
+        .await
+        .map(|_| ())
+    }
+
+    // HiLink API does not expose status reports, messages stay pending
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError> {
+        Ok(vec![])
+    }
+
+    async fn status(&self) -> Result<ModemStatus, SmsError> {
+        let session = self.open_session().await?;
+        let status: MonitoringStatus = self.get(&session, "/api/monitoring/status").await?;
+        let plmn: CurrentPlmn = self.get(&session, "/api/net/current-plmn").await?;
+        let pin: PinStatus = self.get(&session, "/api/pin/status").await?;
+        let sms_count: SmsCount = self.get(&session, "/api/sms/sms-count").await?;
+        let (sim_state, pin_state) = match pin.sim_state {
+            SIM_STATE_NO_SIM => ("No SIM", "Unknown"),
+            SIM_STATE_READY if pin.pin_opt_state == PIN_DISABLED => ("Ready", "Disabled"),
+            SIM_STATE_READY => ("Ready", "Enabled, verified"),
+            SIM_STATE_PIN_REQUIRED => ("PIN required", "Enabled, not verified"),
+            SIM_STATE_PUK_REQUIRED => ("PUK required", "Blocked"),
+            _ => ("Unknown", "Unknown"),
+        };
+        Ok(ModemStatus {
+            operator: plmn.full_name,
+            network_type: network_type_name(status.current_network_type).to_string(),
+            signal_bars: status.signal_icon.clamp(0, MAX_SIGNAL_BARS as i64) as u8,
+            roaming: status.roaming_status == ROAMING,
+            sim_state: sim_state.to_string(),
+            pin_state: pin_state.to_string(),
+            sms_storage: Some(SmsStorage {
+                used: sms_count.local_inbox + sms_count.local_outbox + sms_count.local_draft,
+                capacity: sms_count.local_max,
+            }),
+        })
+    }
+
+    async fn unlock(&self, pin: &str) -> Result<(), SmsError> {
+        let mut session = self.open_session().await?;
+        match self.sim_lock(&session).await? {
+            None => Ok(()),
+            Some(SimLock::PinRequired) => self.verify_pin(&mut session, pin).await,
+            Some(lock) => Err(SmsError::SimLocked(lock)),
+        }
+    }
+
+    async fn send_ussd(&self, _code: &str) -> Result<String, SmsError> {
+        Err(SmsError::ModemError(
+            "USSD is not supported by HuaweiHilink provider".to_string(),
+        ))
+    }
+}
+
+impl HuaweiHilinkSmsService {
+    pub fn new(
+        url: &str,
+        username: Option<String>,
+        password: Option<String>,
+        pin: Option<String>,
+    ) -> Result<Self, SmsError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| SmsError::UnknownError(e.to_string()))?;
+        Ok(Self {
+            url: url.to_string(),
+            credentials: username.zip(password),
+            pin,
+            client,
+        })
+    }
+
+    async fn open_session(&self) -> Result<Session, SmsError> {
+        let response = self
+            .client
+            .get(format!("{}/api/webserver/SesTokInfo", self.url))
+            .send()
+            .await
+            .map_err(|e| SmsError::NetworkError(e.to_string()))?;
+        let body = self
+            .ensure_status_is_success(response)
+            .await?
+            .text()
+            .await?;
+        let token_info: SesTokInfo = parse_response("SesTokInfo", &body)?;
+        let mut session = Session {
+            cookie: token_info.ses_info,
+            token: token_info.tok_info,
+        };
+        if let Some((username, password)) = &self.credentials {
+            self.login(&mut session, username, password).await?;
+        }
+        Ok(session)
+    }
+
+    async fn login(
+        &self,
+        session: &mut Session,
+        username: &str,
+        password: &str,
+    ) -> Result<(), SmsError> {
+        let hashed_password = STANDARD.encode(sha256_hex(password));
+        let request = LoginRequest {
+            username: username.to_string(),
+            password: STANDARD.encode(sha256_hex(&format!(
+                "{}{}{}",
+                username, hashed_password, session.token
+            ))),
+            password_type: PASSWORD_TYPE_SHA256,
+        };
+        let response = self
+            .with_session(
+                self.client.post(format!("{}/api/user/login", self.url)),
+                session,
+            )
+            .body(to_xml(&request)?)
+            .send()
+            .await
+            .map_err(|e| SmsError::NetworkError(e.to_string()))?;
+        let response = self.ensure_status_is_success(response).await?;
+        // Successful login starts new session with its own cookie and token
+        if let Some(cookie) = header_value(&response, "set-cookie") {
+            session.cookie = cookie.split(';').next().unwrap_or_default().to_string();
+        }
+        if let Some(token) = header_value(&response, LOGIN_TOKEN_HEADER) {
+            session.token = token;
+        }
+        let body = response.text().await?;
+        parse_response::<String>("login", &body).map(|_| ())
+    }
+
+    // Stick with locked SIM fails to send without telling why, so SIM is checked only after failure
+    async fn send_single_sms(
+        &self,
+        session: &mut Session,
+        msg: &str,
+        phone: &str,
+    ) -> Result<(), SmsError> {
+        let Err(error) = self.try_send_single_sms(session, msg, phone).await else {
+            return Ok(());
+        };
+        match (self.sim_lock(session).await, &self.pin) {
+            (Ok(Some(SimLock::PinRequired)), Some(pin)) => {
+                self.verify_pin(session, pin).await?;
+                self.try_send_single_sms(session, msg, phone).await
+            }
+            (Ok(Some(lock)), _) => Err(SmsError::SimLocked(lock)),
+            _ => Err(error),
+        }
+    }
+
+    async fn try_send_single_sms(
+        &self,
+        session: &mut Session,
+        msg: &str,
+        phone: &str,
+    ) -> Result<(), SmsError> {
+        let request = SendSmsRequest::new(msg, phone);
+        self.post::<_, String>(session, "/api/sms/send-sms", &request)
+            .await?;
+        self.wait_until_sent(session, phone).await
+    }
+
+    async fn sim_lock(&self, session: &Session) -> Result<Option<SimLock>, SmsError> {
+        let pin: PinStatus = self.get(session, "/api/pin/status").await?;
+        Ok(match pin.sim_state {
+            SIM_STATE_PIN_REQUIRED => Some(SimLock::PinRequired),
+            SIM_STATE_PUK_REQUIRED => Some(SimLock::PukRequired),
+            _ => None,
+        })
+    }
+
+    async fn verify_pin(&self, session: &mut Session, pin: &str) -> Result<(), SmsError> {
+        let request = PinOperateRequest {
+            operate_type: PIN_OPERATION_VERIFY,
+            current_pin: pin.to_string(),
+            new_pin: String::new(),
+            puk_code: String::new(),
+        };
+        self.post::<_, String>(session, "/api/pin/operate", &request)
+            .await
+            .map(|_| ())
+    }
+
+    async fn wait_until_sent(&self, session: &Session, phone: &str) -> Result<(), SmsError> {
+        for _ in 0..SEND_STATUS_CHECKS {
+            // Stick already queued the sms, so failed check can't be answered by sending again
+            let status: SendStatusResponse = self
+                .get(session, "/api/sms/send-status")
+                .await
+                .map_err(SmsError::into_unconfirmed)?;
+            if contains_phone(&status.fail_phone, phone) {
+                return Err(SmsError::UnknownError(format!(
+                    "Modem failed to send sms to {}",
+                    phone
+                )));
+            }
+            if contains_phone(&status.suc_phone, phone) {
+                return Ok(());
+            }
+            tokio::time::sleep(SEND_STATUS_DELAY).await;
+        }
+        Err(SmsError::UnknownError(
+            "Service didn't confirmed successful send".into(),
+        ))
+    }
+
+    async fn get<R: DeserializeOwned>(&self, session: &Session, path: &str) -> Result<R, SmsError> {
+        let response = self
+            .with_session(self.client.get(format!("{}{}", self.url, path)), session)
+            .send()
+            .await
+            .map_err(|e| SmsError::NetworkError(e.to_string()))?;
+        let body = self
+            .ensure_status_is_success(response)
+            .await?
+            .text()
+            .await?;
+        parse_response(path, &body)
+    }
+
+    async fn post<P, R>(
+        &self,
+        session: &mut Session,
+        path: &str,
+        request: &P,
+    ) -> Result<R, SmsError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .with_session(self.client.post(format!("{}{}", self.url, path)), session)
+            .body(to_xml(request)?)
+            .send()
+            .await
+            .map_err(|e| SmsError::NetworkError(e.to_string()))?;
+        let response = self.ensure_status_is_success(response).await?;
+        if let Some(token) = header_value(&response, TOKEN_HEADER) {
+            session.token = token.split('#').next().unwrap_or_default().to_string();
+        }
+        let body = response.text().await?;
+        parse_response(path, &body)
+    }
+
+    fn with_session(&self, request: RequestBuilder, session: &Session) -> RequestBuilder {
+        request
+            .header("Cookie", &session.cookie)
+            .header(TOKEN_HEADER, &session.token)
+            .header(
+                "Content-Type",
+                "application/x-www-form-urlencoded; charset=UTF-8",
+            )
+    }
+
+    async fn ensure_status_is_success(&self, response: Response) -> Result<Response, SmsError> {
+        let status = response.status();
+        match status {
+            StatusCode::OK => Ok(response),
+            StatusCode::NOT_FOUND => Err(SmsError::InvalidResponse(
+                status,
+                format!(
+                    "We could not find service under url {}, make sure usb modem is connected and service is running",
+                    self.url
+                ),
+            )),
+            _ if status.is_server_error() => Err(SmsError::InvalidResponse(
+                status,
+                "Unexpected status code".to_string(),
+            )),
+            _ => Err(SmsError::UnknownError(format!(
+                "Unexpected status code: {}",
+                status
+            ))),
+        }
+    }
+}
+
+// HiLink API answers with status 200 and `<error>` document when request fails
+fn parse_response<R: DeserializeOwned>(api: &str, body: &str) -> Result<R, SmsError> {
+    if body.contains("<error>") {
+        let error: HilinkError = quick_xml::de::from_str(body)
+            .map_err(|e| SmsError::UnknownError(format!("Invalid {} error: {}", api, e)))?;
+        let message = format!(
+            "{} failed with code {}: {}",
+            api,
+            error.code,
+            describe_error(error.code, &error.message)
+        );
+        if error.code == ERROR_SYSTEM_BUSY {
+            return Err(SmsError::ModemBusy(message));
+        }
+        return Err(SmsError::UnknownError(message));
+    }
+    quick_xml::de::from_str(body)
+        .map_err(|e| SmsError::UnknownError(format!("Invalid {} response: {}", api, e)))
+}
+
+fn describe_error(code: i64, message: &str) -> String {
+    match code {
+        100003 => "login is required".to_string(),
+        108006 | 108007 => "wrong username or password".to_string(),
+        103002 => "wrong PIN".to_string(),
+        ERROR_SYSTEM_BUSY => "system busy".to_string(),
+        113004 => "sms could not be sent".to_string(),
+        125002 | 125003 => "session or token expired".to_string(),
+        _ => message.to_string(),
+    }
+}
+
+fn network_type_name(network_type: i64) -> &'static str {
+    match network_type {
+        0 => "No service",
+        1 => "GSM",
+        2 => "GPRS",
+        3 => "EDGE",
+        4 => "WCDMA",
+        5 => "HSDPA",
+        6 => "HSUPA",
+        7 => "HSPA",
+        8 => "TD-SCDMA",
+        9 => "HSPA+",
+        19 | 101 => "LTE",
+        _ => "Unknown",
+    }
+}
+
+fn to_xml<P: Serialize>(request: &P) -> Result<String, SmsError> {
+    quick_xml::se::to_string_with_root("request", request)
+        .map(|xml| format!(r#"<?xml version="1.0" encoding="UTF-8"?>{}"#, xml))
+        .map_err(|e| SmsError::UnknownError(format!("Could not build request: {}", e)))
+}
+
+fn header_value(response: &Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn contains_phone(phones: &str, phone: &str) -> bool {
+    phones.split([',', ';']).any(|p| p.trim() == phone)
+}
+
+fn sha256_hex(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn parse_sms_time(time: &str) -> Result<NaiveDateTime, SmsError> {
+    NaiveDateTime::parse_from_str(time, SMS_TIME_FORMAT)
+        .map_err(|e| SmsError::UnknownError(format!("Invalid sms time '{}': {}", time, e)))
+}
+
+#[derive(Deserialize, Debug)]
+struct SesTokInfo {
+    #[serde(rename = "SesInfo")]
+    ses_info: String,
+    #[serde(rename = "TokInfo")]
+    tok_info: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct HilinkError {
+    code: i64,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Serialize, Debug)]
+struct LoginRequest {
+    #[serde(rename = "Username")]
+    username: String,
+    #[serde(rename = "Password")]
+    password: String,
+    password_type: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct SendSmsRequest {
+    #[serde(rename = "Index")]
+    index: i64,
+    #[serde(rename = "Phones")]
+    phones: Phones,
+    #[serde(rename = "Sca")]
+    sca: String,
+    #[serde(rename = "Content")]
+    content: String,
+    #[serde(rename = "Length")]
+    length: usize,
+    #[serde(rename = "Reserved")]
+    reserved: i64,
+    #[serde(rename = "Date")]
+    date: String,
+}
+
+impl SendSmsRequest {
+    fn new(msg: &str, phone: &str) -> Self {
+        SendSmsRequest {
+            index: -1,
+            phones: Phones {
+                phone: vec![phone.to_string()],
+            },
+            sca: String::new(),
+            content: msg.to_string(),
+            length: msg.chars().count(),
+            reserved: 1,
+            date: chrono::Local::now().format(SMS_TIME_FORMAT).to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct Phones {
+    #[serde(rename = "Phone")]
+    phone: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SendStatusResponse {
+    #[serde(rename = "SucPhone", default)]
+    suc_phone: String,
+    #[serde(rename = "FailPhone", default)]
+    fail_phone: String,
+}
+
+#[derive(Serialize, Debug)]
+struct SmsListRequest {
+    #[serde(rename = "PageIndex")]
+    page_index: i64,
+    #[serde(rename = "ReadCount")]
+    read_count: i64,
+    #[serde(rename = "BoxType")]
+    box_type: i64,
+    #[serde(rename = "SortType")]
+    sort_type: i64,
+    #[serde(rename = "Ascending")]
+    ascending: i64,
+    #[serde(rename = "UnreadPreferred")]
+    unread_preferred: i64,
+}
+
+impl SmsListRequest {
+    fn new(page_index: i64) -> Self {
+        SmsListRequest {
+            page_index,
+            read_count: PAGE_SIZE,
+            box_type: BOX_TYPE_INBOX,
+            sort_type: 0,
+            ascending: 0,
+            unread_preferred: 0,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsListResponse {
+    #[serde(rename = "Count")]
+    count: i64,
+    #[serde(rename = "Messages", default)]
+    messages: SmsMessages,
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct SmsMessages {
+    #[serde(rename = "Message", default)]
+    message: Vec<SmsMessage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsMessage {
+    #[serde(rename = "Smstat")]
+    smstat: i64,
+    #[serde(rename = "Index")]
+    index: i64,
+    #[serde(rename = "Phone", default)]
+    phone: String,
+    #[serde(rename = "Content", default)]
+    content: String,
+    #[serde(rename = "Date")]
+    date: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct MonitoringStatus {
+    #[serde(rename = "SignalIcon", default)]
+    signal_icon: i64,
+    #[serde(rename = "CurrentNetworkType", default)]
+    current_network_type: i64,
+    #[serde(rename = "RoamingStatus", default)]
+    roaming_status: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct CurrentPlmn {
+    #[serde(rename = "FullName", default)]
+    full_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct PinStatus {
+    #[serde(rename = "SimState")]
+    sim_state: i64,
+    #[serde(rename = "PinOptState", default)]
+    pin_opt_state: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct PinOperateRequest {
+    #[serde(rename = "OperateType")]
+    operate_type: i64,
+    #[serde(rename = "CurrentPin")]
+    current_pin: String,
+    #[serde(rename = "NewPin")]
+    new_pin: String,
+    #[serde(rename = "PukCode")]
+    puk_code: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmsCount {
+    #[serde(rename = "LocalInbox")]
+    local_inbox: u32,
+    #[serde(rename = "LocalOutbox")]
+    local_outbox: u32,
+    #[serde(rename = "LocalDraft", default)]
+    local_draft: u32,
+    #[serde(rename = "LocalMax")]
+    local_max: u32,
+}
+
+#[derive(Serialize, Debug)]
+struct DeleteSmsRequest {
+    #[serde(rename = "Index")]
+    index: i64,
+}
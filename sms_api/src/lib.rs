@@ -1,14 +1,29 @@
-use std::time::Duration;
+use std::{fmt, time::Duration};
 
 use alcatel::AlcatelSmsService;
 use async_trait::async_trait;
+use at_serial::AtSerialSmsService;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use failover::FailoverSmsService;
+use huawei_hilink::HuaweiHilinkSmsService;
+use rate_limit::{RateLimitedSmsService, RateLimiter};
 use reqwest::StatusCode;
+use retry::{RetryPolicy, RetryingSmsService};
+use serde::Serialize;
 use sms_config::config::{SmsApiConf, SmsApiProvider};
 use thiserror::Error;
 
 mod alcatel;
+mod at_serial;
+pub mod encoding;
+mod failover;
+mod huawei_hilink;
+pub mod pdu;
+pub mod rate_limit;
+pub mod retry;
 #[cfg(feature = "sms_mock_api")]
 pub mod sms_mock_api;
+pub mod transliteration;
 mod void;
 
 #[derive(Error, Debug)]
@@ -21,24 +36,317 @@ pub enum SmsError {
     InvalidResponse(StatusCode, String),
     #[error("Could not parse json")]
     ResponseParseError(#[from] reqwest::Error),
+    #[error("Modem error: {0}")]
+    ModemError(String),
+    #[error("Modem is busy: {0}")]
+    ModemBusy(String),
+    #[error("Rate limit reached: {0}")]
+    RateLimited(String),
+    #[error("Send was not confirmed, message may have been sent: {0}")]
+    Unconfirmed(String),
+    #[error("Invalid PDU: {0}")]
+    PduError(String),
+    #[error("SIM card is locked, {0}")]
+    SimLocked(SimLock),
+}
+
+impl SmsError {
+    // Errors of modem itself, every next recipient would fail the same way
+    pub fn affects_all_recipients(&self) -> bool {
+        matches!(
+            self,
+            SmsError::NetworkError(_)
+                | SmsError::ModemBusy(_)
+                | SmsError::RateLimited(_)
+                | SmsError::SimLocked(_)
+        )
+    }
+
+    // Failure after modem accepted the message, repeating it could deliver the message twice
+    pub(crate) fn into_unconfirmed(self) -> SmsError {
+        if self.is_retryable() {
+            SmsError::Unconfirmed(self.to_string())
+        } else {
+            self
+        }
+    }
+
+    // Temporary problems of connection or modem, repeated request may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SmsError::NetworkError(_) | SmsError::ModemBusy(_) => true,
+            SmsError::ResponseParseError(e) => e.is_timeout() || e.is_connect(),
+            SmsError::InvalidResponse(status, _) => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimLock {
+    PinRequired,
+    PukRequired,
+}
+
+impl fmt::Display for SimLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimLock::PinRequired => write!(f, "PIN is required, unlock it with `sms modem unlock`"),
+            SimLock::PukRequired => write!(
+                f,
+                "PUK is required, unblock it in modem web interface or phone"
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RecipientStatus {
+    Sent,
+    Failed(SmsError),
+    // Not attempted because modem failed on one of previous recipients
+    Skipped,
+}
+
+#[derive(Debug)]
+pub struct RecipientResult {
+    pub phone: String,
+    pub status: RecipientStatus,
+    // Set only when backup providers are configured, description of provider that sent it
+    pub provider: Option<String>,
+    // TP-MR assigned by AT modem, delivery report of the message carries the same one
+    pub reference: Option<u8>,
+}
+
+// Result of every recipient, in order of given phone numbers
+#[derive(Debug, Default)]
+pub struct SendReport {
+    pub recipients: Vec<RecipientResult>,
+}
+
+impl SendReport {
+    pub fn all_sent(&self) -> bool {
+        self.recipients
+            .iter()
+            .all(|r| matches!(r.status, RecipientStatus::Sent))
+    }
+
+    pub fn not_sent_count(&self) -> usize {
+        self.recipients
+            .iter()
+            .filter(|r| !matches!(r.status, RecipientStatus::Sent))
+            .count()
+    }
+
+    // Failure of one recipient doesn't stop the others unless the modem itself failed
+    pub(crate) fn should_skip(&self) -> bool {
+        self.recipients.iter().any(|r| {
+            matches!(&r.status, RecipientStatus::Failed(e) if e.affects_all_recipients())
+                || matches!(r.status, RecipientStatus::Skipped)
+        })
+    }
+
+    pub(crate) fn add(&mut self, phone: &str, result: Result<(), SmsError>) {
+        self.add_referenced(phone, result.map(|_| None));
+    }
+
+    pub(crate) fn add_referenced(&mut self, phone: &str, result: Result<Option<u8>, SmsError>) {
+        let (status, reference) = match result {
+            Ok(reference) => (RecipientStatus::Sent, reference),
+            Err(e) => (RecipientStatus::Failed(e), None),
+        };
+        self.recipients.push(RecipientResult {
+            phone: phone.to_string(),
+            status,
+            provider: None,
+            reference,
+        });
+    }
+
+    // Recipients failed with retryable error, or skipped because of one
+    pub(crate) fn retryable(&self) -> Vec<usize> {
+        let skipped_retryable = self.recipients.iter().any(|r| {
+            matches!(&r.status, RecipientStatus::Failed(e) if e.affects_all_recipients() && e.is_retryable())
+        });
+        self.recipients
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| match &r.status {
+                RecipientStatus::Sent => false,
+                RecipientStatus::Failed(e) => e.is_retryable(),
+                RecipientStatus::Skipped => skipped_retryable,
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    // Replaces results of recipients at given indexes with results of repeated send
+    pub(crate) fn replace(&mut self, indexes: &[usize], retried: SendReport) {
+        for (index, result) in indexes.iter().zip(retried.recipients) {
+            self.recipients[*index] = result;
+        }
+    }
+
+    pub(crate) fn skip(&mut self, phone: &str) {
+        self.recipients.push(RecipientResult {
+            phone: phone.to_string(),
+            status: RecipientStatus::Skipped,
+            provider: None,
+            reference: None,
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceivedSms {
+    pub id: i64,
+    pub phone: String,
+    pub text: String,
+    pub time: NaiveDateTime,
+    pub unread: bool,
+}
+
+// Messages that could not be decoded are not part of inbox, caller gets only their index
+#[derive(Debug, Clone, Default)]
+pub struct Inbox {
+    pub messages: Vec<ReceivedSms>,
+    pub undecodable: Vec<UndecodableSms>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UndecodableSms {
+    pub id: i64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    Delivered,
+    Pending,
+    Failed,
+}
+
+// Report is stored on modem like received sms and is removed with `delete_delivery_report`
+#[derive(Debug, Clone)]
+pub struct DeliveryReport {
+    pub id: i64,
+    pub phone: String,
+    pub status: DeliveryStatus,
+    pub time: NaiveDateTime,
+    // Set only when backup providers are configured, description of provider that got it
+    pub provider: Option<String>,
+    // Missing when provider doesn't tell which message is reported
+    pub reference: Option<u8>,
+}
+
+pub const MAX_SIGNAL_BARS: u8 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModemStatus {
+    pub operator: String,
+    pub network_type: String,
+    pub signal_bars: u8,
+    pub roaming: bool,
+    pub sim_state: String,
+    pub pin_state: String,
+    pub sms_storage: Option<SmsStorage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SmsStorage {
+    pub used: u32,
+    pub capacity: u32,
 }
 
 #[async_trait]
-pub trait SmsService {
-    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<(), SmsError>;
+pub trait SmsService: Send + Sync {
+    // Error is returned only when nothing could be sent, e.g. modem is not reachable
+    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError>;
+    async fn read_inbox(&self) -> Result<Inbox, SmsError>;
+    async fn delete_sms(&self, id: i64) -> Result<(), SmsError>;
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError>;
+    async fn delete_delivery_report(&self, report: &DeliveryReport) -> Result<(), SmsError> {
+        self.delete_sms(report.id).await
+    }
+    async fn status(&self) -> Result<ModemStatus, SmsError>;
+    async fn unlock(&self, pin: &str) -> Result<(), SmsError>;
+    async fn send_ussd(&self, code: &str) -> Result<String, SmsError>;
 }
 
+// Every provider is wrapped with rate limits and retry policy of config, limits are inside
+// so repeated sends use the budget too. Backup providers are tried before the next retry.
 pub fn create_service(sms_api_config: &SmsApiConf) -> Result<Box<dyn SmsService>, SmsError> {
-    match &sms_api_config.provider {
+    create_service_with_history(sms_api_config, vec![])
+}
+
+// Sms sent before, with time and number of parts, count into rate limits of created service
+pub fn create_service_with_history(
+    sms_api_config: &SmsApiConf,
+    sent: Vec<(DateTime<Utc>, u32)>,
+) -> Result<Box<dyn SmsService>, SmsError> {
+    let mut provider = if sms_api_config.backup_providers.is_empty() {
+        create_provider(&sms_api_config.provider, sms_api_config)?
+    } else {
+        let providers = sms_api_config
+            .providers()
+            .into_iter()
+            .map(|p| Ok((p.description(), create_provider(p, sms_api_config)?)))
+            .collect::<Result<Vec<_>, SmsError>>()?;
+        Box::new(FailoverSmsService::new(providers))
+    };
+    if sms_api_config.rate_limit.is_limited() {
+        let limiter = RateLimiter::from_history(&sms_api_config.rate_limit, sent, Utc::now());
+        provider = Box::new(RateLimitedSmsService::new(provider, limiter));
+    }
+    Ok(Box::new(RetryingSmsService::new(
+        provider,
+        RetryPolicy::from(&sms_api_config.retry),
+    )))
+}
+
+fn create_provider(
+    provider: &SmsApiProvider,
+    sms_api_config: &SmsApiConf,
+) -> Result<Box<dyn SmsService>, SmsError> {
+    match provider {
         SmsApiProvider::Void => Ok(Box::new(void::VoidSmsService)),
         SmsApiProvider::Alcatel {
             url,
             retry_count,
             retry_delay,
+            username,
+            password,
+            pin,
         } => Ok(Box::new(AlcatelSmsService::new(
             url,
             *retry_count,
             Duration::from_millis(*retry_delay),
+            username.clone().zip(password.clone()),
+            pin.clone(),
+            sms_api_config.delivery_reports,
+        )?)),
+        SmsApiProvider::AtSerial {
+            device,
+            baud_rate,
+            pin,
+        } => Ok(Box::new(AtSerialSmsService::new(
+            device,
+            *baud_rate,
+            pin.clone(),
+            sms_api_config.delivery_reports,
+        ))),
+        SmsApiProvider::HuaweiHilink {
+            url,
+            username,
+            password,
+            pin,
+        } => Ok(Box::new(HuaweiHilinkSmsService::new(
+            url,
+            username.clone(),
+            password.clone(),
+            pin.clone(),
         )?)),
     }
 }
@@ -0,0 +1,548 @@
+use chrono::{DateTime, FixedOffset, NaiveDate};
+
+use crate::{
+    encoding::{self, SmsEncoding},
+    SmsError,
+};
+
+const TP_MTI_DELIVER: u8 = 0x00;
+const TP_MTI_SUBMIT: u8 = 0x01;
+const TP_MTI_STATUS_REPORT: u8 = 0x02;
+const TP_MTI_MASK: u8 = 0x03;
+const TP_VPF_RELATIVE: u8 = 0x10;
+const TP_VPF_MASK: u8 = 0x18;
+const TP_SRR: u8 = 0x20;
+const TP_UDHI: u8 = 0x40;
+
+// Relative validity period of 4 days
+const VALIDITY_PERIOD: u8 = 0xAA;
+const DCS_GSM7: u8 = 0x00;
+const DCS_UCS2: u8 = 0x08;
+
+const IEI_CONCATENATION_8BIT: u8 = 0x00;
+const IEI_CONCATENATION_16BIT: u8 = 0x08;
+
+const ADDRESS_INTERNATIONAL: u8 = 0x91;
+const ADDRESS_UNKNOWN: u8 = 0x81;
+const ADDRESS_TYPE_OF_NUMBER_MASK: u8 = 0x70;
+const ADDRESS_TYPE_INTERNATIONAL: u8 = 0x10;
+const ADDRESS_TYPE_ALPHANUMERIC: u8 = 0x50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedPdu {
+    // Hex encoded PDU including empty SMSC field
+    pub hex: String,
+    // Length of TPDU in octets without SMSC, required by `AT+CMGS`
+    pub tpdu_length: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Concatenation {
+    pub reference: u16,
+    pub total: u8,
+    pub sequence: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitPdu {
+    pub recipient: String,
+    pub text: String,
+    pub status_report: bool,
+    pub concatenation: Option<Concatenation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliverPdu {
+    pub sender: String,
+    pub text: String,
+    pub timestamp: DateTime<FixedOffset>,
+    pub concatenation: Option<Concatenation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusReportPdu {
+    pub reference: u8,
+    pub recipient: String,
+    pub discharge_time: DateTime<FixedOffset>,
+    // TP-ST: 0x00-0x1F delivered, 0x20-0x3F still trying, above that given up
+    pub status: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceivedPdu {
+    Deliver(DeliverPdu),
+    StatusReport(StatusReportPdu),
+}
+
+enum UserData {
+    Gsm7(Vec<u8>),
+    Ucs2(Vec<u16>),
+}
+
+// Encodes message as one or more SMS-SUBMIT PDUs, long messages get concatenation header
+// with given reference. Status report is requested only for the last part, so whole message
+// is confirmed once.
+pub fn encode_submit(
+    recipient: &str,
+    text: &str,
+    reference: u8,
+    status_report: bool,
+) -> Result<Vec<EncodedPdu>, SmsError> {
+    let address = encode_address(recipient)?;
+    let parts = split_user_data(text);
+    let total = parts.len();
+    if total > u8::MAX as usize {
+        return Err(SmsError::PduError(format!(
+            "Message needs {} parts, at most {} are allowed",
+            total,
+            u8::MAX
+        )));
+    }
+    let mut pdus = vec![];
+    for (index, part) in parts.into_iter().enumerate() {
+        let header = (total > 1).then(|| {
+            vec![
+                0x05,
+                IEI_CONCATENATION_8BIT,
+                0x03,
+                reference,
+                total as u8,
+                index as u8 + 1,
+            ]
+        });
+        let mut first_octet = TP_MTI_SUBMIT | TP_VPF_RELATIVE;
+        if status_report && index + 1 == total {
+            first_octet |= TP_SRR;
+        }
+        if header.is_some() {
+            first_octet |= TP_UDHI;
+        }
+        let dcs = match part {
+            UserData::Gsm7(_) => DCS_GSM7,
+            UserData::Ucs2(_) => DCS_UCS2,
+        };
+        let mut tpdu = vec![first_octet, 0x00];
+        tpdu.extend_from_slice(&address);
+        tpdu.extend_from_slice(&[0x00, dcs, VALIDITY_PERIOD]);
+        tpdu.extend(encode_user_data(part, header.unwrap_or_default()));
+        pdus.push(EncodedPdu {
+            hex: format!("00{}", to_hex(&tpdu)),
+            tpdu_length: tpdu.len(),
+        });
+    }
+    Ok(pdus)
+}
+
+pub fn decode_submit(hex: &str) -> Result<SubmitPdu, SmsError> {
+    let bytes = from_hex(hex)?;
+    let mut reader = PduReader::new(&bytes);
+    reader.skip_smsc()?;
+    let first_octet = reader.byte()?;
+    if first_octet & TP_MTI_MASK != TP_MTI_SUBMIT {
+        return Err(SmsError::PduError(format!(
+            "Expected SMS-SUBMIT but got message type {}",
+            first_octet & TP_MTI_MASK
+        )));
+    }
+    let _message_reference = reader.byte()?;
+    let recipient = reader.address()?;
+    let _protocol_identifier = reader.byte()?;
+    let dcs = reader.byte()?;
+    match first_octet & TP_VPF_MASK {
+        0x00 => {}
+        TP_VPF_RELATIVE => reader.skip(1)?,
+        _ => reader.skip(7)?,
+    }
+    let (text, concatenation) = reader.user_data(dcs, first_octet & TP_UDHI != 0)?;
+    Ok(SubmitPdu {
+        recipient,
+        text,
+        status_report: first_octet & TP_SRR != 0,
+        concatenation,
+    })
+}
+
+pub fn decode_deliver(hex: &str) -> Result<DeliverPdu, SmsError> {
+    let bytes = from_hex(hex)?;
+    let mut reader = PduReader::new(&bytes);
+    reader.skip_smsc()?;
+    let first_octet = reader.byte()?;
+    if first_octet & TP_MTI_MASK != TP_MTI_DELIVER {
+        return Err(SmsError::PduError(format!(
+            "Expected SMS-DELIVER but got message type {}",
+            first_octet & TP_MTI_MASK
+        )));
+    }
+    let sender = reader.address()?;
+    let _protocol_identifier = reader.byte()?;
+    let dcs = reader.byte()?;
+    let timestamp = reader.timestamp()?;
+    let (text, concatenation) = reader.user_data(dcs, first_octet & TP_UDHI != 0)?;
+    Ok(DeliverPdu {
+        sender,
+        text,
+        timestamp,
+        concatenation,
+    })
+}
+
+pub fn decode_status_report(hex: &str) -> Result<StatusReportPdu, SmsError> {
+    let bytes = from_hex(hex)?;
+    let mut reader = PduReader::new(&bytes);
+    reader.skip_smsc()?;
+    let first_octet = reader.byte()?;
+    if first_octet & TP_MTI_MASK != TP_MTI_STATUS_REPORT {
+        return Err(SmsError::PduError(format!(
+            "Expected SMS-STATUS-REPORT but got message type {}",
+            first_octet & TP_MTI_MASK
+        )));
+    }
+    let reference = reader.byte()?;
+    let recipient = reader.address()?;
+    let _service_centre_time = reader.timestamp()?;
+    let discharge_time = reader.timestamp()?;
+    let status = reader.byte()?;
+    Ok(StatusReportPdu {
+        reference,
+        recipient,
+        discharge_time,
+        status,
+    })
+}
+
+// Messages listed by modem are either received sms or status reports of sent ones
+pub fn decode_received(hex: &str) -> Result<ReceivedPdu, SmsError> {
+    let bytes = from_hex(hex)?;
+    let mut reader = PduReader::new(&bytes);
+    reader.skip_smsc()?;
+    match reader.byte()? & TP_MTI_MASK {
+        TP_MTI_STATUS_REPORT => decode_status_report(hex).map(ReceivedPdu::StatusReport),
+        _ => decode_deliver(hex).map(ReceivedPdu::Deliver),
+    }
+}
+
+fn split_user_data(text: &str) -> Vec<UserData> {
+    let parts = encoding::analyze(text);
+    let mut chunks: Vec<Vec<char>> = vec![vec![]];
+    let mut used = 0;
+    for c in text.chars() {
+        let cost = match parts.encoding {
+            SmsEncoding::Gsm7 if encoding::gsm7_code(c).is_some() => 1,
+            SmsEncoding::Gsm7 => 2,
+            SmsEncoding::Ucs2 => c.len_utf16(),
+        };
+        let limit = match parts.encoding {
+            SmsEncoding::Gsm7 => encoding::GSM7_MULTIPART_LIMIT,
+            SmsEncoding::Ucs2 => encoding::UCS2_MULTIPART_LIMIT,
+        };
+        if parts.segments > 1 && used + cost > limit {
+            chunks.push(vec![]);
+            used = 0;
+        }
+        used += cost;
+        chunks.last_mut().expect("chunk exists").push(c);
+    }
+    chunks
+        .into_iter()
+        .map(|chunk| match parts.encoding {
+            SmsEncoding::Gsm7 => UserData::Gsm7(chunk.into_iter().flat_map(gsm7_septets).collect()),
+            SmsEncoding::Ucs2 => UserData::Ucs2(
+                chunk
+                    .into_iter()
+                    .collect::<String>()
+                    .encode_utf16()
+                    .collect(),
+            ),
+        })
+        .collect()
+}
+
+fn gsm7_septets(c: char) -> Vec<u8> {
+    match (encoding::gsm7_code(c), encoding::gsm7_extension_code(c)) {
+        (Some(code), _) => vec![code],
+        (None, Some(code)) => vec![encoding::GSM7_ESCAPE, code],
+        (None, None) => vec![encoding::gsm7_code('?').expect("? is in GSM-7")],
+    }
+}
+
+fn encode_user_data(data: UserData, header: Vec<u8>) -> Vec<u8> {
+    let mut user_data = vec![];
+    match data {
+        UserData::Gsm7(septets) => {
+            let header_septets = (header.len() * 8).div_ceil(7);
+            let fill_bits = header_septets * 7 - header.len() * 8;
+            user_data.push((header_septets + septets.len()) as u8);
+            user_data.extend_from_slice(&header);
+            user_data.extend(pack_septets(&septets, fill_bits));
+        }
+        UserData::Ucs2(units) => {
+            user_data.push((header.len() + units.len() * 2) as u8);
+            user_data.extend_from_slice(&header);
+            user_data.extend(units.into_iter().flat_map(u16::to_be_bytes));
+        }
+    }
+    user_data
+}
+
+// Packs 7 bit values into octets, least significant bits first,
+// fill bits align user data after header to septet boundary.
+pub fn pack_septets(septets: &[u8], fill_bits: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; (fill_bits + septets.len() * 7).div_ceil(8)];
+    for (index, septet) in septets.iter().enumerate() {
+        let bit = fill_bits + index * 7;
+        let value = ((*septet & 0x7F) as u16) << (bit % 8);
+        bytes[bit / 8] |= value as u8;
+        if let Some(next) = bytes.get_mut(bit / 8 + 1) {
+            *next |= (value >> 8) as u8;
+        }
+    }
+    bytes
+}
+
+pub fn unpack_septets(bytes: &[u8], count: usize, fill_bits: usize) -> Vec<u8> {
+    (0..count)
+        .map(|index| {
+            let bit = fill_bits + index * 7;
+            let low = bytes.get(bit / 8).copied().unwrap_or_default() as u16;
+            let high = bytes.get(bit / 8 + 1).copied().unwrap_or_default() as u16;
+            (((high << 8 | low) >> (bit % 8)) & 0x7F) as u8
+        })
+        .collect()
+}
+
+fn decode_gsm7(septets: &[u8]) -> String {
+    let mut text = String::new();
+    let mut septets = septets.iter();
+    while let Some(septet) = septets.next() {
+        let c = if *septet == encoding::GSM7_ESCAPE {
+            septets
+                .next()
+                .and_then(|code| encoding::gsm7_extension_char(*code))
+        } else {
+            encoding::gsm7_char(*septet)
+        };
+        text.push(c.unwrap_or('?'));
+    }
+    text
+}
+
+pub(crate) fn encode_address(phone: &str) -> Result<Vec<u8>, SmsError> {
+    let digits: String = phone
+        .trim_start_matches('+')
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(SmsError::PduError(format!(
+            "Invalid phone number '{}'",
+            phone
+        )));
+    }
+    let address_type = if phone.starts_with('+') {
+        ADDRESS_INTERNATIONAL
+    } else {
+        ADDRESS_UNKNOWN
+    };
+    let mut address = vec![digits.len() as u8, address_type];
+    address.extend(encode_semi_octets(&digits));
+    Ok(address)
+}
+
+fn encode_semi_octets(digits: &str) -> Vec<u8> {
+    digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let low = pair[0] - b'0';
+            let high = pair.get(1).map(|d| d - b'0').unwrap_or(0x0F);
+            high << 4 | low
+        })
+        .collect()
+}
+
+fn decode_semi_octets(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|b| [b & 0x0F, b >> 4])
+        .take_while(|digit| *digit != 0x0F)
+        .map(|digit| char::from_digit(digit as u32, 16).unwrap_or('?'))
+        .collect()
+}
+
+struct PduReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PduReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], SmsError> {
+        let end = self.position + count;
+        let taken = self.bytes.get(self.position..end).ok_or_else(|| {
+            SmsError::PduError(format!(
+                "Expected {} more octets at position {}",
+                count, self.position
+            ))
+        })?;
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, SmsError> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), SmsError> {
+        self.take(count).map(|_| ())
+    }
+
+    fn skip_smsc(&mut self) -> Result<(), SmsError> {
+        let length = self.byte()? as usize;
+        self.skip(length)
+    }
+
+    fn address(&mut self) -> Result<String, SmsError> {
+        let length = self.byte()? as usize;
+        let address_type = self.byte()?;
+        let value = self.take(length.div_ceil(2))?;
+        match address_type & ADDRESS_TYPE_OF_NUMBER_MASK {
+            ADDRESS_TYPE_ALPHANUMERIC => Ok(decode_gsm7(&unpack_septets(value, length * 4 / 7, 0))),
+            ADDRESS_TYPE_INTERNATIONAL => Ok(format!("+{}", decode_semi_octets(value))),
+            _ => Ok(decode_semi_octets(value)),
+        }
+    }
+
+    // Service centre time stamp, swapped semi-octets with timezone in quarters of hour
+    fn timestamp(&mut self) -> Result<DateTime<FixedOffset>, SmsError> {
+        let value = self.take(7)?;
+        let number = |b: u8| ((b & 0x0F) * 10 + (b >> 4)) as u32;
+        let quarters = ((value[6] & 0x07) * 10 + (value[6] >> 4)) as i32;
+        let offset = if value[6] & 0x08 != 0 {
+            -quarters
+        } else {
+            quarters
+        };
+        NaiveDate::from_ymd_opt(
+            2000 + number(value[0]) as i32,
+            number(value[1]),
+            number(value[2]),
+        )
+        .and_then(|date| date.and_hms_opt(number(value[3]), number(value[4]), number(value[5])))
+        .and_then(|time| {
+            FixedOffset::east_opt(offset * 15 * 60)
+                .and_then(|offset| time.and_local_timezone(offset).single())
+        })
+        .ok_or_else(|| SmsError::PduError(format!("Invalid timestamp {}", to_hex(value))))
+    }
+
+    fn user_data(
+        &mut self,
+        dcs: u8,
+        has_header: bool,
+    ) -> Result<(String, Option<Concatenation>), SmsError> {
+        let length = self.byte()? as usize;
+        let data = &self.bytes[self.position..];
+        let (header_length, concatenation) = if has_header {
+            let header_length = *data
+                .first()
+                .ok_or_else(|| SmsError::PduError("Missing user data header".to_string()))?
+                as usize
+                + 1;
+            let header = data.get(1..header_length).ok_or_else(|| {
+                SmsError::PduError("User data header is longer than message".to_string())
+            })?;
+            (header_length, parse_concatenation(header))
+        } else {
+            (0, None)
+        };
+        let text = match alphabet(dcs) {
+            SmsEncoding::Gsm7 => {
+                let header_septets = (header_length * 8).div_ceil(7);
+                let fill_bits = header_septets * 7 - header_length * 8;
+                let septets = unpack_septets(
+                    &data[header_length..],
+                    length.saturating_sub(header_septets),
+                    fill_bits,
+                );
+                decode_gsm7(&septets)
+            }
+            SmsEncoding::Ucs2 => {
+                let bytes = data.get(header_length..length).ok_or_else(|| {
+                    SmsError::PduError("User data is shorter than declared".to_string())
+                })?;
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+        };
+        Ok((text, concatenation))
+    }
+}
+
+fn alphabet(dcs: u8) -> SmsEncoding {
+    let is_ucs2 = match dcs >> 4 {
+        // General data coding groups, alphabet is in bits 2 and 3
+        0x0..=0x7 => dcs & 0x0C == 0x08,
+        // Message waiting indication group with UCS-2 text
+        0xE => true,
+        _ => false,
+    };
+    if is_ucs2 {
+        SmsEncoding::Ucs2
+    } else {
+        SmsEncoding::Gsm7
+    }
+}
+
+fn parse_concatenation(mut header: &[u8]) -> Option<Concatenation> {
+    while let [iei, length, rest @ ..] = header {
+        let value = rest.get(..*length as usize)?;
+        match (*iei, value) {
+            (IEI_CONCATENATION_8BIT, [reference, total, sequence]) => {
+                return Some(Concatenation {
+                    reference: *reference as u16,
+                    total: *total,
+                    sequence: *sequence,
+                })
+            }
+            (IEI_CONCATENATION_16BIT, [high, low, total, sequence]) => {
+                return Some(Concatenation {
+                    reference: u16::from_be_bytes([*high, *low]),
+                    total: *total,
+                    sequence: *sequence,
+                })
+            }
+            _ => header = &rest[*length as usize..],
+        }
+    }
+    None
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, SmsError> {
+    let hex = hex.trim();
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(SmsError::PduError(format!("Invalid hex value '{}'", hex)));
+    }
+    if hex.len() % 2 == 1 {
+        return Err(SmsError::PduError(format!(
+            "Hex value has odd length {}",
+            hex.len()
+        )));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&hex[index..index + 2], 16)
+                .map_err(|e| SmsError::PduError(format!("Invalid hex value, Reason: {}", e)))
+        })
+        .collect()
+}
@@ -0,0 +1,269 @@
+use std::{sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+use sms_config::config::RateLimitConf;
+
+use crate::{encoding, DeliveryReport, Inbox, ModemStatus, SendReport, SmsError, SmsService};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Budget {
+    pub remaining: u32,
+    pub limit: u32,
+}
+
+// Sms parts that can be sent right away, missing when limit is not configured
+#[derive(Debug, Clone, Serialize)]
+pub struct RateBudget {
+    pub per_minute: Option<Budget>,
+    pub per_hour: Option<Budget>,
+    pub today: Option<Budget>,
+}
+
+// Refills continuously, full bucket allows burst of its whole capacity
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    updated: DateTime<Utc>,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, period: Duration, now: DateTime<Utc>) -> Self {
+        Self {
+            capacity: capacity as f64,
+            per_second: capacity as f64 / period.as_secs_f64(),
+            tokens: capacity as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: DateTime<Utc>) {
+        let elapsed = (now - self.updated).to_std().unwrap_or_default();
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.per_second).min(self.capacity);
+        self.updated = self.updated.max(now);
+    }
+
+    // Message longer than capacity waits only for full bucket
+    fn wait_time(&self, parts: u32) -> Duration {
+        let missing = (parts as f64).min(self.capacity) - self.tokens;
+        if missing <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(missing / self.per_second)
+    }
+
+    fn budget(&self) -> Budget {
+        Budget {
+            remaining: self.tokens.max(0.0) as u32,
+            limit: self.capacity as u32,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    conf: RateLimitConf,
+    per_minute: Option<TokenBucket>,
+    per_hour: Option<TokenBucket>,
+    // Daily cap is counted per local calendar day
+    day: NaiveDate,
+    sent_today: u32,
+    last_sent: Option<DateTime<Utc>>,
+}
+
+impl RateLimiter {
+    pub fn new(conf: &RateLimitConf, now: DateTime<Utc>) -> Self {
+        let bucket = |limit: Option<u32>, period| {
+            limit
+                .filter(|limit| *limit > 0)
+                .map(|limit| TokenBucket::new(limit, period, now))
+        };
+        Self {
+            conf: conf.clone(),
+            per_minute: bucket(conf.per_minute, MINUTE),
+            per_hour: bucket(conf.per_hour, HOUR),
+            day: local_day(now),
+            sent_today: 0,
+            last_sent: None,
+        }
+    }
+
+    // Replays sms sent before, so limits hold across runs of the program
+    pub fn from_history(
+        conf: &RateLimitConf,
+        mut sent: Vec<(DateTime<Utc>, u32)>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        sent.sort_by_key(|(at, _)| *at);
+        let start = sent.first().map(|(at, _)| *at).unwrap_or(now);
+        let mut limiter = Self::new(conf, start);
+        for (at, parts) in sent {
+            limiter.refill(at);
+            limiter.take(parts, at);
+        }
+        limiter.refill(now);
+        limiter
+    }
+
+    // Zero when message of given parts can be sent now, error when daily cap is reached
+    pub fn wait_time(&mut self, parts: u32, now: DateTime<Utc>) -> Result<Duration, SmsError> {
+        self.refill(now);
+        if let Some(cap) = self.conf.daily_cap {
+            if self.sent_today + parts > cap {
+                return Err(SmsError::RateLimited(format!(
+                    "daily cap of {} sms reached",
+                    cap
+                )));
+            }
+        }
+        let spacing = self
+            .last_sent
+            .map(|last| last + Duration::from_millis(self.conf.min_spacing) - now)
+            .and_then(|wait| wait.to_std().ok())
+            .unwrap_or_default();
+        Ok([&self.per_minute, &self.per_hour]
+            .into_iter()
+            .flatten()
+            .map(|bucket| bucket.wait_time(parts))
+            .fold(spacing, Duration::max))
+    }
+
+    pub fn take(&mut self, parts: u32, now: DateTime<Utc>) {
+        for bucket in [&mut self.per_minute, &mut self.per_hour]
+            .into_iter()
+            .flatten()
+        {
+            bucket.tokens -= parts as f64;
+        }
+        self.sent_today += parts;
+        self.last_sent = Some(now);
+    }
+
+    pub fn budget(&mut self, now: DateTime<Utc>) -> RateBudget {
+        self.refill(now);
+        RateBudget {
+            per_minute: self.per_minute.as_ref().map(TokenBucket::budget),
+            per_hour: self.per_hour.as_ref().map(TokenBucket::budget),
+            today: self.conf.daily_cap.map(|cap| Budget {
+                remaining: cap.saturating_sub(self.sent_today),
+                limit: cap,
+            }),
+        }
+    }
+
+    fn refill(&mut self, now: DateTime<Utc>) {
+        for bucket in [&mut self.per_minute, &mut self.per_hour]
+            .into_iter()
+            .flatten()
+        {
+            bucket.refill(now);
+        }
+        if local_day(now) > self.day {
+            self.day = local_day(now);
+            self.sent_today = 0;
+        }
+    }
+}
+
+fn local_day(time: DateTime<Utc>) -> NaiveDate {
+    time.with_timezone(&Local).date_naive()
+}
+
+// Budget left after given sms, time they were sent and their parts
+pub fn budget(conf: &RateLimitConf, sent: Vec<(DateTime<Utc>, u32)>) -> Option<RateBudget> {
+    if !conf.is_limited() {
+        return None;
+    }
+    let now = Utc::now();
+    Some(RateLimiter::from_history(conf, sent, now).budget(now))
+}
+
+pub(crate) struct RateLimitedSmsService {
+    inner: Box<dyn SmsService>,
+    limiter: Mutex<RateLimiter>,
+}
+
+impl RateLimitedSmsService {
+    pub fn new(inner: Box<dyn SmsService>, limiter: RateLimiter) -> Self {
+        Self {
+            inner,
+            limiter: Mutex::new(limiter),
+        }
+    }
+
+    async fn acquire(&self, parts: u32) -> Result<(), SmsError> {
+        loop {
+            let wait = {
+                let mut limiter = self.limiter.lock().unwrap();
+                let now = Utc::now();
+                let wait = limiter.wait_time(parts, now)?;
+                if wait.is_zero() {
+                    limiter.take(parts, now);
+                }
+                wait
+            };
+            if wait.is_zero() {
+                return Ok(());
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[async_trait]
+impl SmsService for RateLimitedSmsService {
+    // Recipients are sent one by one, so every sms waits for its budget
+    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError> {
+        let parts = encoding::analyze(msg).segments as u32;
+        let mut report = SendReport::default();
+        for phone in phone_numbers {
+            if report.should_skip() {
+                report.skip(phone);
+                continue;
+            }
+            if let Err(e) = self.acquire(parts).await {
+                report.add(phone, Err(e));
+                continue;
+            }
+            match self.inner.send_sms(msg, &[phone]).await {
+                Ok(single) => report.recipients.extend(single.recipients),
+                Err(e) => report.add(phone, Err(e)),
+            }
+        }
+        Ok(report)
+    }
+
+    async fn read_inbox(&self) -> Result<Inbox, SmsError> {
+        self.inner.read_inbox().await
+    }
+
+    async fn delete_sms(&self, id: i64) -> Result<(), SmsError> {
+        self.inner.delete_sms(id).await
+    }
+
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError> {
+        self.inner.read_delivery_reports().await
+    }
+
+    async fn delete_delivery_report(&self, report: &DeliveryReport) -> Result<(), SmsError> {
+        self.inner.delete_delivery_report(report).await
+    }
+
+    async fn status(&self) -> Result<ModemStatus, SmsError> {
+        self.inner.status().await
+    }
+
+    async fn unlock(&self, pin: &str) -> Result<(), SmsError> {
+        self.inner.unlock(pin).await
+    }
+
+    async fn send_ussd(&self, code: &str) -> Result<String, SmsError> {
+        self.inner.send_ussd(code).await
+    }
+}
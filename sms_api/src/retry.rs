@@ -0,0 +1,171 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use sms_config::config::RetryConf;
+
+use crate::{DeliveryReport, Inbox, ModemStatus, SendReport, SmsError, SmsService};
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    pub max_elapsed: Duration,
+}
+
+impl From<&RetryConf> for RetryPolicy {
+    fn from(conf: &RetryConf) -> Self {
+        Self {
+            max_attempts: conf.max_attempts,
+            initial_delay: Duration::from_millis(conf.initial_delay),
+            max_delay: Duration::from_millis(conf.max_delay),
+            multiplier: conf.multiplier,
+            jitter: conf.jitter,
+            max_elapsed: Duration::from_millis(conf.max_elapsed),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Delay before given retry without jitter, the first retry waits initial delay
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(retry.saturating_sub(1) as i32);
+        Duration::try_from_secs_f64(self.initial_delay.as_secs_f64() * factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    // Randomized, so modems shared by several senders are not hit at the same moment
+    pub fn delay(&self, retry: u32) -> Duration {
+        let base = self.base_delay(retry);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 + jitter * (2.0 * random_fraction() - 1.0);
+        Duration::try_from_secs_f64(base.as_secs_f64() * factor).unwrap_or(base)
+    }
+
+    // Sleeps before next attempt, false when policy allows no more attempts
+    pub async fn wait(&self, attempt: u32, started: Instant) -> bool {
+        let delay = self.delay(attempt);
+        if attempt >= self.max_attempts || started.elapsed() + delay > self.max_elapsed {
+            return false;
+        }
+        tokio::time::sleep(delay).await;
+        true
+    }
+
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, SmsError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, SmsError>>,
+    {
+        let started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if e.is_retryable() => {
+                    if !self.wait(attempt, started).await {
+                        return Err(e);
+                    }
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    nanos as f64 / 1_000_000_000.0
+}
+
+pub(crate) struct RetryingSmsService {
+    inner: Box<dyn SmsService>,
+    policy: RetryPolicy,
+}
+
+impl RetryingSmsService {
+    pub fn new(inner: Box<dyn SmsService>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl SmsService for RetryingSmsService {
+    // Only recipients failed with retryable error are sent again. Providers report failures
+    // after modem accepted the message as unconfirmed, which is not retried, so nobody gets
+    // message twice.
+    async fn send_sms(&self, msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError> {
+        let started = Instant::now();
+        let mut attempt = 1;
+        let mut report = loop {
+            match self.inner.send_sms(msg, phone_numbers).await {
+                Err(e) if e.is_retryable() => {
+                    if !self.policy.wait(attempt, started).await {
+                        return Err(e);
+                    }
+                    attempt += 1;
+                }
+                result => break result?,
+            }
+        };
+        loop {
+            let retried = report.retryable();
+            if retried.is_empty() || !self.policy.wait(attempt, started).await {
+                return Ok(report);
+            }
+            attempt += 1;
+            let phones: Vec<&str> = retried
+                .iter()
+                .map(|index| report.recipients[*index].phone.as_str())
+                .collect();
+            match self.inner.send_sms(msg, &phones).await {
+                Ok(retried_report) => report.replace(&retried, retried_report),
+                Err(e) if e.is_retryable() => {}
+                Err(_) => return Ok(report),
+            }
+        }
+    }
+
+    async fn read_inbox(&self) -> Result<Inbox, SmsError> {
+        self.policy.run(|| self.inner.read_inbox()).await
+    }
+
+    async fn delete_sms(&self, id: i64) -> Result<(), SmsError> {
+        self.policy.run(|| self.inner.delete_sms(id)).await
+    }
+
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError> {
+        self.policy.run(|| self.inner.read_delivery_reports()).await
+    }
+
+    async fn delete_delivery_report(&self, report: &DeliveryReport) -> Result<(), SmsError> {
+        self.policy
+            .run(|| self.inner.delete_delivery_report(report))
+            .await
+    }
+
+    async fn status(&self) -> Result<ModemStatus, SmsError> {
+        self.policy.run(|| self.inner.status()).await
+    }
+
+    // PIN and USSD requests are not repeated, wrong PIN or paid USSD code must not be sent twice
+    async fn unlock(&self, pin: &str) -> Result<(), SmsError> {
+        self.inner.unlock(pin).await
+    }
+
+    async fn send_ussd(&self, code: &str) -> Result<String, SmsError> {
+        self.inner.send_ussd(code).await
+    }
+}
@@ -1,4 +1,8 @@
+use std::sync::{Arc, Mutex};
+
 pub use mockito;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_serial::{SerialPort, SerialStream};
 
 pub async fn sending_sms_is_successful(server: &mut mockito::Server) -> AlcatelMock {
     let mock_send = server
@@ -44,6 +48,49 @@ pub async fn sending_sms_failure(server: &mut mockito::Server) -> AlcatelMock {
         mock_get_status,
     }
 }
+pub async fn reading_inbox_is_successful(server: &mut mockito::Server) -> AlcatelInboxMock {
+    let mock_contact_list = server
+        .mock("POST", "/jrd/webapi?api=GetSMSContactList")
+        .with_status(200)
+        .with_body(
+            r#"{ "jsonrpc": "2.0", "result": { "SMSContactList": [
+                { "ContactId": 1, "PhoneNumber": ["123456789"], "SMSId": 12, "SMSType": 1, "SMSTime": "2023-10-21 12:30:00", "SMSContent": "See you tomorrow", "TSMSCount": 2, "UnreadCount": 1 }
+            ], "Page": 0, "TotalPageCount": 1 }, "id": "6.2" }"#,
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+    let mock_content_list = server
+        .mock("POST", "/jrd/webapi?api=GetSMSContentList")
+        .with_status(200)
+        .with_body(
+            r#"{ "jsonrpc": "2.0", "result": { "ContactId": 1, "PhoneNumber": ["123456789"], "SMSContentList": [
+                { "SMSId": 11, "SMSType": 2, "SMSTime": "2023-10-21 12:00:00", "SMSContent": "Are we meeting?", "ReportStatus": 0 },
+                { "SMSId": 12, "SMSType": 1, "SMSTime": "2023-10-21 12:30:00", "SMSContent": "See you tomorrow", "ReportStatus": 0 }
+            ], "Page": 0, "TotalPageCount": 1 }, "id": "6.3" }"#,
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    AlcatelInboxMock {
+        mock_contact_list,
+        mock_content_list,
+    }
+}
+
+pub struct AlcatelInboxMock {
+    mock_contact_list: mockito::Mock,
+    mock_content_list: mockito::Mock,
+}
+
+impl AlcatelInboxMock {
+    pub fn assert_called(&self) {
+        self.mock_contact_list.assert();
+        self.mock_content_list.assert();
+    }
+}
+
 pub struct AlcatelMock {
     mock_send: mockito::Mock,
     mock_get_status: mockito::Mock,
@@ -55,3 +102,857 @@ impl AlcatelMock {
         self.mock_get_status.assert();
     }
 }
+
+// Credentials `admin`/`secret` and tokens as encrypted by Alcatel web interface
+const ALCATEL_ENCRYPTED_USERNAME: &str = "dc13ibej?7";
+const ALCATEL_ENCRYPTED_PASSWORD: &str = "fb03gbnk4765";
+const ALCATEL_FIRST_TOKEN: (i64, &str) = (1111, "df46egmo");
+const ALCATEL_SECOND_TOKEN: (i64, &str) = (2222, "gf76fgno");
+
+pub async fn alcatel_login_is_successful(server: &mut mockito::Server) -> MockSet {
+    MockSet {
+        mocks: vec![
+            alcatel_login(server, ALCATEL_FIRST_TOKEN.0).await,
+            alcatel_send_sms(server, ALCATEL_FIRST_TOKEN.1, 200).await,
+            alcatel_send_status(server, ALCATEL_FIRST_TOKEN.1).await,
+        ],
+    }
+}
+
+// First token is rejected with 401, so service has to login again and repeat request
+pub async fn alcatel_token_expired(server: &mut mockito::Server) -> MockSet {
+    MockSet {
+        mocks: vec![
+            alcatel_login(server, ALCATEL_FIRST_TOKEN.0).await,
+            alcatel_login(server, ALCATEL_SECOND_TOKEN.0).await,
+            alcatel_send_sms(server, ALCATEL_FIRST_TOKEN.1, 401).await,
+            alcatel_send_sms(server, ALCATEL_SECOND_TOKEN.1, 200).await,
+            alcatel_send_status(server, ALCATEL_SECOND_TOKEN.1).await,
+        ],
+    }
+}
+
+pub async fn alcatel_status_is_successful(server: &mut mockito::Server) -> MockSet {
+    let results = [
+        (
+            "GetSystemStatus",
+            r#"{ "NetworkType": 8, "SignalStrength": 3, "ConnectionStatus": 2, "Roaming": 1 }"#,
+        ),
+        (
+            "GetNetworkInfo",
+            r#"{ "PLMN": "26003", "NetworkType": 8, "NetworkName": "Orange", "SpnName": "Orange PL", "Roaming": 1, "SignalStrength": 3 }"#,
+        ),
+        (
+            "GetSimStatus",
+            r#"{ "SIMState": 7, "PinState": 2, "PinRemainingTimes": 3, "PukRemainingTimes": 10 }"#,
+        ),
+        (
+            "GetSMSStorageState",
+            r#"{ "UnreadReport": 0, "LeftCount": 88, "MaxCount": 100, "TUseCount": 12, "UnreadSMSCount": 1 }"#,
+        ),
+    ];
+    let mut mocks = vec![];
+    for (method, result) in results {
+        mocks.push(
+            server
+                .mock("POST", format!("/jrd/webapi?api={}", method).as_str())
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{ "jsonrpc": "2.0", "result": {}, "id": "1" }}"#,
+                    result
+                ))
+                .create_async()
+                .await,
+        );
+    }
+    MockSet { mocks }
+}
+
+pub const SIM_PIN: &str = "1234";
+
+// SIM requires PIN and accepts `SIM_PIN`
+pub async fn alcatel_pin_required(server: &mut mockito::Server) -> MockSet {
+    let mock_sim_status = server
+        .mock("POST", "/jrd/webapi?api=GetSimStatus")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "SIMState": 2, "PinState": 1, "PinRemainingTimes": 3 }, "id": "2.1" }"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let mock_unlock = server
+        .mock("POST", "/jrd/webapi?api=UnlockPin")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({ "params": { "Pin": SIM_PIN } }),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "jsonrpc": "2.0", "result": {}, "id": "2.2" }"#)
+        .expect(1)
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![mock_sim_status, mock_unlock],
+    }
+}
+
+// Sms is not sent until SIM is unlocked, the second attempt succeeds
+pub async fn alcatel_sim_locked(server: &mut mockito::Server) -> MockSet {
+    let mock_send = server
+        .mock("POST", "/jrd/webapi?api=SendSMS")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .expect(2)
+        .create_async()
+        .await;
+    let mock_not_sent = server
+        .mock("POST", "/jrd/webapi?api=GetSendSMSResult")
+        .with_status(200)
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "SendStatus": 1 }, "id": "6.7" }"#)
+        .with_header("content-type", "application/json")
+        .expect(MAX_RETRIES)
+        .create_async()
+        .await;
+    let mock_sent = server
+        .mock("POST", "/jrd/webapi?api=GetSendSMSResult")
+        .with_status(200)
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "SendStatus": 2 }, "id": "6.7" }"#)
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+    let mut mocks = alcatel_pin_required(server).await.mocks;
+    mocks.extend([mock_send, mock_not_sent, mock_sent]);
+    MockSet { mocks }
+}
+
+// Modem answers the first SendSMS with 503 and accepts it when repeated
+pub async fn alcatel_busy_then_sending_sms(server: &mut mockito::Server) -> MockSet {
+    let mock_busy = server
+        .mock("POST", "/jrd/webapi?api=SendSMS")
+        .with_status(503)
+        .expect(1)
+        .create_async()
+        .await;
+    let AlcatelMock {
+        mock_send,
+        mock_get_status,
+    } = sending_sms_is_successful(server).await;
+    MockSet {
+        mocks: vec![mock_busy, mock_send, mock_get_status],
+    }
+}
+
+// The first sms is refused because modem is still sending previous one, repeated is sent
+pub async fn alcatel_busy_sending_previous_sms(server: &mut mockito::Server) -> MockSet {
+    let mock_send = server
+        .mock("POST", "/jrd/webapi?api=SendSMS")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .expect(2)
+        .create_async()
+        .await;
+    let mock_busy_status = server
+        .mock("POST", "/jrd/webapi?api=GetSendSMSResult")
+        .with_status(200)
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "SendStatus": 3 }, "id": "6.7" }"#)
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+    let mock_sent_status = server
+        .mock("POST", "/jrd/webapi?api=GetSendSMSResult")
+        .with_status(200)
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "SendStatus": 2 }, "id": "6.7" }"#)
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![mock_send, mock_busy_status, mock_sent_status],
+    }
+}
+
+// SendSMS answered with JSON-RPC busy error once, then accepted
+pub async fn alcatel_send_rejected_as_busy(server: &mut mockito::Server) -> MockSet {
+    let mock_busy = server
+        .mock("POST", "/jrd/webapi?api=SendSMS")
+        .with_status(200)
+        .with_body(
+            r#"{ "jsonrpc": "2.0", "error": { "code": "060102", "message": "System busy" }, "id": "6.6" }"#,
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+    let AlcatelMock {
+        mock_send,
+        mock_get_status,
+    } = sending_sms_is_successful(server).await;
+    MockSet {
+        mocks: vec![mock_busy, mock_send, mock_get_status],
+    }
+}
+
+// SendSMS is accepted but checking its result fails with 503
+pub async fn alcatel_send_status_check_failing(server: &mut mockito::Server) -> MockSet {
+    let mock_send = server
+        .mock("POST", "/jrd/webapi?api=SendSMS")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+    let mock_get_status = server
+        .mock("POST", "/jrd/webapi?api=GetSendSMSResult")
+        .with_status(503)
+        .expect(1)
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![mock_send, mock_get_status],
+    }
+}
+
+// Report setting is off and is switched on before sending
+pub async fn alcatel_sending_with_delivery_reports(server: &mut mockito::Server) -> MockSet {
+    let mock_get_settings = server
+        .mock("POST", "/jrd/webapi?api=GetSMSSettings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "SMSReportSwitch": 0, "SMSCenter": "+48501200777", "StoreFlag": 0 }, "id": "6.5" }"#)
+        .create_async()
+        .await;
+    let mock_set_settings = server
+        .mock("POST", "/jrd/webapi?api=SetSMSSettings")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "params": { "SMSReportSwitch": 1, "SMSCenter": "+48501200777", "StoreFlag": 0 }
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "jsonrpc": "2.0", "result": {}, "id": "6.6" }"#)
+        .create_async()
+        .await;
+    let sending = sending_sms_is_successful(server).await;
+    MockSet {
+        mocks: vec![
+            mock_get_settings,
+            mock_set_settings,
+            sending.mock_send,
+            sending.mock_get_status,
+        ],
+    }
+}
+
+// Delivered report for 123456789 next to received sms and failed report for 987654321
+pub async fn alcatel_delivery_reports(server: &mut mockito::Server) -> MockSet {
+    let mock_contact_list = server
+        .mock("POST", "/jrd/webapi?api=GetSMSContactList")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{ "jsonrpc": "2.0", "result": { "SMSContactList": [
+                { "ContactId": 1, "PhoneNumber": ["123456789"], "SMSId": 22, "SMSType": 1, "SMSTime": "2026-10-18 10:30:00", "SMSContent": "Thanks", "TSMSCount": 2, "UnreadCount": 1 },
+                { "ContactId": 2, "PhoneNumber": ["987654321"], "SMSId": 23, "SMSType": 4, "SMSTime": "2026-10-18 10:01:00", "SMSContent": "Message failed", "TSMSCount": 1, "UnreadCount": 0 }
+            ], "Page": 0, "TotalPageCount": 1 }, "id": "6.2" }"#,
+        )
+        .create_async()
+        .await;
+    let mock_first_contact = server
+        .mock("POST", "/jrd/webapi?api=GetSMSContentList")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({ "params": { "ContactId": 1 } }),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{ "jsonrpc": "2.0", "result": { "ContactId": 1, "PhoneNumber": ["123456789"], "SMSContentList": [
+                { "SMSId": 21, "SMSType": 4, "SMSTime": "2026-10-18 10:00:05", "SMSContent": "Message delivered", "ReportStatus": 0 },
+                { "SMSId": 22, "SMSType": 1, "SMSTime": "2026-10-18 10:30:00", "SMSContent": "Thanks", "ReportStatus": 0 }
+            ], "Page": 0, "TotalPageCount": 1 }, "id": "6.3" }"#,
+        )
+        .create_async()
+        .await;
+    let mock_second_contact = server
+        .mock("POST", "/jrd/webapi?api=GetSMSContentList")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({ "params": { "ContactId": 2 } }),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{ "jsonrpc": "2.0", "result": { "ContactId": 2, "PhoneNumber": ["987654321"], "SMSContentList": [
+                { "SMSId": 23, "SMSType": 4, "SMSTime": "2026-10-18 10:01:00", "SMSContent": "Message failed", "ReportStatus": 0 }
+            ], "Page": 0, "TotalPageCount": 1 }, "id": "6.3" }"#,
+        )
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![mock_contact_list, mock_first_contact, mock_second_contact],
+    }
+}
+
+pub const USSD_BALANCE_CODE: &str = "*101#";
+pub const USSD_BALANCE_REPLY: &str = "Saldo: 12,50 PLN, wazne do 2026-12-31";
+
+// Network answers on the second check of USSD result
+pub async fn alcatel_ussd_is_successful(server: &mut mockito::Server) -> MockSet {
+    let mock_send = server
+        .mock("POST", "/jrd/webapi?api=SendUSSD")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({ "params": { "UssdContent": USSD_BALANCE_CODE, "UssdType": 1 } }),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "jsonrpc": "2.0", "result": {}, "id": "8.1" }"#)
+        .create_async()
+        .await;
+    let mock_sending = server
+        .mock("POST", "/jrd/webapi?api=GetUSSDSendResult")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "UssdType": 1, "SendState": 1, "UssdContent": "" }, "id": "8.2" }"#)
+        .create_async()
+        .await;
+    let mock_result = server
+        .mock("POST", "/jrd/webapi?api=GetUSSDSendResult")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "result": { "UssdType": 1, "SendState": 2, "UssdContent": USSD_BALANCE_REPLY },
+                "id": "8.2"
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let mock_end = server
+        .mock("POST", "/jrd/webapi?api=SetUSSDEnd")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "jsonrpc": "2.0", "result": {}, "id": "8.3" }"#)
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![mock_send, mock_sending, mock_result, mock_end],
+    }
+}
+
+async fn alcatel_login(server: &mut mockito::Server, token: i64) -> mockito::Mock {
+    server
+        .mock("POST", "/jrd/webapi?api=Login")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "method": "Login",
+            "params": {
+                "UserName": ALCATEL_ENCRYPTED_USERNAME,
+                "Password": ALCATEL_ENCRYPTED_PASSWORD
+            }
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{ "jsonrpc": "2.0", "result": {{ "token": {} }}, "id": "1.1" }}"#,
+            token
+        ))
+        .expect(1)
+        .create_async()
+        .await
+}
+
+async fn alcatel_send_sms(
+    server: &mut mockito::Server,
+    encrypted_token: &str,
+    status: usize,
+) -> mockito::Mock {
+    server
+        .mock("POST", "/jrd/webapi?api=SendSMS")
+        .match_header("_TclRequestVerificationToken", encrypted_token)
+        .with_status(status)
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await
+}
+
+async fn alcatel_send_status(server: &mut mockito::Server, encrypted_token: &str) -> mockito::Mock {
+    server
+        .mock("POST", "/jrd/webapi?api=GetSendSMSResult")
+        .match_header("_TclRequestVerificationToken", encrypted_token)
+        .with_status(200)
+        .with_body(r#"{ "jsonrpc": "2.0", "result": { "SendStatus": 2 }, "id": "6.7" }"#)
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await
+}
+
+pub const HILINK_SESSION: &str = "SessionID=hilink-session";
+pub const HILINK_TOKEN: &str = "hilink-token";
+const HILINK_LOGGED_IN_SESSION: &str = "SessionID=logged-in-session";
+const HILINK_LOGGED_IN_TOKEN: &str = "logged-in-token";
+// Password `secret` of user `admin` hashed with `HILINK_TOKEN`
+const HILINK_HASHED_PASSWORD: &str =
+    "MTk0Y2NmYzgxZjY0MzcyYThmYmUxYmZhZDcxNTBmMzFmOGQ0MDJiNTdkZGJhZjcyMjNkMTVlOGI0N2JjNDAwNw==";
+
+pub async fn hilink_sending_sms_is_successful(server: &mut mockito::Server) -> MockSet {
+    MockSet {
+        mocks: vec![
+            hilink_session(server).await,
+            hilink_send_sms(server, HILINK_SESSION, HILINK_TOKEN, 1).await,
+            hilink_send_status(
+                server,
+                "<SucPhone>123456789</SucPhone><FailPhone></FailPhone>",
+            )
+            .await,
+        ],
+    }
+}
+
+pub async fn hilink_sending_sms_failure(server: &mut mockito::Server) -> MockSet {
+    MockSet {
+        mocks: vec![
+            hilink_session(server).await,
+            hilink_send_sms(server, HILINK_SESSION, HILINK_TOKEN, 1).await,
+            hilink_send_status(
+                server,
+                "<SucPhone></SucPhone><FailPhone>123456789</FailPhone>",
+            )
+            .await,
+        ],
+    }
+}
+
+// Login as `admin` with password `secret` and send sms with session started by login
+pub async fn hilink_login_is_successful(server: &mut mockito::Server) -> MockSet {
+    let mock_login = server
+        .mock("POST", "/api/user/login")
+        .match_header("__RequestVerificationToken", HILINK_TOKEN)
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::Regex("<Username>admin</Username>".to_string()),
+            mockito::Matcher::Regex(format!("<Password>{}</Password>", HILINK_HASHED_PASSWORD)),
+        ]))
+        .with_status(200)
+        .with_header(
+            "set-cookie",
+            &format!("{}; path=/; HttpOnly", HILINK_LOGGED_IN_SESSION),
+        )
+        .with_header("__RequestVerificationTokenone", HILINK_LOGGED_IN_TOKEN)
+        .with_body("<response>OK</response>")
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![
+            hilink_session(server).await,
+            mock_login,
+            hilink_send_sms(server, HILINK_LOGGED_IN_SESSION, HILINK_LOGGED_IN_TOKEN, 1).await,
+            hilink_send_status(
+                server,
+                "<SucPhone>123456789</SucPhone><FailPhone></FailPhone>",
+            )
+            .await,
+        ],
+    }
+}
+
+pub async fn hilink_reading_inbox_is_successful(server: &mut mockito::Server) -> MockSet {
+    let mock_sms_list = server
+        .mock("POST", "/api/sms/sms-list")
+        .match_header("__RequestVerificationToken", HILINK_TOKEN)
+        .match_body(mockito::Matcher::Regex("<BoxType>1</BoxType>".to_string()))
+        .with_status(200)
+        .with_body(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<response>
+<Count>2</Count>
+<Messages>
+<Message><Smstat>1</Smstat><Index>40001</Index><Phone>+48123456789</Phone><Content>Are we meeting?</Content><Date>2023-10-21 12:00:00</Date><Sca></Sca><SaveType>4</SaveType><Priority>0</Priority><SmsType>1</SmsType></Message>
+<Message><Smstat>0</Smstat><Index>40002</Index><Phone>+48123456789</Phone><Content>See you &amp; tomorrow</Content><Date>2023-10-21 12:30:00</Date><Sca></Sca><SaveType>4</SaveType><Priority>0</Priority><SmsType>1</SmsType></Message>
+</Messages>
+</response>"#,
+        )
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![hilink_session(server).await, mock_sms_list],
+    }
+}
+
+pub async fn hilink_deleting_sms_is_successful(server: &mut mockito::Server) -> MockSet {
+    let mock_delete = server
+        .mock("POST", "/api/sms/delete-sms")
+        .match_body(mockito::Matcher::Regex("<Index>40002</Index>".to_string()))
+        .with_status(200)
+        .with_body("<response>OK</response>")
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![hilink_session(server).await, mock_delete],
+    }
+}
+
+pub async fn hilink_status_is_successful(server: &mut mockito::Server) -> MockSet {
+    let responses = [
+        (
+            "/api/monitoring/status",
+            "<ConnectionStatus>901</ConnectionStatus><SignalIcon>4</SignalIcon><CurrentNetworkType>19</CurrentNetworkType><RoamingStatus>1</RoamingStatus><SimStatus>1</SimStatus>",
+        ),
+        (
+            "/api/net/current-plmn",
+            "<State>0</State><FullName>Plus</FullName><ShortName>Plus</ShortName><Numeric>26001</Numeric><Rat>7</Rat>",
+        ),
+        (
+            "/api/pin/status",
+            "<SimState>257</SimState><PinOptState>258</PinOptState><SimPinTimes>3</SimPinTimes><SimPukTimes>10</SimPukTimes>",
+        ),
+        (
+            "/api/sms/sms-count",
+            "<LocalUnread>1</LocalUnread><LocalInbox>7</LocalInbox><LocalOutbox>2</LocalOutbox><LocalDraft>1</LocalDraft><LocalMax>500</LocalMax><SimMax>30</SimMax>",
+        ),
+    ];
+    let mut mocks = vec![hilink_session(server).await];
+    for (path, response) in responses {
+        mocks.push(
+            server
+                .mock("GET", path)
+                .match_header("cookie", HILINK_SESSION)
+                .with_status(200)
+                .with_body(format!("<response>{}</response>", response))
+                .create_async()
+                .await,
+        );
+    }
+    MockSet { mocks }
+}
+
+// Sms fails until SIM is unlocked with `SIM_PIN`, the second attempt succeeds
+pub async fn hilink_sim_locked(server: &mut mockito::Server) -> MockSet {
+    let mock_pin_status = server
+        .mock("GET", "/api/pin/status")
+        .with_status(200)
+        .with_body("<response><SimState>260</SimState><PinOptState>259</PinOptState><SimPinTimes>3</SimPinTimes><SimPukTimes>10</SimPukTimes></response>")
+        .expect(1)
+        .create_async()
+        .await;
+    let mock_unlock = server
+        .mock("POST", "/api/pin/operate")
+        .match_body(mockito::Matcher::Regex(format!(
+            "<CurrentPin>{}</CurrentPin>",
+            SIM_PIN
+        )))
+        .with_status(200)
+        .with_body("<response>OK</response>")
+        .expect(1)
+        .create_async()
+        .await;
+    let mock_failed = server
+        .mock("GET", "/api/sms/send-status")
+        .with_status(200)
+        .with_body("<response><Phone></Phone><SucPhone></SucPhone><FailPhone>123456789</FailPhone><TotalCount>1</TotalCount><CurIndex>1</CurIndex></response>")
+        .expect(1)
+        .create_async()
+        .await;
+    MockSet {
+        mocks: vec![
+            hilink_session(server).await,
+            hilink_send_sms(server, HILINK_SESSION, HILINK_TOKEN, 2).await,
+            mock_pin_status,
+            mock_unlock,
+            mock_failed,
+            hilink_send_status(
+                server,
+                "<SucPhone>123456789</SucPhone><FailPhone></FailPhone>",
+            )
+            .await,
+        ],
+    }
+}
+
+async fn hilink_session(server: &mut mockito::Server) -> mockito::Mock {
+    server
+        .mock("GET", "/api/webserver/SesTokInfo")
+        .with_status(200)
+        .with_body(format!(
+            "<response><SesInfo>{}</SesInfo><TokInfo>{}</TokInfo></response>",
+            HILINK_SESSION, HILINK_TOKEN
+        ))
+        .create_async()
+        .await
+}
+
+async fn hilink_send_sms(
+    server: &mut mockito::Server,
+    session: &str,
+    token: &str,
+    hits: usize,
+) -> mockito::Mock {
+    server
+        .mock("POST", "/api/sms/send-sms")
+        .match_header("cookie", session)
+        .match_header("__RequestVerificationToken", token)
+        .match_body(mockito::Matcher::Regex(
+            "<Phone>123456789</Phone>".to_string(),
+        ))
+        .with_status(200)
+        .with_body("<response>OK</response>")
+        .expect(hits)
+        .create_async()
+        .await
+}
+
+async fn hilink_send_status(server: &mut mockito::Server, phones: &str) -> mockito::Mock {
+    server
+        .mock("GET", "/api/sms/send-status")
+        .with_status(200)
+        .with_body(format!(
+            "<response><Phone></Phone>{}<TotalCount>1</TotalCount><CurIndex>1</CurIndex></response>",
+            phones
+        ))
+        .create_async()
+        .await
+}
+
+// Mocks of all requests needed by one operation of a provider
+pub struct MockSet {
+    mocks: Vec<mockito::Mock>,
+}
+
+impl MockSet {
+    pub fn assert_called(&self) {
+        for mock in &self.mocks {
+            mock.assert();
+        }
+    }
+}
+
+pub struct FakeAtModem {
+    pub device: String,
+    commands: Arc<Mutex<Vec<String>>>,
+    sent_sms: Arc<Mutex<Vec<(String, String)>>>,
+    report_requests: Arc<Mutex<Vec<bool>>>,
+    // Keeps pseudo terminal open between connections of tested service
+    _slave: SerialStream,
+}
+
+impl FakeAtModem {
+    pub fn commands(&self) -> Vec<String> {
+        self.commands.lock().unwrap().clone()
+    }
+
+    pub fn sent_sms(&self) -> Vec<(String, String)> {
+        self.sent_sms.lock().unwrap().clone()
+    }
+
+    // Whether status report was requested, for every submitted PDU
+    pub fn report_requests(&self) -> Vec<bool> {
+        self.report_requests.lock().unwrap().clone()
+    }
+}
+
+pub fn at_modem_is_working() -> FakeAtModem {
+    spawn_fake_at_modem(RejectSms::None, None)
+}
+
+pub fn at_modem_rejecting_sms() -> FakeAtModem {
+    spawn_fake_at_modem(RejectSms::All, None)
+}
+
+// Only sms to given recipient is rejected, the others are sent
+pub fn at_modem_rejecting_sms_to(recipient: &str) -> FakeAtModem {
+    spawn_fake_at_modem(RejectSms::To(recipient.to_string()), None)
+}
+
+// The first sms is rejected with busy SIM, next ones are sent
+pub fn at_modem_busy_once() -> FakeAtModem {
+    spawn_fake_at_modem(RejectSms::BusyOnce, None)
+}
+
+// The second submitted part is rejected with busy SIM, the others are sent
+pub fn at_modem_busy_on_second_part() -> FakeAtModem {
+    spawn_fake_at_modem(RejectSms::BusyOnSecond, None)
+}
+
+// SIM stays locked until `AT+CPIN` is called with given pin
+pub fn at_modem_with_locked_sim(pin: &str) -> FakeAtModem {
+    spawn_fake_at_modem(RejectSms::None, Some(pin.to_string()))
+}
+
+enum RejectSms {
+    None,
+    All,
+    To(String),
+    BusyOnce,
+    BusyOnSecond,
+}
+
+impl RejectSms {
+    // Error answered to submitted sms, busy modem rejects only the first one
+    fn error_for(&self, recipient: &str, submitted: usize) -> Option<&'static str> {
+        match self {
+            RejectSms::None => None,
+            RejectSms::All => Some("+CMS ERROR: 500"),
+            RejectSms::To(rejected) if rejected == recipient => Some("+CMS ERROR: 500"),
+            RejectSms::To(_) => None,
+            RejectSms::BusyOnce if submitted == 0 => Some("+CMS ERROR: 314"),
+            RejectSms::BusyOnce => None,
+            RejectSms::BusyOnSecond if submitted == 1 => Some("+CMS ERROR: 314"),
+            RejectSms::BusyOnSecond => None,
+        }
+    }
+}
+
+// Read message, sent message that is not part of inbox, unread message split into
+// two parts, the second one encoded as UCS-2, and truncated message that can't be decoded
+const AT_MODEM_INBOX: &str = "\r\n+CMGL: 1,1,,33\r\n\
+    07914806010000F0040B918421436587F90000320112210000800F417919742F83DAE5323DED3EFF00\r\n\
+    +CMGL: 2,3,,17\r\n0011000B918421436587F90000AA03D9F21C\r\n\
+    +CMGL: 3,0,,38\r\n\
+    07914806010000F0440B918421436587F9000032011221030080150500032A0201886F90FE2D0E8FF565773A0C02\r\n\
+    +CMGL: 4,0,,51\r\n\
+    07914806010000F0440B918421436587F9000832011221031080200500032A0202006A007500740072006F002C00200063007A0065015B01070021\r\n\
+    +CMGL: 5,1,,4\r\n07914806\r\n\
+    \r\nOK\r\n";
+
+// Index of the first status report stored by fake modem, after messages of inbox
+const AT_MODEM_FIRST_REPORT_ID: i64 = 10;
+
+// Status report of successful delivery listed by `AT+CMGL`, delivered on 2026-10-18 10:00:05
+fn at_modem_status_report(id: i64, reference: u8, recipient: &str) -> String {
+    let mut tpdu = vec![0x06, reference];
+    tpdu.extend(crate::pdu::encode_address(recipient).expect("valid recipient"));
+    tpdu.extend([0x62, 0x01, 0x81, 0x01, 0x00, 0x00, 0x80]);
+    tpdu.extend([0x62, 0x01, 0x81, 0x01, 0x00, 0x50, 0x80]);
+    tpdu.push(0x00);
+    format!(
+        "\r\n+CMGL: {},0,,{}\r\n00{}\r\n",
+        id,
+        tpdu.len(),
+        crate::pdu::to_hex(&tpdu)
+    )
+}
+
+fn spawn_fake_at_modem(reject_sms: RejectSms, sim_pin: Option<String>) -> FakeAtModem {
+    let (master, slave) = SerialStream::pair().expect("pseudo terminal pair");
+    let modem = FakeAtModem {
+        device: slave.name().expect("pseudo terminal name"),
+        commands: Arc::new(Mutex::new(vec![])),
+        sent_sms: Arc::new(Mutex::new(vec![])),
+        report_requests: Arc::new(Mutex::new(vec![])),
+        _slave: slave,
+    };
+    tokio::spawn(run_fake_at_modem(
+        master,
+        reject_sms,
+        sim_pin,
+        modem.commands.clone(),
+        modem.sent_sms.clone(),
+        modem.report_requests.clone(),
+    ));
+    modem
+}
+
+async fn run_fake_at_modem(
+    mut master: SerialStream,
+    reject_sms: RejectSms,
+    mut sim_pin: Option<String>,
+    commands: Arc<Mutex<Vec<String>>>,
+    sent_sms: Arc<Mutex<Vec<(String, String)>>>,
+    report_requests: Arc<Mutex<Vec<bool>>>,
+) {
+    let mut buffer = vec![];
+    let mut reports: Vec<(i64, String)> = vec![];
+    let mut awaiting_pdu = false;
+    let mut chunk = [0u8; 256];
+    loop {
+        let read = match master.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+        loop {
+            if awaiting_pdu {
+                let Some(end) = buffer.iter().position(|b| *b == 0x1A) else {
+                    break;
+                };
+                let pdu: Vec<u8> = buffer.drain(..=end).collect();
+                awaiting_pdu = false;
+                let submit = crate::pdu::decode_submit(&String::from_utf8_lossy(&pdu[..end]))
+                    .expect("valid SMS-SUBMIT PDU");
+                let submitted = report_requests.lock().unwrap().len();
+                report_requests.lock().unwrap().push(submit.status_report);
+                let error = reject_sms.error_for(&submit.recipient, submitted);
+                // Every submitted part gets next message reference
+                let reference = submitted as u8;
+                if submit.status_report && error.is_none() {
+                    let id = AT_MODEM_FIRST_REPORT_ID + reports.len() as i64;
+                    let report = at_modem_status_report(id, reference, &submit.recipient);
+                    reports.push((id, report));
+                }
+                sent_sms
+                    .lock()
+                    .unwrap()
+                    .push((submit.recipient, submit.text));
+                let response = match error {
+                    Some(error) => format!("\r\n{}\r\n", error),
+                    None => format!("\r\n+CMGS: {}\r\n\r\nOK\r\n", reference),
+                };
+                let _ = master.write_all(response.as_bytes()).await;
+                continue;
+            }
+            let Some(end) = buffer.iter().position(|b| *b == b'\r') else {
+                break;
+            };
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let command = String::from_utf8_lossy(&line).trim().to_string();
+            if command.is_empty() {
+                continue;
+            }
+            commands.lock().unwrap().push(command.clone());
+            if command == format!("AT+CUSD=1,\"{}\",15", USSD_BALANCE_CODE) && sim_pin.is_none() {
+                let response = format!("\r\nOK\r\n\r\n+CUSD: 0,\"{}\",15\r\n", USSD_BALANCE_REPLY);
+                let _ = master.write_all(response.as_bytes()).await;
+                continue;
+            }
+            if command == "AT+CMGL=4" && sim_pin.is_none() {
+                let listed: String = reports.iter().map(|(_, report)| report.as_str()).collect();
+                let response = format!("{}{}", listed, AT_MODEM_INBOX);
+                let _ = master.write_all(response.as_bytes()).await;
+                continue;
+            }
+            let response = match command.as_str() {
+                "ATE0" => "\r\nOK\r\n",
+                "AT+CPIN?" if sim_pin.is_some() => "\r\n+CPIN: SIM PIN\r\n\r\nOK\r\n",
+                "AT+CPIN?" => "\r\n+CPIN: READY\r\n\r\nOK\r\n",
+                command if command.starts_with("AT+CPIN=") => {
+                    let pin = command["AT+CPIN=".len()..].trim_matches('"');
+                    if sim_pin.as_deref() == Some(pin) {
+                        sim_pin = None;
+                        "\r\nOK\r\n"
+                    } else {
+                        "\r\n+CME ERROR: 16\r\n"
+                    }
+                }
+                // SIM PIN required
+                _ if sim_pin.is_some() => "\r\n+CME ERROR: 11\r\n",
+                "AT+CMGF=0" => "\r\nOK\r\n",
+                "AT+CNMI=2,1,0,2,0" => "\r\nOK\r\n",
+                "AT+COPS?" => "\r\n+COPS: 0,0,\"Orange PL\",7\r\n\r\nOK\r\n",
+                "AT+CSQ" => "\r\n+CSQ: 17,99\r\n\r\nOK\r\n",
+                "AT+CREG?" => "\r\n+CREG: 0,1\r\n\r\nOK\r\n",
+                "AT+CLCK=\"SC\",2" => "\r\n+CLCK: 1\r\n\r\nOK\r\n",
+                "AT+CPMS?" => "\r\n+CPMS: \"SM\",4,30,\"SM\",4,30,\"SM\",4,30\r\n\r\nOK\r\n",
+                command if command.starts_with("AT+CMGD=") => {
+                    let id = &command["AT+CMGD=".len()..];
+                    reports.retain(|(report_id, _)| report_id.to_string() != id);
+                    "\r\nOK\r\n"
+                }
+                command if command.starts_with("AT+CMGS=") => {
+                    awaiting_pdu = true;
+                    "\r\n> "
+                }
+                _ => "\r\nERROR\r\n",
+            };
+            let _ = master.write_all(response.as_bytes()).await;
+        }
+    }
+}
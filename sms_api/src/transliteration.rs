@@ -0,0 +1,56 @@
+use crate::encoding;
+
+// Replaces characters outside of GSM-7 alphabet with their closest GSM-7 equivalents.
+// Characters without known replacement are kept, so message may still need UCS-2.
+pub fn transliterate(text: &str) -> String {
+    let mut transliterated = String::with_capacity(text.len());
+    for c in text.chars() {
+        match replacement(c) {
+            Some(replacement) if !encoding::is_gsm7(c) => transliterated.push_str(replacement),
+            _ => transliterated.push(c),
+        }
+    }
+    transliterated
+}
+
+fn replacement(c: char) -> Option<&'static str> {
+    let replacement = match c {
+        'ą' | 'á' | 'â' | 'ã' | 'ă' | 'ā' => "a",
+        'Ą' | 'Á' | 'Â' | 'Ã' | 'Ă' | 'Ā' | 'À' => "A",
+        'ć' | 'č' | 'ç' => "c",
+        'Ć' | 'Č' => "C",
+        'ď' => "d",
+        'Ď' => "D",
+        'ę' | 'ě' | 'ê' | 'ë' | 'ē' => "e",
+        'Ę' | 'Ě' | 'Ê' | 'Ë' | 'Ē' | 'È' => "E",
+        'í' | 'î' | 'ï' | 'ī' => "i",
+        'Í' | 'Î' | 'Ï' | 'Ī' | 'Ì' => "I",
+        'ł' | 'ľ' | 'ĺ' => "l",
+        'Ł' | 'Ľ' | 'Ĺ' => "L",
+        'ń' | 'ň' => "n",
+        'Ń' | 'Ň' => "N",
+        'ó' | 'ô' | 'õ' | 'ő' | 'ō' => "o",
+        'Ó' | 'Ô' | 'Õ' | 'Ő' | 'Ō' | 'Ò' => "O",
+        'ř' | 'ŕ' => "r",
+        'Ř' | 'Ŕ' => "R",
+        'ś' | 'š' | 'ş' => "s",
+        'Ś' | 'Š' | 'Ş' => "S",
+        'ť' | 'ţ' => "t",
+        'Ť' | 'Ţ' => "T",
+        'ú' | 'ů' | 'û' | 'ű' | 'ū' => "u",
+        'Ú' | 'Ů' | 'Û' | 'Ű' | 'Ū' | 'Ù' => "U",
+        'ý' | 'ÿ' => "y",
+        'Ý' | 'Ÿ' => "Y",
+        'ź' | 'ż' | 'ž' => "z",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        '‘' | '’' | '‚' | '‛' | '′' | '`' | '´' => "'",
+        '“' | '”' | '„' | '‟' | '″' | '«' | '»' => "\"",
+        '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => "-",
+        '…' => "...",
+        '•' | '·' => "*",
+        '\u{a0}' | '\u{2002}' | '\u{2003}' | '\u{2009}' | '\u{202f}' => " ",
+        '\t' => " ",
+        _ => return None,
+    };
+    Some(replacement)
+}
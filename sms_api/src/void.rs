@@ -1,12 +1,48 @@
 use async_trait::async_trait;
 
-use crate::{SmsError, SmsService};
+use crate::{DeliveryReport, Inbox, ModemStatus, SendReport, SmsError, SmsService};
 
 pub(crate) struct VoidSmsService;
 
 #[async_trait]
 impl SmsService for VoidSmsService {
-    async fn send_sms(&self, _msg: &str, _phone_numbers: &[&str]) -> Result<(), SmsError> {
+    async fn send_sms(&self, _msg: &str, phone_numbers: &[&str]) -> Result<SendReport, SmsError> {
+        let mut report = SendReport::default();
+        for phone in phone_numbers {
+            report.add(phone, Ok(()));
+        }
+        Ok(report)
+    }
+
+    async fn read_inbox(&self) -> Result<Inbox, SmsError> {
+        Ok(Inbox::default())
+    }
+
+    async fn delete_sms(&self, _id: i64) -> Result<(), SmsError> {
         Ok(())
     }
+
+    async fn read_delivery_reports(&self) -> Result<Vec<DeliveryReport>, SmsError> {
+        Ok(vec![])
+    }
+
+    async fn status(&self) -> Result<ModemStatus, SmsError> {
+        Ok(ModemStatus {
+            operator: "Void".to_string(),
+            network_type: "None".to_string(),
+            signal_bars: 0,
+            roaming: false,
+            sim_state: "None".to_string(),
+            pin_state: "None".to_string(),
+            sms_storage: None,
+        })
+    }
+
+    async fn unlock(&self, _pin: &str) -> Result<(), SmsError> {
+        Ok(())
+    }
+
+    async fn send_ussd(&self, _code: &str) -> Result<String, SmsError> {
+        Ok(String::new())
+    }
 }
@@ -0,0 +1,302 @@
+use sms_api::{
+    sms_mock_api::{self, mockito},
+    DeliveryStatus, RecipientStatus, SimLock, SmsError,
+};
+use sms_config::config::{RetryConf, SmsApiConf, SmsApiProvider};
+
+fn alcatel_config(url: String, credentials: Option<(&str, &str)>, pin: Option<&str>) -> SmsApiConf {
+    SmsApiConf {
+        provider: SmsApiProvider::Alcatel {
+            url,
+            retry_count: 3,
+            retry_delay: 50,
+            username: credentials.map(|(username, _)| username.to_string()),
+            password: credentials.map(|(_, password)| password.to_string()),
+            pin: pin.map(str::to_string),
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn should_login_once_and_reuse_token() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_login_is_successful(&mut server).await;
+    let config = alcatel_config(server.url(), Some(("admin", "secret")), None);
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_login_again_when_token_expired() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_token_expired(&mut server).await;
+    let config = alcatel_config(server.url(), Some(("admin", "secret")), None);
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_read_modem_status() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_status_is_successful(&mut server).await;
+    let service = sms_api::create_service(&alcatel_config(server.url(), None, None)).unwrap();
+
+    // when
+    let status = service.status().await.unwrap();
+
+    // then
+    mock_handler.assert_called();
+    assert_eq!(status.operator, "Orange PL");
+    assert_eq!(status.network_type, "LTE");
+    assert_eq!(status.signal_bars, 3);
+    assert!(!status.roaming);
+    assert_eq!(status.sim_state, "Ready");
+    assert_eq!(status.pin_state, "Enabled, verified");
+    let storage = status.sms_storage.unwrap();
+    assert_eq!((storage.used, storage.capacity), (12, 100));
+}
+
+#[tokio::test]
+async fn should_unlock_sim_and_send_again_when_pin_is_configured() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_sim_locked(&mut server).await;
+    let config = alcatel_config(server.url(), None, Some(sms_mock_api::SIM_PIN));
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_repeat_sending_when_modem_is_busy() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_busy_then_sending_sms(&mut server).await;
+    let config = SmsApiConf {
+        retry: RetryConf {
+            initial_delay: 10,
+            ..RetryConf::default()
+        },
+        ..alcatel_config(server.url(), None, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_repeat_sms_refused_while_previous_one_is_sent() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_busy_sending_previous_sms(&mut server).await;
+    let config = SmsApiConf {
+        retry: RetryConf {
+            initial_delay: 10,
+            ..RetryConf::default()
+        },
+        ..alcatel_config(server.url(), None, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_repeat_sms_rejected_with_busy_error() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_send_rejected_as_busy(&mut server).await;
+    let config = SmsApiConf {
+        retry: RetryConf {
+            initial_delay: 10,
+            ..RetryConf::default()
+        },
+        ..alcatel_config(server.url(), None, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_not_repeat_sms_when_send_result_check_fails() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_send_status_check_failing(&mut server).await;
+    let config = SmsApiConf {
+        retry: RetryConf {
+            initial_delay: 10,
+            ..RetryConf::default()
+        },
+        ..alcatel_config(server.url(), None, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    let report = result.unwrap();
+    assert!(
+        matches!(
+            report.recipients[0].status,
+            RecipientStatus::Failed(SmsError::Unconfirmed(_))
+        ),
+        "{:?}",
+        report
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_report_locked_sim_when_pin_is_not_configured() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let _mock_handler = sms_mock_api::alcatel_sim_locked(&mut server).await;
+    let service = sms_api::create_service(&alcatel_config(server.url(), None, None)).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321"])
+        .await;
+
+    // then
+    let report = result.unwrap();
+    assert!(matches!(
+        report.recipients[0].status,
+        RecipientStatus::Failed(SmsError::SimLocked(SimLock::PinRequired))
+    ));
+    assert!(matches!(
+        report.recipients[1].status,
+        RecipientStatus::Skipped
+    ));
+}
+
+#[tokio::test]
+async fn should_wait_for_ussd_reply() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_ussd_is_successful(&mut server).await;
+    let service = sms_api::create_service(&alcatel_config(server.url(), None, None)).unwrap();
+
+    // when
+    let reply = service.send_ussd(sms_mock_api::USSD_BALANCE_CODE).await;
+
+    // then
+    assert_eq!(reply.unwrap(), sms_mock_api::USSD_BALANCE_REPLY);
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_enable_delivery_reports_before_sending() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_sending_with_delivery_reports(&mut server).await;
+    let config = SmsApiConf {
+        delivery_reports: true,
+        ..alcatel_config(server.url(), None, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_read_delivery_reports() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_delivery_reports(&mut server).await;
+    let service = sms_api::create_service(&alcatel_config(server.url(), None, None)).unwrap();
+
+    // when
+    let reports = service.read_delivery_reports().await.unwrap();
+
+    // then
+    mock_handler.assert_called();
+    let reports: Vec<(i64, &str, DeliveryStatus)> = reports
+        .iter()
+        .map(|r| (r.id, r.phone.as_str(), r.status))
+        .collect();
+    assert_eq!(
+        reports,
+        vec![
+            (21, "123456789", DeliveryStatus::Delivered),
+            (23, "987654321", DeliveryStatus::Failed),
+        ]
+    );
+}
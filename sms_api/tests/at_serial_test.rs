@@ -0,0 +1,391 @@
+use sms_api::{sms_mock_api, DeliveryStatus, RecipientStatus, SimLock, SmsError};
+use sms_config::config::{RetryConf, SmsApiConf, SmsApiProvider};
+
+fn at_serial_config(device: &str, pin: Option<&str>) -> SmsApiConf {
+    SmsApiConf {
+        provider: SmsApiProvider::AtSerial {
+            device: device.to_string(),
+            baud_rate: 115200,
+            pin: pin.map(str::to_string),
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn should_send_sms_in_pdu_mode() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello {world} €", &["123456789", "987654321"])
+        .await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    assert!(modem.commands().contains(&"AT+CMGF=0".to_string()));
+    assert_eq!(
+        modem.sent_sms(),
+        vec![
+            ("123456789".to_string(), "Hello {world} €".to_string()),
+            ("987654321".to_string(), "Hello {world} €".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn should_send_long_unicode_sms_in_parts() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+    let message = "Zażółć gęślą jaźń. ".repeat(5);
+
+    // when
+    let result = service.send_sms(&message, &["+48123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    let sent = modem.sent_sms();
+    assert_eq!(sent.len(), 2);
+    assert!(sent.iter().all(|(phone, _)| phone == "+48123456789"));
+    assert_eq!(format!("{}{}", sent[0].1, sent[1].1), message);
+}
+
+#[tokio::test]
+async fn should_fail_when_modem_rejects_sms() {
+    // given
+    let modem = sms_mock_api::at_modem_rejecting_sms();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    let report = result.unwrap();
+    assert!(matches!(
+        &report.recipients[0].status,
+        RecipientStatus::Failed(e) if e.to_string().contains("+CMS ERROR: 500")
+    ));
+}
+
+#[tokio::test]
+async fn should_repeat_sms_rejected_by_busy_modem() {
+    // given
+    let modem = sms_mock_api::at_modem_busy_once();
+    let config = SmsApiConf {
+        retry: RetryConf {
+            initial_delay: 10,
+            ..RetryConf::default()
+        },
+        ..at_serial_config(&modem.device, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321"])
+        .await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    let recipients: Vec<String> = modem
+        .sent_sms()
+        .into_iter()
+        .map(|(phone, _)| phone)
+        .collect();
+    assert_eq!(recipients, vec!["123456789", "123456789", "987654321"]);
+}
+
+#[tokio::test]
+async fn should_not_repeat_long_sms_when_modem_is_busy_after_the_first_part() {
+    // given
+    let modem = sms_mock_api::at_modem_busy_on_second_part();
+    let config = SmsApiConf {
+        retry: RetryConf {
+            initial_delay: 10,
+            ..RetryConf::default()
+        },
+        ..at_serial_config(&modem.device, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+    let text = "Zażółć gęślą jaźń. ".repeat(5);
+
+    // when
+    let result = service.send_sms(&text, &["123456789"]).await;
+
+    // then
+    let report = result.unwrap();
+    assert!(
+        matches!(
+            report.recipients[0].status,
+            RecipientStatus::Failed(SmsError::Unconfirmed(_))
+        ),
+        "{:?}",
+        report
+    );
+    assert_eq!(modem.sent_sms().len(), 2);
+}
+
+#[tokio::test]
+async fn should_continue_sending_after_rejected_recipient() {
+    // given
+    let modem = sms_mock_api::at_modem_rejecting_sms_to("123456789");
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321"])
+        .await;
+
+    // then
+    let report = result.unwrap();
+    assert_eq!(report.not_sent_count(), 1);
+    assert_eq!(report.recipients[0].phone, "123456789");
+    assert!(matches!(
+        report.recipients[0].status,
+        RecipientStatus::Failed(_)
+    ));
+    assert_eq!(report.recipients[1].phone, "987654321");
+    assert!(matches!(report.recipients[1].status, RecipientStatus::Sent));
+    assert_eq!(modem.sent_sms().len(), 2);
+}
+
+#[tokio::test]
+async fn should_read_only_received_sms_from_modem() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let inbox = service.read_inbox().await.unwrap();
+
+    // then
+    let messages = inbox.messages;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].id, 3);
+    assert_eq!(messages[0].text, "Do zobaczenia jutro, cześć!");
+    assert_eq!(messages[0].phone, "+48123456789");
+    assert!(messages[0].unread);
+    assert_eq!(messages[1].text, "Are we meeting?");
+    assert!(!messages[1].unread);
+    let undecodable: Vec<i64> = inbox.undecodable.iter().map(|sms| sms.id).collect();
+    assert_eq!(undecodable, vec![5]);
+}
+
+#[tokio::test]
+async fn should_delete_sms_from_modem() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service.delete_sms(3).await;
+
+    // then
+    assert!(result.is_ok());
+    assert!(modem.commands().contains(&"AT+CMGD=3".to_string()));
+}
+
+#[tokio::test]
+async fn should_read_modem_status() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let status = service.status().await.unwrap();
+
+    // then
+    assert_eq!(status.operator, "Orange PL");
+    assert_eq!(status.network_type, "LTE");
+    assert_eq!(status.signal_bars, 3);
+    assert!(!status.roaming);
+    assert_eq!(status.sim_state, "Ready");
+    assert_eq!(status.pin_state, "Enabled, verified");
+    let storage = status.sms_storage.unwrap();
+    assert_eq!((storage.used, storage.capacity), (4, 30));
+}
+
+#[tokio::test]
+async fn should_unlock_sim_with_configured_pin_before_sending() {
+    // given
+    let modem = sms_mock_api::at_modem_with_locked_sim(sms_mock_api::SIM_PIN);
+    let config = at_serial_config(&modem.device, Some(sms_mock_api::SIM_PIN));
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    assert!(modem.commands().contains(&"AT+CPIN=\"1234\"".to_string()));
+    assert_eq!(modem.sent_sms().len(), 1);
+}
+
+#[tokio::test]
+async fn should_fail_with_sim_locked_when_pin_is_not_configured() {
+    // given
+    let modem = sms_mock_api::at_modem_with_locked_sim(sms_mock_api::SIM_PIN);
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(matches!(
+        result,
+        Err(SmsError::SimLocked(SimLock::PinRequired))
+    ));
+    assert!(modem.sent_sms().is_empty());
+}
+
+#[tokio::test]
+async fn should_reject_pin_which_is_not_only_digits() {
+    // given
+    let modem = sms_mock_api::at_modem_with_locked_sim(sms_mock_api::SIM_PIN);
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service.unlock("1234\"\rAT+CMGD=1").await;
+
+    // then
+    assert!(
+        matches!(result, Err(SmsError::ModemError(_))),
+        "{:?}",
+        result
+    );
+    assert!(!modem
+        .commands()
+        .iter()
+        .any(|command| command.starts_with("AT+CPIN=") || command.starts_with("AT+CMGD")));
+}
+
+#[tokio::test]
+async fn should_unlock_sim_on_demand() {
+    // given
+    let modem = sms_mock_api::at_modem_with_locked_sim(sms_mock_api::SIM_PIN);
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service.unlock(sms_mock_api::SIM_PIN).await;
+
+    // then
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(service.status().await.unwrap().sim_state, "Ready");
+}
+
+#[tokio::test]
+async fn should_reject_ussd_code_outside_of_ussd_alphabet() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let result = service.send_ussd("*101#\",15\rAT+CMGD=1").await;
+
+    // then
+    assert!(
+        matches!(result, Err(SmsError::ModemError(_))),
+        "{:?}",
+        result
+    );
+    assert!(!modem
+        .commands()
+        .iter()
+        .any(|command| command.starts_with("AT+CUSD") || command.starts_with("AT+CMGD")));
+}
+
+#[tokio::test]
+async fn should_send_ussd_and_return_network_reply() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let service = sms_api::create_service(&at_serial_config(&modem.device, None)).unwrap();
+
+    // when
+    let reply = service.send_ussd(sms_mock_api::USSD_BALANCE_CODE).await;
+
+    // then
+    assert_eq!(reply.unwrap(), sms_mock_api::USSD_BALANCE_REPLY);
+    assert!(modem
+        .commands()
+        .contains(&"AT+CUSD=1,\"*101#\",15".to_string()));
+}
+
+#[tokio::test]
+async fn should_request_report_for_last_part_and_read_it() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let config = SmsApiConf {
+        delivery_reports: true,
+        ..at_serial_config(&modem.device, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+    let text = "Zażółć gęślą jaźń. ".repeat(5);
+
+    // when
+    let result = service.send_sms(&text, &["+48123456789"]).await;
+    let reports = service.read_delivery_reports().await.unwrap();
+    let inbox = service.read_inbox().await.unwrap();
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    assert!(modem.commands().contains(&"AT+CNMI=2,1,0,2,0".to_string()));
+    assert_eq!(modem.report_requests(), vec![false, true]);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].phone, "+48123456789");
+    assert_eq!(reports[0].status, DeliveryStatus::Delivered);
+    assert_eq!(inbox.messages.len(), 2);
+}
+
+#[tokio::test]
+async fn should_give_report_reference_of_last_part_to_recipient() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let config = SmsApiConf {
+        delivery_reports: true,
+        ..at_serial_config(&modem.device, None)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+    let text = "Zażółć gęślą jaźń. ".repeat(5);
+
+    // when
+    let report = service
+        .send_sms(&text, &["+48123456789", "+48987654321"])
+        .await
+        .unwrap();
+    let reports = service.read_delivery_reports().await.unwrap();
+
+    // then
+    let sent: Vec<Option<u8>> = report.recipients.iter().map(|r| r.reference).collect();
+    let reported: Vec<Option<u8>> = reports.iter().map(|r| r.reference).collect();
+    assert_eq!(sent, vec![Some(1), Some(3)]);
+    assert_eq!(reported, sent);
+}
@@ -0,0 +1,58 @@
+use sms_api::encoding::{self, SmsEncoding};
+
+#[test]
+fn should_fit_gsm7_message_in_one_part() {
+    // given
+    let text = "a".repeat(160);
+
+    // when
+    let parts = encoding::analyze(&text);
+
+    // then
+    assert_eq!(parts.encoding, SmsEncoding::Gsm7);
+    assert_eq!(parts.segments, 1);
+    assert!(parts.ucs2_characters.is_empty());
+}
+
+#[test]
+fn should_count_extension_characters_as_two_septets() {
+    // given
+    let text = format!("{}€", "a".repeat(152));
+
+    // when
+    let parts = encoding::analyze(&text);
+
+    // then
+    assert_eq!(parts.encoding, SmsEncoding::Gsm7);
+    assert_eq!(parts.units, 154);
+    assert_eq!(parts.segments, 1);
+    assert_eq!(encoding::analyze(&format!("{}€", "a".repeat(159))).segments, 2);
+}
+
+#[test]
+fn should_not_split_escape_sequence_between_parts() {
+    // given
+    let text = format!("{}€{}", "a".repeat(152), "a".repeat(152));
+
+    // when
+    let parts = encoding::analyze(&text);
+
+    // then
+    assert_eq!(parts.units, 306);
+    assert_eq!(parts.segments, 3);
+}
+
+#[test]
+fn should_switch_to_ucs2_because_of_diacritics() {
+    // given
+    let text = format!("Zażółć {}", "a".repeat(64));
+
+    // when
+    let parts = encoding::analyze(&text);
+
+    // then
+    assert_eq!(parts.encoding, SmsEncoding::Ucs2);
+    assert_eq!(parts.ucs2_characters, vec!['ż', 'ó', 'ł', 'ć']);
+    assert_eq!(parts.units, 71);
+    assert_eq!(parts.segments, 2);
+}
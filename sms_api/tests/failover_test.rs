@@ -0,0 +1,142 @@
+use sms_api::{sms_mock_api, RecipientStatus};
+use sms_config::config::{RetryConf, SmsApiConf, SmsApiProvider};
+
+fn at_serial(device: &str) -> SmsApiProvider {
+    SmsApiProvider::AtSerial {
+        device: device.to_string(),
+        baud_rate: 115200,
+        pin: None,
+    }
+}
+
+// Retries are disabled, so only failover can send the message
+fn failover_config(primary: &str, backup: &str) -> SmsApiConf {
+    SmsApiConf {
+        provider: at_serial(primary),
+        backup_providers: vec![at_serial(backup)],
+        retry: RetryConf {
+            max_attempts: 1,
+            ..RetryConf::default()
+        },
+        ..SmsApiConf::default()
+    }
+}
+
+fn providers(report: &sms_api::SendReport) -> Vec<Option<String>> {
+    report
+        .recipients
+        .iter()
+        .map(|r| r.provider.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn should_send_through_backup_when_primary_is_not_reachable() {
+    // given
+    let backup = sms_mock_api::at_modem_is_working();
+    let config = failover_config("/dev/not-existing-modem", &backup.device);
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321"])
+        .await;
+
+    // then
+    let report = result.unwrap();
+    assert!(report.all_sent(), "{:?}", report);
+    let backup_description = format!("AtSerial {}", backup.device);
+    assert_eq!(
+        providers(&report),
+        vec![Some(backup_description.clone()), Some(backup_description)]
+    );
+    assert_eq!(backup.sent_sms().len(), 2);
+}
+
+#[tokio::test]
+async fn should_move_recipients_to_backup_when_primary_is_busy() {
+    // given
+    let primary = sms_mock_api::at_modem_busy_once();
+    let backup = sms_mock_api::at_modem_is_working();
+    let config = failover_config(&primary.device, &backup.device);
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321"])
+        .await;
+
+    // then
+    let report = result.unwrap();
+    assert!(report.all_sent(), "{:?}", report);
+    let recipients: Vec<String> = backup
+        .sent_sms()
+        .into_iter()
+        .map(|(phone, _)| phone)
+        .collect();
+    assert_eq!(recipients, vec!["123456789", "987654321"]);
+    assert_eq!(
+        report.recipients[0].provider,
+        Some(format!("AtSerial {}", backup.device))
+    );
+}
+
+#[tokio::test]
+async fn should_not_move_recipient_rejected_by_primary_to_backup() {
+    // given
+    let primary = sms_mock_api::at_modem_rejecting_sms_to("123456789");
+    let backup = sms_mock_api::at_modem_is_working();
+    let config = failover_config(&primary.device, &backup.device);
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321"])
+        .await;
+
+    // then
+    let report = result.unwrap();
+    assert!(
+        matches!(report.recipients[0].status, RecipientStatus::Failed(_)),
+        "{:?}",
+        report
+    );
+    assert_eq!(
+        providers(&report),
+        vec![
+            Some(format!("AtSerial {}", primary.device)),
+            Some(format!("AtSerial {}", primary.device))
+        ]
+    );
+    assert!(backup.sent_sms().is_empty());
+}
+
+#[tokio::test]
+async fn should_read_and_delete_delivery_reports_of_backup() {
+    // given
+    let backup = sms_mock_api::at_modem_is_working();
+    let config = SmsApiConf {
+        delivery_reports: true,
+        ..failover_config("/dev/not-existing-modem", &backup.device)
+    };
+    let service = sms_api::create_service(&config).unwrap();
+    service
+        .send_sms("Hello world", &["123456789"])
+        .await
+        .unwrap();
+
+    // when
+    let reports = service.read_delivery_reports().await.unwrap();
+    let deleted = service.delete_delivery_report(&reports[0]).await;
+
+    // then
+    assert_eq!(reports.len(), 1);
+    assert_eq!(
+        reports[0].provider,
+        Some(format!("AtSerial {}", backup.device))
+    );
+    assert!(deleted.is_ok(), "{:?}", deleted);
+    assert!(backup
+        .commands()
+        .contains(&format!("AT+CMGD={}", reports[0].id)));
+}
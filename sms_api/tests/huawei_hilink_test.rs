@@ -0,0 +1,161 @@
+use sms_api::{
+    sms_mock_api::{self, mockito},
+    RecipientStatus,
+};
+use sms_config::config::{SmsApiConf, SmsApiProvider};
+
+fn hilink_config(url: String, credentials: Option<(&str, &str)>, pin: Option<&str>) -> SmsApiConf {
+    SmsApiConf {
+        provider: SmsApiProvider::HuaweiHilink {
+            url,
+            username: credentials.map(|(username, _)| username.to_string()),
+            password: credentials.map(|(_, password)| password.to_string()),
+            pin: pin.map(str::to_string),
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn should_send_sms_with_session_token() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::hilink_sending_sms_is_successful(&mut server).await;
+    let service = sms_api::create_service(&hilink_config(server.url(), None, None)).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello <world> & you", &["123456789"])
+        .await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_fail_when_hilink_reports_failed_phone() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::hilink_sending_sms_failure(&mut server).await;
+    let service = sms_api::create_service(&hilink_config(server.url(), None, None)).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    let report = result.unwrap();
+    assert!(matches!(
+        &report.recipients[0].status,
+        RecipientStatus::Failed(e) if e.to_string().contains("123456789")
+    ));
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_login_before_sending_when_credentials_are_configured() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::hilink_login_is_successful(&mut server).await;
+    let config = hilink_config(server.url(), Some(("admin", "secret")), None);
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_read_inbox_from_hilink() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::hilink_reading_inbox_is_successful(&mut server).await;
+    let service = sms_api::create_service(&hilink_config(server.url(), None, None)).unwrap();
+
+    // when
+    let inbox = service.read_inbox().await.unwrap().messages;
+
+    // then
+    mock_handler.assert_called();
+    assert_eq!(inbox.len(), 2);
+    assert_eq!(inbox[0].id, 40002);
+    assert_eq!(inbox[0].phone, "+48123456789");
+    assert_eq!(inbox[0].text, "See you & tomorrow");
+    assert!(inbox[0].unread);
+    assert_eq!(inbox[1].text, "Are we meeting?");
+    assert!(!inbox[1].unread);
+}
+
+#[tokio::test]
+async fn should_delete_sms_from_hilink() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::hilink_deleting_sms_is_successful(&mut server).await;
+    let service = sms_api::create_service(&hilink_config(server.url(), None, None)).unwrap();
+
+    // when
+    let result = service.delete_sms(40002).await;
+
+    // then
+    assert!(result.is_ok(), "{:?}", result);
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_read_hilink_status() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::hilink_status_is_successful(&mut server).await;
+    let service = sms_api::create_service(&hilink_config(server.url(), None, None)).unwrap();
+
+    // when
+    let status = service.status().await.unwrap();
+
+    // then
+    mock_handler.assert_called();
+    assert_eq!(status.operator, "Plus");
+    assert_eq!(status.network_type, "LTE");
+    assert_eq!(status.signal_bars, 4);
+    assert!(status.roaming);
+    assert_eq!(status.sim_state, "Ready");
+    assert_eq!(status.pin_state, "Disabled");
+    let storage = status.sms_storage.unwrap();
+    assert_eq!((storage.used, storage.capacity), (10, 500));
+}
+
+#[tokio::test]
+async fn should_unlock_sim_and_send_again_when_pin_is_configured() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::hilink_sim_locked(&mut server).await;
+    let config = hilink_config(server.url(), None, Some(sms_mock_api::SIM_PIN));
+    let service = sms_api::create_service(&config).unwrap();
+
+    // when
+    let result = service.send_sms("Hello world", &["123456789"]).await;
+
+    // then
+    assert!(
+        matches!(&result, Ok(report) if report.all_sent()),
+        "{:?}",
+        result
+    );
+    mock_handler.assert_called();
+}
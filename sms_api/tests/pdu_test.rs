@@ -0,0 +1,162 @@
+use sms_api::{
+    pdu::{self, Concatenation},
+    SmsError,
+};
+
+#[test]
+fn should_encode_submit_pdu() {
+    // when
+    let pdus = pdu::encode_submit("+46708251358", "hellohello", 0, false).unwrap();
+
+    // then
+    assert_eq!(pdus.len(), 1);
+    assert_eq!(
+        pdus[0].hex,
+        "0011000B916407281553F80000AA0AE8329BFD4697D9EC37"
+    );
+    assert_eq!(pdus[0].tpdu_length, 23);
+}
+
+#[test]
+fn should_decode_deliver_pdu() {
+    // given
+    let hex = "07917283010010F5040BC87238880900F10000993092516195800AE8329BFD4697D9EC37";
+
+    // when
+    let deliver = pdu::decode_deliver(hex).unwrap();
+
+    // then
+    assert_eq!(deliver.sender, "27838890001");
+    assert_eq!(deliver.text, "hellohello");
+    assert_eq!(
+        deliver.timestamp.format("%m-%d %H:%M:%S %:z").to_string(),
+        "03-29 15:16:59 +02:00"
+    );
+    assert_eq!(deliver.concatenation, None);
+}
+
+#[test]
+fn should_reject_pdu_with_non_hex_characters() {
+    // when
+    let result = pdu::decode_deliver("07917283010010F5€4");
+
+    // then
+    assert!(matches!(result, Err(SmsError::PduError(_))), "{:?}", result);
+}
+
+#[test]
+fn should_decode_concatenated_ucs2_deliver_pdu() {
+    // given
+    let hex = "07914806010000F0440B918421436587F9000832011221031080200500032A0202006A007500740072006F002C00200063007A0065015B01070021";
+
+    // when
+    let deliver = pdu::decode_deliver(hex).unwrap();
+
+    // then
+    assert_eq!(deliver.sender, "+48123456789");
+    assert_eq!(deliver.text, "jutro, cześć!");
+    assert_eq!(deliver.timestamp.to_rfc3339(), "2023-10-21T12:30:01+02:00");
+    assert_eq!(
+        deliver.concatenation,
+        Some(Concatenation {
+            reference: 0x2A,
+            total: 2,
+            sequence: 2
+        })
+    );
+}
+
+#[test]
+fn should_decode_gsm7_text_after_concatenation_header() {
+    // given
+    let hex = "07914806010000F0440B918421436587F9000032011221030080150500032A0201886F90FE2D0E8FF565773A0C02";
+
+    // when
+    let deliver = pdu::decode_deliver(hex).unwrap();
+
+    // then
+    assert_eq!(deliver.text, "Do zobaczenia ");
+    assert_eq!(deliver.concatenation.map(|c| c.sequence), Some(1));
+}
+
+#[test]
+fn should_split_long_message_into_concatenated_parts() {
+    // given
+    let text = format!("{}[€]{}", "a".repeat(150), "b".repeat(160));
+
+    // when
+    let pdus = pdu::encode_submit("123456789", &text, 7, true).unwrap();
+    let decoded: Vec<_> = pdus
+        .iter()
+        .map(|p| pdu::decode_submit(&p.hex).unwrap())
+        .collect();
+
+    // then
+    assert_eq!(pdus.len(), 3);
+    assert!(decoded.iter().all(|d| d.recipient == "123456789"));
+    assert_eq!(
+        decoded.iter().map(|d| d.status_report).collect::<Vec<_>>(),
+        vec![false, false, true]
+    );
+    assert_eq!(
+        decoded.iter().map(|d| d.text.as_str()).collect::<String>(),
+        text
+    );
+    assert_eq!(
+        decoded
+            .iter()
+            .map(|d| d.concatenation.unwrap())
+            .map(|c| (c.reference, c.total, c.sequence))
+            .collect::<Vec<_>>(),
+        vec![(7, 3, 1), (7, 3, 2), (7, 3, 3)]
+    );
+}
+
+#[test]
+fn should_encode_ucs2_submit_pdu() {
+    // when
+    let pdus = pdu::encode_submit("+48123456789", "Zażółć 😀", 0, false).unwrap();
+    let decoded = pdu::decode_submit(&pdus[0].hex).unwrap();
+
+    // then
+    assert_eq!(
+        pdus[0].hex,
+        "0011000B918421436587F90008AA12005A0061017C00F3014201070020D83DDE00"
+    );
+    assert_eq!(decoded.text, "Zażółć 😀");
+}
+
+#[test]
+fn should_pack_and_unpack_septets_with_fill_bits() {
+    // given
+    let septets: Vec<u8> = (0..20).map(|i| (i * 13) % 128).collect();
+
+    // when
+    let packed = pdu::pack_septets(&septets, 1);
+    let unpacked = pdu::unpack_septets(&packed, septets.len(), 1);
+
+    // then
+    assert_eq!(packed.len(), 18);
+    assert_eq!(unpacked, septets);
+}
+
+#[test]
+fn should_decode_status_report_pdu() {
+    // given
+    let hex = "07914806010000F00608098189674523F1620181011000806201810110018046";
+
+    // when
+    let pdu = pdu::decode_received(hex).unwrap();
+
+    // then
+    let pdu::ReceivedPdu::StatusReport(report) = pdu else {
+        panic!("Expected status report but got {:?}", pdu);
+    };
+    assert_eq!(report.reference, 8);
+    assert_eq!(report.recipient, "987654321");
+    assert_eq!(report.status, 0x46);
+    assert_eq!(
+        report.discharge_time.to_rfc3339(),
+        "2026-10-18T10:01:10+02:00"
+    );
+}
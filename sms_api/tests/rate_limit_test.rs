@@ -0,0 +1,173 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use sms_api::{rate_limit::RateLimiter, sms_mock_api, RecipientStatus, SmsError};
+use sms_config::config::{RateLimitConf, SmsApiConf, SmsApiProvider};
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap() + chrono::Duration::seconds(seconds)
+}
+
+#[test]
+fn should_wait_for_minute_budget_when_bucket_is_empty() {
+    // given
+    let conf = RateLimitConf {
+        per_minute: Some(2),
+        ..RateLimitConf::default()
+    };
+    let mut limiter = RateLimiter::new(&conf, at(0));
+    limiter.take(1, at(0));
+    limiter.take(1, at(0));
+
+    // when
+    let wait = limiter.wait_time(1, at(0)).unwrap();
+    let wait_later = limiter.wait_time(1, at(30)).unwrap();
+
+    // then
+    assert_eq!(wait, Duration::from_secs(30));
+    assert_eq!(wait_later, Duration::ZERO);
+}
+
+#[test]
+fn should_reject_sms_over_daily_cap() {
+    // given
+    let conf = RateLimitConf {
+        daily_cap: Some(3),
+        ..RateLimitConf::default()
+    };
+    let mut limiter = RateLimiter::new(&conf, at(0));
+    limiter.take(2, at(0));
+
+    // when
+    let result = limiter.wait_time(2, at(10));
+
+    // then
+    assert!(
+        matches!(result, Err(SmsError::RateLimited(_))),
+        "{:?}",
+        result
+    );
+    assert_eq!(limiter.wait_time(1, at(10)).unwrap(), Duration::ZERO);
+}
+
+#[test]
+fn should_keep_minimum_spacing_between_sms() {
+    // given
+    let conf = RateLimitConf {
+        min_spacing: 5000,
+        ..RateLimitConf::default()
+    };
+    let mut limiter = RateLimiter::new(&conf, at(0));
+    limiter.take(1, at(0));
+
+    // when
+    let wait = limiter.wait_time(1, at(2)).unwrap();
+
+    // then
+    assert_eq!(wait, Duration::from_secs(3));
+}
+
+#[test]
+fn should_count_budget_from_sent_history() {
+    // given
+    let conf = RateLimitConf {
+        per_minute: Some(5),
+        per_hour: Some(100),
+        daily_cap: Some(500),
+        ..RateLimitConf::default()
+    };
+    let sent = vec![(at(-3600), 10), (at(0), 2), (at(0), 1)];
+
+    // when
+    let budget = RateLimiter::from_history(&conf, sent, at(0)).budget(at(0));
+
+    // then
+    let per_minute = budget.per_minute.unwrap();
+    assert_eq!((per_minute.remaining, per_minute.limit), (2, 5));
+    let today = budget.today.unwrap();
+    assert_eq!((today.remaining, today.limit), (487, 500));
+}
+
+#[tokio::test]
+async fn should_pace_sms_sent_through_service() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let config = SmsApiConf {
+        provider: SmsApiProvider::AtSerial {
+            device: modem.device.clone(),
+            baud_rate: 115200,
+            pin: None,
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: RateLimitConf {
+            min_spacing: 300,
+            daily_cap: Some(2),
+            ..RateLimitConf::default()
+        },
+        backup_providers: Default::default(),
+    };
+    let service = sms_api::create_service(&config).unwrap();
+    let started = Instant::now();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321", "111222333"])
+        .await
+        .unwrap();
+
+    // then
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert!(matches!(result.recipients[0].status, RecipientStatus::Sent));
+    assert!(matches!(result.recipients[1].status, RecipientStatus::Sent));
+    assert!(
+        matches!(
+            result.recipients[2].status,
+            RecipientStatus::Failed(SmsError::RateLimited(_))
+        ),
+        "{:?}",
+        result
+    );
+    assert_eq!(modem.sent_sms().len(), 2);
+}
+
+#[tokio::test]
+async fn should_count_given_history_into_limits_of_service() {
+    // given
+    let modem = sms_mock_api::at_modem_is_working();
+    let config = SmsApiConf {
+        provider: SmsApiProvider::AtSerial {
+            device: modem.device.clone(),
+            baud_rate: 115200,
+            pin: None,
+        },
+        rate_limit: RateLimitConf {
+            daily_cap: Some(3),
+            ..RateLimitConf::default()
+        },
+        ..SmsApiConf::default()
+    };
+    let sent = vec![(Utc::now(), 2)];
+    let service = sms_api::create_service_with_history(&config, sent).unwrap();
+
+    // when
+    let result = service
+        .send_sms("Hello world", &["123456789", "987654321"])
+        .await
+        .unwrap();
+
+    // then
+    assert!(matches!(result.recipients[0].status, RecipientStatus::Sent));
+    assert!(
+        matches!(
+            result.recipients[1].status,
+            RecipientStatus::Failed(SmsError::RateLimited(_))
+        ),
+        "{:?}",
+        result
+    );
+    assert_eq!(modem.sent_sms().len(), 1);
+}
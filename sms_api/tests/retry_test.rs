@@ -0,0 +1,107 @@
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use sms_api::{retry::RetryPolicy, SmsError};
+
+fn policy(max_attempts: u32, jitter: f64) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        multiplier: 2.0,
+        jitter,
+        max_elapsed: Duration::from_secs(5),
+    }
+}
+
+#[test]
+fn should_grow_delay_exponentially_up_to_max_delay() {
+    // given
+    let policy = policy(10, 0.0);
+
+    // when
+    let delays: Vec<u128> = (1..=5)
+        .map(|retry| policy.base_delay(retry).as_millis())
+        .collect();
+
+    // then
+    assert_eq!(delays, vec![10, 20, 40, 50, 50]);
+}
+
+#[test]
+fn should_keep_jittered_delay_within_jitter_fraction() {
+    // given
+    let policy = policy(10, 0.5);
+
+    // when
+    let delays: Vec<Duration> = (0..20).map(|_| policy.delay(2)).collect();
+
+    // then
+    assert!(delays
+        .iter()
+        .all(|d| *d >= Duration::from_millis(10) && *d <= Duration::from_millis(30)));
+}
+
+#[tokio::test]
+async fn should_retry_retryable_error_until_max_attempts() {
+    // given
+    let policy = policy(3, 0.2);
+    let attempts = AtomicU32::new(0);
+
+    // when
+    let result: Result<(), SmsError> = policy
+        .run(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(SmsError::ModemBusy("+CMS ERROR: 314".to_string()))
+        })
+        .await;
+
+    // then
+    assert!(matches!(result, Err(SmsError::ModemBusy(_))));
+    assert_eq!(attempts.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test]
+async fn should_not_retry_final_error() {
+    // given
+    let policy = policy(3, 0.2);
+    let attempts = AtomicU32::new(0);
+
+    // when
+    let result: Result<(), SmsError> = policy
+        .run(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(SmsError::ModemError("+CMS ERROR: 500".to_string()))
+        })
+        .await;
+
+    // then
+    assert!(matches!(result, Err(SmsError::ModemError(_))));
+    assert_eq!(attempts.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn should_stop_retrying_after_max_elapsed_time() {
+    // given
+    let policy = RetryPolicy {
+        max_elapsed: Duration::from_millis(15),
+        ..policy(10, 0.0)
+    };
+    let attempts = AtomicU32::new(0);
+
+    // when
+    let result: Result<(), SmsError> = policy
+        .run(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(SmsError::NetworkError(
+                "Modem did not respond in time".to_string(),
+            ))
+        })
+        .await;
+
+    // then
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::Relaxed), 2);
+}
@@ -0,0 +1,41 @@
+use sms_api::{
+    encoding::{self, SmsEncoding},
+    transliteration,
+};
+
+#[test]
+fn should_transliterate_polish_diacritics_to_gsm7() {
+    // given
+    let text = "Zażółć gęślą jaźń. ZAŻÓŁĆ GĘŚLĄ JAŹŃ";
+
+    // when
+    let transliterated = transliteration::transliterate(text);
+
+    // then
+    assert_eq!(transliterated, "Zazolc gesla jazn. ZAZOLC GESLA JAZN");
+    assert_eq!(encoding::analyze(&transliterated).encoding, SmsEncoding::Gsm7);
+}
+
+#[test]
+fn should_transliterate_typographic_punctuation() {
+    // given
+    let text = "„Quoted” – ‘single’…";
+
+    // when
+    let transliterated = transliteration::transliterate(text);
+
+    // then
+    assert_eq!(transliterated, "\"Quoted\" - 'single'...");
+}
+
+#[test]
+fn should_keep_gsm7_and_unknown_characters() {
+    // given
+    let text = "Café à Ørsted € 😀";
+
+    // when
+    let transliterated = transliteration::transliterate(text);
+
+    // then
+    assert_eq!(transliterated, text);
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Debug, Parser)]
 #[command(name = "sms")]
@@ -22,6 +23,38 @@ pub enum Commands {
     Send(SendSmsArgs),
     #[command(subcommand, about = "Manage importing resouces")]
     Import(ImportCommads),
+    #[command(about = "Show received sms")]
+    Inbox {
+        #[arg(long, help = "Delete received sms with given id")]
+        delete: Option<i64>,
+    },
+    #[command(about = "Show history of sent sms")]
+    History(HistoryArgs),
+    #[command(subcommand, about = "Check state of usb modem")]
+    Modem(ModemCommands),
+    #[command(about = "Send USSD code and show reply of network")]
+    Ussd {
+        #[arg(help = "USSD code like \"*101#\", defaults to balance_ussd from config")]
+        code: Option<String>,
+    },
+    #[command(subcommand, about = "Continue interrupted sending of sms")]
+    Campaign(CampaignCommands),
+    #[command(subcommand, about = "Manage scheduled sms")]
+    Jobs(JobsCommands),
+    #[command(subcommand, about = "Manage recurring sms schedules")]
+    Schedule(ScheduleCommands),
+    #[command(subcommand, about = "Manage exporting resources")]
+    Export(ExportCommands),
+    #[command(about = "Run background service sending scheduled sms")]
+    Daemon {
+        #[arg(
+            long,
+            default_value_t = 30,
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "How often to check for due jobs, in seconds"
+        )]
+        interval: u64,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -91,15 +124,69 @@ pub struct AssignGroupArgs {
 #[derive(Debug, Subcommand)]
 pub enum TemplatesCommands {
     #[command(arg_required_else_help = true, about = "Create new template")]
-    Create { name: String, text: String },
+    Create {
+        name: String,
+        text: String,
+        #[arg(
+            long,
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help = "Transliterate messages sent with this template to GSM-7"
+        )]
+        transliterate: Option<bool>,
+    },
     #[command(arg_required_else_help = true, about = "Delete template")]
     Delete { name: String },
     #[command(arg_required_else_help = true, about = "Get template")]
     Get { name: String },
     #[command(arg_required_else_help = true, about = "Update template")]
-    Update { name: String, text: String },
+    Update {
+        name: String,
+        text: String,
+        #[arg(
+            long,
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help = "Transliterate messages sent with this template to GSM-7"
+        )]
+        transliterate: Option<bool>,
+    },
     #[command(about = "List all templates")]
     List,
+    #[command(
+        arg_required_else_help = true,
+        about = "Render template for given contact"
+    )]
+    Preview {
+        name: String,
+        #[command(flatten)]
+        contact: TemplatePreviewContactArgs,
+        #[arg(
+            long = "var",
+            value_parser = parse_key_value,
+            help = "Value of template variable, format 'key=value'"
+        )]
+        vars: Vec<(String, String)>,
+    },
+    #[command(about = "Validate all templates")]
+    Check {
+        #[arg(
+            long,
+            help = "Maximum number of sms parts per message, defaults to value from config"
+        )]
+        max_segments: Option<usize>,
+    },
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct TemplatePreviewContactArgs {
+    #[arg(short)]
+    pub contact_name: String,
+    #[arg(
+        short,
+        help = "If many contacts have the same name, select one by index. Index starts from 0"
+    )]
+    pub index: Option<usize>,
 }
 
 #[derive(Debug, Args)]
@@ -109,6 +196,31 @@ pub struct SendSmsArgs {
     pub to: SmsTargetArgs,
     #[command(flatten)]
     pub message: SmsMessageArgs,
+    #[arg(
+        long,
+        value_parser = parse_local_datetime,
+        help = "Schedule sending at given local time, format 'YYYY-MM-DD HH:MM'"
+    )]
+    pub at: Option<DateTime<Local>>,
+    #[arg(
+        long = "var",
+        value_parser = parse_key_value,
+        help = "Value of template variable, format 'key=value'"
+    )]
+    pub vars: Vec<(String, String)>,
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "Replace diacritics and typographic characters to keep message in GSM-7, overrides template and config"
+    )]
+    pub transliterate: Option<bool>,
+    #[arg(
+        long,
+        conflicts_with = "at",
+        help = "Request delivery reports and wait until recipients receive message"
+    )]
+    pub wait_delivery: bool,
 }
 
 #[derive(Debug, Args, Clone)]
@@ -143,10 +255,226 @@ pub struct SmsMessageArgs {
 pub enum ImportCommads {
     #[command(
         arg_required_else_help = true,
-        about = "Replace all current contacts with contacts from csv file"
+        about = "Replace all current contacts with contacts from csv or json file"
     )]
     ReplaceContacts {
         source_csv: PathBuf,
         group_name: String,
+        #[command(flatten)]
+        columns: CsvColumnsArgs,
     },
+    #[command(
+        arg_required_else_help = true,
+        about = "Merge contacts from csv or json file into current contacts"
+    )]
+    MergeContacts {
+        source_csv: PathBuf,
+        #[command(flatten)]
+        columns: CsvColumnsArgs,
+        #[arg(long, help = "Remove contacts missing in csv file")]
+        remove_missing: bool,
+        #[arg(long, help = "Only show changes without saving them")]
+        dry_run: bool,
+    },
+    #[command(
+        arg_required_else_help = true,
+        about = "Create or update templates from csv or json file"
+    )]
+    Templates { source: PathBuf },
+    #[command(
+        arg_required_else_help = true,
+        about = "Create groups and assign existing contacts from csv or json file"
+    )]
+    Groups { source: PathBuf },
+    #[command(
+        arg_required_else_help = true,
+        about = "Merge contacts from vCard file, categories are assigned as groups"
+    )]
+    Vcard {
+        source: PathBuf,
+        #[arg(long, help = "Only show changes without saving them")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportCommands {
+    #[command(
+        arg_required_else_help = true,
+        about = "Export contacts, groups and templates to files"
+    )]
+    Data {
+        output_dir: PathBuf,
+        #[arg(long, value_enum, default_value_t = FileFormat::Csv)]
+        format: FileFormat,
+    },
+    #[command(
+        arg_required_else_help = true,
+        about = "Export contacts to vCard file, groups are exported as categories"
+    )]
+    Vcard {
+        output_file: PathBuf,
+        #[arg(long, value_enum, default_value_t = VCardVersion::V3)]
+        vcard_version: VCardVersion,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum VCardVersion {
+    #[value(name = "3")]
+    V3,
+    #[value(name = "4")]
+    V4,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FileFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CsvColumnsArgs {
+    #[arg(long, default_value = "first_name")]
+    pub first_name_column: String,
+    #[arg(long, default_value = "surname_name")]
+    pub surname_column: String,
+    #[arg(long, default_value = "phone")]
+    pub phone_column: String,
+    #[arg(long, default_value = "contact_name", help = "Optional column")]
+    pub contact_name_column: String,
+    #[arg(long, default_value_t = ',')]
+    pub delimiter: char,
+}
+
+#[derive(Debug, Args)]
+#[command()]
+pub struct HistoryArgs {
+    #[arg(short)]
+    pub contact_name: Option<String>,
+    #[arg(short)]
+    pub group_name: Option<String>,
+    #[arg(long, help = "First day to show, format YYYY-MM-DD")]
+    pub from: Option<NaiveDate>,
+    #[arg(long, help = "Last day to show, format YYYY-MM-DD")]
+    pub to: Option<NaiveDate>,
+    #[arg(long, value_enum)]
+    pub status: Option<MessageStatus>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum MessageStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ModemCommands {
+    #[command(about = "Show signal, network, SIM and sms storage state")]
+    Status {
+        #[arg(long, help = "Print status as json")]
+        json: bool,
+    },
+    #[command(about = "Unlock SIM with PIN")]
+    Unlock {
+        #[arg(help = "PIN of SIM, defaults to pin from config")]
+        pin: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CampaignCommands {
+    #[command(
+        arg_required_else_help = true,
+        about = "Send message to recipients the campaign has not reached yet"
+    )]
+    Resume { id: String },
+    #[command(
+        arg_required_else_help = true,
+        about = "Send message again to recipients it failed for"
+    )]
+    RetryFailed { id: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum JobsCommands {
+    #[command(about = "List scheduled sms")]
+    List {
+        #[arg(short, long, help = "Show also sent, failed and cancelled jobs")]
+        all: bool,
+    },
+    #[command(arg_required_else_help = true, about = "Cancel scheduled sms")]
+    Cancel { id: String },
+    #[command(arg_required_else_help = true, about = "Change time of scheduled sms")]
+    Reschedule {
+        id: String,
+        #[arg(value_parser = parse_local_datetime)]
+        at: DateTime<Local>,
+    },
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("Expected format 'key=value' but got '{}'", value))
+}
+
+fn parse_local_datetime(value: &str) -> Result<DateTime<Local>, String> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+        .map_err(|e| format!("Expected format 'YYYY-MM-DD HH:MM', Reason: {}", e))?
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| format!("Time '{}' does not exist in local timezone", value))
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ScheduleCommands {
+    #[command(
+        arg_required_else_help = true,
+        about = "Add schedule sending template to group"
+    )]
+    Add {
+        name: String,
+        #[command(flatten)]
+        expression: ScheduleExpressionArgs,
+        #[arg(short)]
+        template: String,
+        #[arg(short)]
+        group_name: String,
+    },
+    #[command(about = "List all schedules")]
+    List,
+    #[command(arg_required_else_help = true, about = "Pause schedule")]
+    Pause { name: String },
+    #[command(arg_required_else_help = true, about = "Resume paused schedule")]
+    Resume { name: String },
+    #[command(arg_required_else_help = true, about = "Delete schedule")]
+    Delete { name: String },
+    #[command(
+        arg_required_else_help = true,
+        about = "Show next fire times of schedule expression"
+    )]
+    Preview {
+        #[command(flatten)]
+        expression: ScheduleExpressionArgs,
+        #[arg(short, default_value_t = 5)]
+        number: usize,
+    },
+}
+
+#[derive(Debug, Args, Clone)]
+#[clap(group(
+    clap::ArgGroup::new("expression")
+        .required(true)
+        .args(&["cron", "rrule"]),
+))]
+pub struct ScheduleExpressionArgs {
+    #[arg(long, help = "Cron expression, for example '0 9 * * Mon'")]
+    pub cron: Option<String>,
+    #[arg(long, help = "RRULE, for example 'FREQ=WEEKLY;BYDAY=MO;BYHOUR=9'")]
+    pub rrule: Option<String>,
 }
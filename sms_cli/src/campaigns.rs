@@ -0,0 +1,18 @@
+use sms_config::config::SmsApiConf;
+use sms_db::campaigns::RecipientState;
+
+use crate::{args_parser::CampaignCommands, sms_send};
+
+pub async fn manage_campaigns(
+    cmd: CampaignCommands,
+    sms_api_config: &SmsApiConf,
+) -> Result<String, String> {
+    match cmd {
+        CampaignCommands::Resume { id } => {
+            sms_send::resume_campaign(&id, RecipientState::Pending, sms_api_config).await
+        }
+        CampaignCommands::RetryFailed { id } => {
+            sms_send::resume_campaign(&id, RecipientState::Failed, sms_api_config).await
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use csv::StringRecord;
+use serde::{Deserialize, Serialize};
+
+use crate::args_parser::CsvColumnsArgs;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactRow {
+    pub first_name: String,
+    pub surname_name: String,
+    pub phone: String,
+    pub contact_name: Option<String>,
+}
+
+pub fn read_contacts(
+    source_csv: &Path,
+    columns: &CsvColumnsArgs,
+) -> Result<Vec<ContactRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(columns.delimiter as u8)
+        .trim(csv::Trim::All)
+        .from_path(source_csv)
+        .map_err(|e| {
+            format!(
+                "Could not open file {}, Reason: {}",
+                source_csv.display(),
+                e
+            )
+        })?;
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Could not read csv headers, Reason: {}", e))?
+        .clone();
+    let first_name = column_index(&headers, &columns.first_name_column)?;
+    let surname_name = column_index(&headers, &columns.surname_column)?;
+    let phone = column_index(&headers, &columns.phone_column)?;
+    let contact_name = headers
+        .iter()
+        .position(|header| header == columns.contact_name_column);
+
+    let mut rows = vec![];
+    for (line, record) in reader.records().enumerate() {
+        let record =
+            record.map_err(|e| format!("Could not read csv row {}, Reason: {}", line + 1, e))?;
+        let row = ContactRow {
+            first_name: field(&record, first_name),
+            surname_name: field(&record, surname_name),
+            phone: field(&record, phone),
+            contact_name: contact_name
+                .map(|index| field(&record, index))
+                .filter(|name| !name.is_empty()),
+        };
+        if row.phone.is_empty() {
+            return Err(format!("Missing phone number in csv row {}", line + 1));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn column_index(headers: &StringRecord, column: &str) -> Result<usize, String> {
+    headers
+        .iter()
+        .position(|header| header == column)
+        .ok_or_else(|| format!("Column '{}' not found in csv headers", column))
+}
+
+fn field(record: &StringRecord, index: usize) -> String {
+    record.get(index).unwrap_or_default().to_string()
+}
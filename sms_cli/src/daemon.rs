@@ -0,0 +1,163 @@
+use std::{future::Future, time::Duration};
+
+use chrono::Local;
+use sms_config::config::{SmsApiConf, SmsConfig};
+use sms_db::{
+    recurring_schedules::RecurringSchedule,
+    repository,
+    scheduled_jobs::{JobStatus, ScheduledJob},
+};
+
+use crate::{
+    args_parser::{SendSmsArgs, SmsMessageArgs, SmsTargetArgs},
+    delivery, jobs, local_time, recurrence, sms_send,
+};
+
+pub async fn run_daemon(interval: u64, config: &SmsConfig) -> Result<String, String> {
+    println!("Daemon started, checking for due jobs every {}s", interval);
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if let Err(e) = with_repository(config, run_tick(&config.sms_api)).await {
+                    println!("Could not check for due jobs, Reason: {}", e);
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                return Ok("Daemon stopped".to_string());
+            }
+        }
+    }
+}
+
+// Db is open only during a tick, so other commands can use it in between
+async fn with_repository(
+    config: &SmsConfig,
+    action: impl Future<Output = ()>,
+) -> Result<(), String> {
+    repository::init(config)
+        .await
+        .map_err(|e| format!("Could not open db {:?}", e))?;
+    action.await;
+    repository::close();
+    Ok(())
+}
+
+pub async fn run_tick(sms_api_config: &SmsApiConf) {
+    if let Err(e) = fail_interrupted_jobs().await {
+        println!("Could not check for interrupted jobs, Reason: {}", e);
+    }
+    if let Err(e) = dispatch_due_jobs(sms_api_config).await {
+        println!("Could not dispatch due jobs, Reason: {}", e);
+    }
+    if let Err(e) = dispatch_due_schedules(sms_api_config).await {
+        println!("Could not dispatch due schedules, Reason: {}", e);
+    }
+    if sms_api_config.delivery_reports {
+        if let Err(e) = delivery::sync_delivery_reports(sms_api_config).await {
+            println!("Could not sync delivery reports, Reason: {}", e);
+        }
+    }
+}
+
+// Db stays locked until tick is done, so job left in dispatching state was interrupted
+// in the middle of sending. We can't tell which messages went out, so we fail them
+// instead of sending twice.
+async fn fail_interrupted_jobs() -> Result<(), String> {
+    let jobs = repository::scheduled_jobs();
+    for job in jobs.find_by_status(JobStatus::Dispatching).await? {
+        println!("Job {} was interrupted, marking as failed", job.id.id);
+        jobs.change_status(
+            &job.id,
+            JobStatus::Dispatching,
+            JobStatus::Failed,
+            Some("Interrupted while sending, reschedule to send again".to_string()),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn dispatch_due_jobs(sms_api_config: &SmsApiConf) -> Result<(), String> {
+    let jobs = repository::scheduled_jobs();
+    for job in jobs.find_due_jobs().await? {
+        let claimed = jobs
+            .change_status(&job.id, JobStatus::Scheduled, JobStatus::Dispatching, None)
+            .await?;
+        if let Some(job) = claimed {
+            dispatch_job(job, sms_api_config).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn dispatch_job(job: ScheduledJob, sms_api_config: &SmsApiConf) -> Result<(), String> {
+    println!("Dispatching job {}", job.id.id);
+    let result = sms_send::send_sms(jobs::send_args_of(&job), sms_api_config).await;
+    let (status, error) = match result {
+        Ok(_) => (JobStatus::Sent, None),
+        Err(e) => {
+            println!("Job {} failed, Reason: {}", job.id.id, e);
+            (JobStatus::Failed, Some(e))
+        }
+    };
+    repository::scheduled_jobs()
+        .change_status(&job.id, JobStatus::Dispatching, status, error)
+        .await
+        .map(|_| ())
+}
+
+// Schedule is moved to its next run before sending, so restarted daemon never fires it twice.
+// Schedule whose next run can't be computed is paused, so it doesn't stop the others.
+async fn dispatch_due_schedules(sms_api_config: &SmsApiConf) -> Result<(), String> {
+    let schedules = repository::recurring_schedules();
+    for mut schedule in schedules.find_due_schedules().await? {
+        let next_run_at = match recurrence::next_fire_time(&schedule.spec, Local::now()) {
+            Ok(next_run_at) => next_run_at,
+            Err(e) => {
+                println!("Schedule {} paused, Reason: {}", schedule.name, e);
+                schedule.paused = true;
+                schedule.error = Some(e);
+                schedules.update(schedule).await?;
+                continue;
+            }
+        };
+        let claimed = schedules
+            .advance(&schedule, local_time::to_datetime(next_run_at))
+            .await?;
+        if let Some(schedule) = claimed {
+            dispatch_schedule(schedule, sms_api_config).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn dispatch_schedule(
+    mut schedule: RecurringSchedule,
+    sms_api_config: &SmsApiConf,
+) -> Result<(), String> {
+    println!("Dispatching schedule {}", schedule.name);
+    let send_args = SendSmsArgs {
+        to: SmsTargetArgs {
+            number: None,
+            contact_name: None,
+            group_name: Some(schedule.group.id.to_raw()),
+        },
+        message: SmsMessageArgs {
+            plain: None,
+            template: Some(schedule.template.id.to_raw()),
+        },
+        at: None,
+        vars: vec![],
+        transliterate: None,
+        wait_delivery: false,
+    };
+    schedule.error = match sms_send::send_sms(send_args, sms_api_config).await {
+        Ok(_) => None,
+        Err(e) => {
+            println!("Schedule {} failed, Reason: {}", schedule.name, e);
+            Some(e)
+        }
+    };
+    repository::recurring_schedules().update(schedule).await
+}
@@ -0,0 +1,149 @@
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::args_parser::FileFormat;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateRow {
+    pub name: String,
+    pub text: String,
+    #[serde(default)]
+    pub transliterate: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupRow {
+    pub name: String,
+    pub members: Vec<GroupMemberRow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupMemberRow {
+    pub contact_name: String,
+    pub phone: String,
+}
+
+// Csv can't nest members inside group, so every member gets its own row.
+// Group without members is written as single row with empty contact fields.
+#[derive(Debug, Serialize, Deserialize)]
+struct GroupMemberCsvRow {
+    group_name: String,
+    contact_name: String,
+    phone: String,
+}
+
+pub fn format_of(path: &Path) -> Result<FileFormat, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("csv") => Ok(FileFormat::Csv),
+        Some(extension) if extension.eq_ignore_ascii_case("json") => Ok(FileFormat::Json),
+        _ => Err(format!(
+            "Unknown format of file {}, expected .csv or .json extension",
+            path.display()
+        )),
+    }
+}
+
+pub fn extension(format: FileFormat) -> &'static str {
+    match format {
+        FileFormat::Csv => "csv",
+        FileFormat::Json => "json",
+    }
+}
+
+pub fn write_rows<T: Serialize>(path: &Path, format: FileFormat, rows: &[T]) -> Result<(), String> {
+    match format {
+        FileFormat::Csv => {
+            let mut writer = csv::Writer::from_path(path)
+                .map_err(|e| format!("Could not create file {}, Reason: {}", path.display(), e))?;
+            for row in rows {
+                writer
+                    .serialize(row)
+                    .map_err(|e| format!("Could not write {}, Reason: {}", path.display(), e))?;
+            }
+            writer
+                .flush()
+                .map_err(|e| format!("Could not write {}, Reason: {}", path.display(), e))
+        }
+        FileFormat::Json => {
+            let file = std::fs::File::create(path)
+                .map_err(|e| format!("Could not create file {}, Reason: {}", path.display(), e))?;
+            serde_json::to_writer_pretty(file, rows)
+                .map_err(|e| format!("Could not write {}, Reason: {}", path.display(), e))
+        }
+    }
+}
+
+pub fn read_rows<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, String> {
+    match format_of(path)? {
+        FileFormat::Csv => csv::Reader::from_path(path)
+            .map_err(|e| format!("Could not open file {}, Reason: {}", path.display(), e))?
+            .deserialize()
+            .collect::<Result<Vec<T>, _>>()
+            .map_err(|e| format!("Could not read {}, Reason: {}", path.display(), e)),
+        FileFormat::Json => {
+            let file = std::fs::File::open(path)
+                .map_err(|e| format!("Could not open file {}, Reason: {}", path.display(), e))?;
+            serde_json::from_reader(std::io::BufReader::new(file))
+                .map_err(|e| format!("Could not read {}, Reason: {}", path.display(), e))
+        }
+    }
+}
+
+pub fn write_groups(path: &Path, format: FileFormat, groups: &[GroupRow]) -> Result<(), String> {
+    match format {
+        FileFormat::Csv => write_rows(path, format, &to_csv_rows(groups)),
+        FileFormat::Json => write_rows(path, format, groups),
+    }
+}
+
+pub fn read_groups(path: &Path) -> Result<Vec<GroupRow>, String> {
+    match format_of(path)? {
+        FileFormat::Csv => Ok(from_csv_rows(read_rows(path)?)),
+        FileFormat::Json => read_rows(path),
+    }
+}
+
+fn to_csv_rows(groups: &[GroupRow]) -> Vec<GroupMemberCsvRow> {
+    let mut rows = vec![];
+    for group in groups {
+        if group.members.is_empty() {
+            rows.push(GroupMemberCsvRow {
+                group_name: group.name.clone(),
+                contact_name: String::new(),
+                phone: String::new(),
+            });
+        }
+        for member in &group.members {
+            rows.push(GroupMemberCsvRow {
+                group_name: group.name.clone(),
+                contact_name: member.contact_name.clone(),
+                phone: member.phone.clone(),
+            });
+        }
+    }
+    rows
+}
+
+fn from_csv_rows(rows: Vec<GroupMemberCsvRow>) -> Vec<GroupRow> {
+    let mut groups: Vec<GroupRow> = vec![];
+    for row in rows {
+        let group_index = match groups.iter().position(|g| g.name == row.group_name) {
+            Some(index) => index,
+            None => {
+                groups.push(GroupRow {
+                    name: row.group_name,
+                    members: vec![],
+                });
+                groups.len() - 1
+            }
+        };
+        if !row.phone.is_empty() {
+            groups[group_index].members.push(GroupMemberRow {
+                contact_name: row.contact_name,
+                phone: row.phone,
+            });
+        }
+    }
+    groups
+}
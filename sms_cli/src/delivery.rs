@@ -0,0 +1,99 @@
+use chrono::{Duration, Local};
+use sms_api::DeliveryReport;
+use sms_config::config::SmsApiConf;
+use sms_db::{
+    repository,
+    sent_messages::{DeliveryStatus, SentMessage},
+};
+
+use crate::local_time;
+
+// Report of message sent by other tool is left on modem for a while, then removed
+const REPORT_RETENTION_DAYS: i64 = 7;
+
+// Reads reports stored on modem, updates matching messages of history and removes matched
+// or expired reports, so they are not matched again. Returns number of updated messages.
+pub async fn sync_delivery_reports(sms_api_config: &SmsApiConf) -> Result<usize, String> {
+    let service = sms_api::create_service(sms_api_config)
+        .map_err(|e| format!("Could not read delivery reports, Reason: {:?}", e))?;
+    let reports = service
+        .read_delivery_reports()
+        .await
+        .map_err(|e| format!("Could not read delivery reports, Reason: {}", e))?;
+    if reports.is_empty() {
+        return Ok(0);
+    }
+    let sent_messages = repository::sent_messages();
+    let mut awaiting = sent_messages.find_awaiting_delivery().await?;
+    let updated = apply_reports(&mut awaiting, &reports);
+    let count = updated.len();
+    let mut matched = vec![false; reports.len()];
+    for (index, message) in updated {
+        matched[index] = true;
+        sent_messages.update(message).await?;
+    }
+    let expired = Local::now().naive_local() - Duration::days(REPORT_RETENTION_DAYS);
+    for (report, matched) in reports.iter().zip(matched) {
+        if !matched && report.time > expired {
+            continue;
+        }
+        service
+            .delete_delivery_report(report)
+            .await
+            .map_err(|e| format!("Could not delete delivery report, Reason: {}", e))?;
+    }
+    Ok(count)
+}
+
+// Every final report is given to the oldest message awaiting delivery to the same phone,
+// with the same message reference when both of them have one.
+// Messages that got final status are moved from `awaiting` to returned list,
+// together with index of their report.
+pub fn apply_reports(
+    awaiting: &mut Vec<SentMessage>,
+    reports: &[DeliveryReport],
+) -> Vec<(usize, SentMessage)> {
+    let mut updated = vec![];
+    for (report_index, report) in reports.iter().enumerate() {
+        let status = match report.status {
+            sms_api::DeliveryStatus::Delivered => DeliveryStatus::Delivered,
+            sms_api::DeliveryStatus::Failed => DeliveryStatus::Failed,
+            sms_api::DeliveryStatus::Pending => continue,
+        };
+        let Some(index) = awaiting.iter().position(|message| {
+            same_phone(&message.phone, &report.phone) && same_reference(message, report)
+        }) else {
+            continue;
+        };
+        let mut message = awaiting.remove(index);
+        message.delivery = Some(status);
+        if status == DeliveryStatus::Delivered {
+            message.delivered_at = report
+                .time
+                .and_local_timezone(Local)
+                .earliest()
+                .map(local_time::to_datetime);
+        }
+        updated.push((report_index, message));
+    }
+    updated
+}
+
+fn same_reference(message: &SentMessage, report: &DeliveryReport) -> bool {
+    match (message.reference, report.reference) {
+        (Some(sent), Some(reported)) => sent == reported,
+        _ => true,
+    }
+}
+
+// Reports carry number in international format even when sms was sent to national one
+fn same_phone(first: &str, second: &str) -> bool {
+    let digits = |phone: &str| -> String { phone.chars().filter(char::is_ascii_digit).collect() };
+    let (first, second) = (digits(first), digits(second));
+    let (shorter, longer) = if first.len() <= second.len() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    !shorter.is_empty() && longer.ends_with(&shorter)
+}
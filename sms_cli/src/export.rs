@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use sms_db::repository;
+
+use crate::{
+    args_parser::{ExportCommands, FileFormat, VCardVersion},
+    contacts_csv::ContactRow,
+    data_files::{self, GroupMemberRow, GroupRow, TemplateRow},
+    vcard::{self, VCard},
+};
+
+pub async fn manage_exports(export_commands: ExportCommands) -> Result<String, String> {
+    match export_commands {
+        ExportCommands::Data { output_dir, format } => handle_export_data(output_dir, format).await,
+        ExportCommands::Vcard {
+            output_file,
+            vcard_version,
+        } => handle_export_vcard(output_file, vcard_version).await,
+    }
+}
+
+async fn handle_export_data(output_dir: PathBuf, format: FileFormat) -> Result<String, String> {
+    std::fs::create_dir_all(&output_dir).map_err(|e| {
+        format!(
+            "Could not create directory {}, Reason: {}",
+            output_dir.display(),
+            e
+        )
+    })?;
+    let contacts = export_contacts(&output_dir, format).await?;
+    let groups = export_groups(&output_dir, format).await?;
+    let templates = export_templates(&output_dir, format).await?;
+    Ok(format!(
+        "Exported {} contacts, {} groups and {} templates to {}",
+        contacts,
+        groups,
+        templates,
+        output_dir.display()
+    ))
+}
+
+async fn handle_export_vcard(
+    output_file: PathBuf,
+    vcard_version: VCardVersion,
+) -> Result<String, String> {
+    let groups = repository::groups();
+    let mut categories: HashMap<String, Vec<String>> = HashMap::new();
+    for group in groups.get_all().await? {
+        let members = groups
+            .find_group_details(&group.id)
+            .await?
+            .map(|details| details.contacts)
+            .unwrap_or_default();
+        for member in members {
+            categories
+                .entry(member.id.to_string())
+                .or_default()
+                .push(group.name.clone());
+        }
+    }
+    let cards: Vec<VCard> = repository::contacts()
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|c| VCard {
+            categories: categories.remove(&c.id.to_string()).unwrap_or_default(),
+            first_name: c.first_name,
+            surname_name: c.surname_name,
+            phone: c.phone,
+            contact_name: Some(c.contact_name),
+        })
+        .collect();
+    std::fs::write(&output_file, vcard::write(&cards, vcard_version)).map_err(|e| {
+        format!(
+            "Could not write file {}, Reason: {}",
+            output_file.display(),
+            e
+        )
+    })?;
+    Ok(format!(
+        "Exported {} contacts to {}",
+        cards.len(),
+        output_file.display()
+    ))
+}
+
+fn export_file(output_dir: &Path, name: &str, format: FileFormat) -> PathBuf {
+    output_dir.join(format!("{}.{}", name, data_files::extension(format)))
+}
+
+async fn export_contacts(output_dir: &Path, format: FileFormat) -> Result<usize, String> {
+    let rows: Vec<ContactRow> = repository::contacts()
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|c| ContactRow {
+            first_name: c.first_name,
+            surname_name: c.surname_name,
+            phone: c.phone,
+            contact_name: Some(c.contact_name),
+        })
+        .collect();
+    data_files::write_rows(&export_file(output_dir, "contacts", format), format, &rows)?;
+    Ok(rows.len())
+}
+
+async fn export_groups(output_dir: &Path, format: FileFormat) -> Result<usize, String> {
+    let groups = repository::groups();
+    let mut rows = vec![];
+    for group in groups.get_all().await? {
+        let members = groups
+            .find_group_details(&group.id)
+            .await?
+            .map(|details| details.contacts)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| GroupMemberRow {
+                contact_name: c.contact_name,
+                phone: c.phone,
+            })
+            .collect();
+        rows.push(GroupRow {
+            name: group.name,
+            members,
+        });
+    }
+    data_files::write_groups(&export_file(output_dir, "groups", format), format, &rows)?;
+    Ok(rows.len())
+}
+
+async fn export_templates(output_dir: &Path, format: FileFormat) -> Result<usize, String> {
+    let rows: Vec<TemplateRow> = repository::templates()
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|t| TemplateRow {
+            name: t.name,
+            text: t.text,
+            transliterate: t.transliterate,
+        })
+        .collect();
+    data_files::write_rows(&export_file(output_dir, "templates", format), format, &rows)?;
+    Ok(rows.len())
+}
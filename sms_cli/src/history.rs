@@ -0,0 +1,94 @@
+use chrono::{Local, NaiveDate};
+use prettytable::row;
+use sms_db::{
+    contacts::Contact,
+    groups::Group,
+    repository,
+    sent_messages::{DeliveryStatus, SentMessage, SentMessageFilter, SentMessageStatus},
+    Datetime,
+};
+
+use crate::{
+    args_parser::{HistoryArgs, MessageStatus},
+    local_time,
+};
+
+pub async fn show_history(history_args: HistoryArgs) -> Result<String, String> {
+    let filter = create_filter(history_args).await?;
+    let messages = repository::sent_messages().find_history(filter).await?;
+    let contacts = repository::contacts().get_all().await?;
+    Ok(render_history_table(messages, &contacts))
+}
+
+async fn create_filter(history_args: HistoryArgs) -> Result<SentMessageFilter, String> {
+    let contacts = match history_args.contact_name {
+        Some(contact_name) => Some(
+            repository::contacts()
+                .find_all_by_contact_name(&contact_name)
+                .await?
+                .into_iter()
+                .map(|c| c.id)
+                .collect(),
+        ),
+        None => None,
+    };
+    Ok(SentMessageFilter {
+        contacts,
+        group: history_args.group_name.as_deref().map(Group::id_from_name),
+        from: history_args.from.map(start_of_day),
+        to: history_args
+            .to
+            .and_then(|day| day.succ_opt())
+            .map(start_of_day),
+        status: history_args.status.map(|status| match status {
+            MessageStatus::Pending => SentMessageStatus::Pending,
+            MessageStatus::Sent => SentMessageStatus::Sent,
+            MessageStatus::Failed => SentMessageStatus::Failed,
+        }),
+    })
+}
+
+fn start_of_day(day: NaiveDate) -> Datetime {
+    let local_midnight = day
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .expect("Midnight should exist in local timezone");
+    local_time::to_datetime(local_midnight)
+}
+
+fn render_history_table(messages: Vec<SentMessage>, contacts: &[Contact]) -> String {
+    let mut table = prettytable::Table::new();
+    table.add_row(row![
+        "Time", "To", "Template", "Provider", "Status", "Delivery", "Text", "Error"
+    ]);
+    for message in messages {
+        let delivery = describe_delivery(&message);
+        let recipient = message
+            .contact
+            .as_ref()
+            .and_then(|id| contacts.iter().find(|c| &c.id == id))
+            .map(|c| format!("{} ({})", c.contact_name, message.phone))
+            .unwrap_or(message.phone);
+        table.add_row(row![
+            local_time::format(&message.created_at),
+            recipient,
+            message.template_name.unwrap_or_default(),
+            message.provider,
+            format!("{:?}", message.status),
+            delivery,
+            message.text,
+            message.error.unwrap_or_default()
+        ]);
+    }
+    table.to_string()
+}
+
+fn describe_delivery(message: &SentMessage) -> String {
+    match (&message.delivery, &message.delivered_at) {
+        (Some(DeliveryStatus::Delivered), Some(delivered_at)) => {
+            format!("Delivered {}", local_time::format(delivered_at))
+        }
+        (Some(delivery), _) => format!("{:?}", delivery),
+        (None, _) => String::new(),
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use prettytable::row;
+use sms_api::{Inbox, ReceivedSms, SmsError};
+use sms_config::config::SmsApiConf;
+use sms_db::{contacts::Contact, repository};
+
+pub async fn show_inbox(sms_api_config: &SmsApiConf) -> Result<String, String> {
+    let inbox = read_inbox(sms_api_config)
+        .await
+        .map_err(|e| format!("Could not read inbox, Reason: {:?}", e))?;
+    let contacts = repository::contacts().get_all().await?;
+    let mut output = render_inbox_table(inbox.messages, &contacts);
+    for sms in inbox.undecodable {
+        output.push_str(&format!(
+            "Skipped message {} which could not be decoded, {}\n",
+            sms.id, sms.reason
+        ));
+    }
+    Ok(output)
+}
+
+pub async fn read_inbox(sms_api_config: &SmsApiConf) -> Result<Inbox, SmsError> {
+    sms_api::create_service(sms_api_config)?.read_inbox().await
+}
+
+pub async fn delete_sms(id: i64, sms_api_config: &SmsApiConf) -> Result<String, String> {
+    sms_api::create_service(sms_api_config)
+        .map_err(|e| format!("Could not delete sms, Reason: {:?}", e))?
+        .delete_sms(id)
+        .await
+        .map(|_| "Sms deleted successfully".to_string())
+        .map_err(|e| format!("Could not delete sms, Reason: {:?}", e))
+}
+
+fn render_inbox_table(messages: Vec<ReceivedSms>, contacts: &[Contact]) -> String {
+    let contact_names: HashMap<&str, &str> = contacts
+        .iter()
+        .map(|c| (c.phone.as_str(), c.contact_name.as_str()))
+        .collect();
+    let mut table = prettytable::Table::new();
+    table.add_row(row!["Id", "From", "Time", "Text"]);
+    for message in messages {
+        let sender = match contact_names.get(message.phone.as_str()) {
+            Some(contact_name) => format!("{} ({})", contact_name, message.phone),
+            None => message.phone,
+        };
+        let time = message.time.format("%Y-%m-%d %H:%M").to_string();
+        let time = if message.unread {
+            format!("{} *", time)
+        } else {
+            time
+        };
+        table.add_row(row![message.id, sender, time, message.text]);
+    }
+    table.to_string()
+}
@@ -0,0 +1,164 @@
+use chrono::{DateTime, Local};
+use prettytable::row;
+use sms_db::{
+    repository,
+    scheduled_jobs::{JobMessage, JobStatus, JobTarget, ScheduledJob},
+    sms_repository::RecordEntity,
+};
+
+use crate::{
+    args_parser::{JobsCommands, SendSmsArgs, SmsMessageArgs, SmsTargetArgs},
+    local_time,
+};
+
+pub async fn manage_jobs(cmd: JobsCommands) -> Result<String, String> {
+    match cmd {
+        JobsCommands::List { all } => handle_list_jobs(all).await,
+        JobsCommands::Cancel { id } => handle_cancel_job(id).await,
+        JobsCommands::Reschedule { id, at } => handle_reschedule_job(id, at).await,
+    }
+}
+
+pub async fn schedule_sms(send_args: SendSmsArgs, at: DateTime<Local>) -> Result<String, String> {
+    ensure_in_future(&at)?;
+    let mut job = ScheduledJob::new(
+        job_target(send_args.to),
+        job_message(send_args.message),
+        send_args.vars,
+        local_time::to_datetime(at),
+    );
+    job.transliterate = send_args.transliterate;
+    let job = repository::scheduled_jobs().create(job).await?;
+    Ok(format!(
+        "Message scheduled at {} with id {}",
+        local_time::format(&job.send_at),
+        job.id.id
+    ))
+}
+
+pub fn send_args_of(job: &ScheduledJob) -> SendSmsArgs {
+    let (number, contact_name, group_name) = match job.target.clone() {
+        JobTarget::Number(number) => (Some(number), None, None),
+        JobTarget::Contact(contact_name) => (None, Some(contact_name), None),
+        JobTarget::Group(group_name) => (None, None, Some(group_name)),
+    };
+    let (plain, template) = match job.message.clone() {
+        JobMessage::Plain(plain) => (Some(plain), None),
+        JobMessage::Template(template) => (None, Some(template)),
+    };
+    SendSmsArgs {
+        to: SmsTargetArgs {
+            number,
+            contact_name,
+            group_name,
+        },
+        message: SmsMessageArgs { plain, template },
+        at: None,
+        vars: job.vars.clone(),
+        transliterate: job.transliterate,
+        wait_delivery: false,
+    }
+}
+
+fn job_target(target: SmsTargetArgs) -> JobTarget {
+    if let Some(number) = target.number {
+        return JobTarget::Number(number);
+    }
+    if let Some(contact_name) = target.contact_name {
+        return JobTarget::Contact(contact_name);
+    }
+    if let Some(group_name) = target.group_name {
+        return JobTarget::Group(group_name);
+    }
+    panic!("Invalid state, no target were specified")
+}
+
+fn job_message(message: SmsMessageArgs) -> JobMessage {
+    if let Some(plain) = message.plain {
+        return JobMessage::Plain(plain);
+    }
+    if let Some(template) = message.template {
+        return JobMessage::Template(template);
+    }
+    panic!("Invalid state, no message were specified")
+}
+
+async fn handle_list_jobs(all: bool) -> Result<String, String> {
+    let jobs = if all {
+        repository::scheduled_jobs().get_all().await?
+    } else {
+        repository::scheduled_jobs()
+            .find_by_status(JobStatus::Scheduled)
+            .await?
+    };
+    Ok(render_jobs_table(jobs))
+}
+
+async fn handle_cancel_job(id: String) -> Result<String, String> {
+    repository::scheduled_jobs()
+        .change_status(
+            &ScheduledJob::id_from_str(&id),
+            JobStatus::Scheduled,
+            JobStatus::Cancelled,
+            None,
+        )
+        .await?
+        .map(|_| "Job cancelled successfully".to_string())
+        .ok_or_else(|| format!("Could not find scheduled job with id '{}'", id))
+}
+
+async fn handle_reschedule_job(id: String, at: DateTime<Local>) -> Result<String, String> {
+    ensure_in_future(&at)?;
+    let jobs = repository::scheduled_jobs();
+    let mut job = jobs
+        .get(&ScheduledJob::id_from_str(&id))
+        .await?
+        .ok_or_else(|| format!("Could not find job with id '{}'", id))?;
+    if matches!(job.status, JobStatus::Dispatching | JobStatus::Sent) {
+        return Err(format!(
+            "Job with id '{}' is {:?} and can not be rescheduled",
+            id, job.status
+        ));
+    }
+    job.send_at = local_time::to_datetime(at);
+    job.status = JobStatus::Scheduled;
+    job.error = None;
+    jobs.update(job)
+        .await
+        .map(|_| "Job rescheduled successfully".to_string())
+}
+
+fn ensure_in_future(at: &DateTime<Local>) -> Result<(), String> {
+    if *at <= Local::now() {
+        return Err(format!(
+            "Time {} is in the past",
+            at.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    Ok(())
+}
+
+fn render_jobs_table(jobs: Vec<ScheduledJob>) -> String {
+    let mut table = prettytable::Table::new();
+    table.add_row(row!["Id", "Send at", "To", "Message", "Status", "Error"]);
+    for job in jobs {
+        let to = match &job.target {
+            JobTarget::Number(number) => number.clone(),
+            JobTarget::Contact(contact_name) => format!("contact: {}", contact_name),
+            JobTarget::Group(group_name) => format!("group: {}", group_name),
+        };
+        let message = match &job.message {
+            JobMessage::Plain(plain) => plain.clone(),
+            JobMessage::Template(template) => format!("template: {}", template),
+        };
+        table.add_row(row![
+            job.id.id,
+            local_time::format(&job.send_at),
+            to,
+            message,
+            format!("{:?}", job.status),
+            job.error.unwrap_or_default()
+        ]);
+    }
+    table.to_string()
+}
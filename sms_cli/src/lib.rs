@@ -4,3 +4,20 @@ pub mod templates;
 pub mod contacts;
 pub mod groups;
 pub mod replace;
+pub mod inbox;
+pub mod history;
+pub mod jobs;
+pub mod daemon;
+pub mod recurrence;
+pub mod schedules;
+pub mod local_time;
+pub mod contacts_csv;
+pub mod data_files;
+pub mod export;
+pub mod vcard;
+pub mod template_vars;
+pub mod modem;
+pub mod ussd;
+pub mod delivery;
+pub mod campaigns;
+pub mod rate_limit;
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Local, Utc};
+use sms_db::Datetime;
+
+pub fn to_datetime(time: DateTime<Local>) -> Datetime {
+    Datetime::from(time.with_timezone(&Utc))
+}
+
+pub fn format(time: &Datetime) -> String {
+    time.0
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
@@ -5,7 +5,9 @@ use sms_cli::{
         Commands::Contacts,
         Commands::Send,
         Commands::Templates,
-        Commands::{Groups, Import},
+        Commands::{
+            Campaign, Daemon, Export, Groups, History, Import, Inbox, Jobs, Modem, Schedule, Ussd,
+        },
     },
     contacts,
 };
@@ -13,13 +15,36 @@ use sms_cli::{
 #[tokio::main]
 async fn main() {
     let args = args_parser::Cli::parse();
-    init_dependencies().await;
+    init_config();
+    // Daemon opens db only while checking for due jobs
+    if !matches!(args.command, Daemon { .. }) {
+        init_repository().await;
+    }
     let result = match args.command {
         Contacts(command) => contacts::manage_contacts(command).await,
-        Templates(commad) => sms_cli::templates::manage_templates(commad).await,
+        Templates(commad) => sms_cli::templates::manage_templates(commad, sms_config::get()).await,
         Groups(command) => sms_cli::groups::manage_groups(command).await,
         Send(send_args) => sms_cli::sms_send::send_sms(send_args, &sms_config::get().sms_api).await,
         Import(import_commands) => sms_cli::replace::manage_imports(import_commands).await,
+        Inbox { delete: None } => sms_cli::inbox::show_inbox(&sms_config::get().sms_api).await,
+        Inbox { delete: Some(id) } => {
+            sms_cli::inbox::delete_sms(id, &sms_config::get().sms_api).await
+        }
+        History(history_args) => sms_cli::history::show_history(history_args).await,
+        Modem(modem_commands) => {
+            sms_cli::modem::manage_modem(modem_commands, &sms_config::get().sms_api).await
+        }
+        Ussd { code } => sms_cli::ussd::send_ussd(code, &sms_config::get().sms_api).await,
+        Campaign(campaign_commands) => {
+            sms_cli::campaigns::manage_campaigns(campaign_commands, &sms_config::get().sms_api)
+                .await
+        }
+        Jobs(jobs_commands) => sms_cli::jobs::manage_jobs(jobs_commands).await,
+        Schedule(schedule_commands) => {
+            sms_cli::schedules::manage_schedules(schedule_commands).await
+        }
+        Export(export_commands) => sms_cli::export::manage_exports(export_commands).await,
+        Daemon { interval } => sms_cli::daemon::run_daemon(interval, sms_config::get()).await,
     };
     display_action_message(result);
 }
@@ -27,16 +52,22 @@ async fn main() {
 fn display_action_message(result: Result<String, String>) {
     match result {
         Ok(message) => println!("{}", message),
-        Err(e) => println!("Error while handling command, Reason: {:?}", e),
+        Err(e) => {
+            println!("Error while handling command, Reason: {}", e);
+            std::process::exit(1);
+        }
     };
 }
 
-async fn init_dependencies() {
+fn init_config() {
     let init_result = sms_config::init();
     if let Err(err) = init_result {
         println!("Could not initialize config. Reason {:?}", err);
         std::process::exit(1);
     }
+}
+
+async fn init_repository() {
     let init_result = sms_db::repository::init(sms_config::get()).await;
     if let Err(err) = init_result {
         println!("Could not initialize repository. Reason {:?}", err);
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use prettytable::{row, Table};
+use sms_api::{
+    rate_limit::{Budget, RateBudget},
+    ModemStatus, MAX_SIGNAL_BARS,
+};
+use sms_config::config::SmsApiConf;
+use sms_db::{balances::Balance, repository};
+
+use crate::{args_parser::ModemCommands, local_time, rate_limit};
+
+pub async fn manage_modem(
+    modem_commands: ModemCommands,
+    sms_api_config: &SmsApiConf,
+) -> Result<String, String> {
+    match modem_commands {
+        ModemCommands::Status { json } => {
+            let balance = repository::balances().get(&Balance::current_id()).await?;
+            let sent = rate_limit::load_sent_history(sms_api_config).await?;
+            show_status(sms_api_config, balance.as_ref(), sent, json).await
+        }
+        ModemCommands::Unlock { pin } => unlock(sms_api_config, pin).await,
+    }
+}
+
+pub async fn unlock(sms_api_config: &SmsApiConf, pin: Option<String>) -> Result<String, String> {
+    let pin = pin
+        .or_else(|| sms_api_config.provider.pin().map(str::to_string))
+        .ok_or("No PIN given and none is configured for provider")?;
+    sms_api::create_service(sms_api_config)
+        .map_err(|e| format!("Could not unlock SIM, Reason: {:?}", e))?
+        .unlock(&pin)
+        .await
+        .map(|_| "SIM unlocked".to_string())
+        .map_err(|e| format!("Could not unlock SIM, Reason: {}", e))
+}
+
+// Rate budget is counted from given sent sms, time and parts of each
+pub async fn show_status(
+    sms_api_config: &SmsApiConf,
+    balance: Option<&Balance>,
+    sent: Vec<(DateTime<Utc>, u32)>,
+    json: bool,
+) -> Result<String, String> {
+    let status = sms_api::create_service(sms_api_config)
+        .map_err(|e| format!("Could not read modem status, Reason: {:?}", e))?
+        .status()
+        .await
+        .map_err(|e| format!("Could not read modem status, Reason: {:?}", e))?;
+    let budget = sms_api::rate_limit::budget(&sms_api_config.rate_limit, sent);
+    if json {
+        let mut output = serde_json::to_value(&status)
+            .map_err(|e| format!("Could not serialize modem status, Reason: {}", e))?;
+        output["balance"] = match balance {
+            Some(balance) => serde_json::json!({
+                "amount": balance.amount,
+                "checked_at": local_time::format(&balance.checked_at),
+            }),
+            None => serde_json::Value::Null,
+        };
+        output["rate_budget"] = serde_json::to_value(&budget)
+            .map_err(|e| format!("Could not serialize modem status, Reason: {}", e))?;
+        serde_json::to_string_pretty(&output)
+            .map_err(|e| format!("Could not serialize modem status, Reason: {}", e))
+    } else {
+        Ok(render_status_table(&status, balance, budget.as_ref()))
+    }
+}
+
+fn render_status_table(
+    status: &ModemStatus,
+    balance: Option<&Balance>,
+    budget: Option<&RateBudget>,
+) -> String {
+    let storage = match &status.sms_storage {
+        Some(storage) => format!("{}/{} used", storage.used, storage.capacity),
+        None => "Unknown".to_string(),
+    };
+    let mut table = Table::new();
+    table.add_row(row!["Operator", status.operator]);
+    table.add_row(row!["Network", status.network_type]);
+    table.add_row(row![
+        "Signal",
+        format!("{}/{}", status.signal_bars, MAX_SIGNAL_BARS)
+    ]);
+    table.add_row(row!["Roaming", if status.roaming { "yes" } else { "no" }]);
+    table.add_row(row!["SIM", status.sim_state]);
+    table.add_row(row!["PIN", status.pin_state]);
+    table.add_row(row!["SMS storage", storage]);
+    if let Some(balance) = balance {
+        table.add_row(row![
+            "Balance",
+            format!(
+                "{:.2} (checked {})",
+                balance.amount,
+                local_time::format(&balance.checked_at)
+            )
+        ]);
+    }
+    if let Some(budget) = budget {
+        table.add_row(row!["Rate budget", render_budget(budget)]);
+    }
+    table.to_string()
+}
+
+fn render_budget(budget: &RateBudget) -> String {
+    let limit = |budget: &Option<Budget>, period: &str| {
+        budget
+            .as_ref()
+            .map(|b| format!("{}/{} {}", b.remaining, b.limit, period))
+    };
+    let limits: Vec<String> = [
+        limit(&budget.per_minute, "per minute"),
+        limit(&budget.per_hour, "per hour"),
+        limit(&budget.today, "today"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if limits.is_empty() {
+        "Only minimum spacing".to_string()
+    } else {
+        limits.join(", ")
+    }
+}
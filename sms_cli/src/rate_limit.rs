@@ -0,0 +1,33 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use sms_api::encoding;
+use sms_config::config::SmsApiConf;
+use sms_db::{
+    repository,
+    sent_messages::{SentMessageFilter, SentMessageStatus},
+};
+
+use crate::local_time;
+
+// Sms sent during the last day with their parts, limits count them so they hold across runs
+pub async fn load_sent_history(
+    sms_api_config: &SmsApiConf,
+) -> Result<Vec<(DateTime<Utc>, u32)>, String> {
+    if !sms_api_config.rate_limit.is_limited() {
+        return Ok(vec![]);
+    }
+    let filter = SentMessageFilter {
+        from: Some(local_time::to_datetime(Local::now() - Duration::days(1))),
+        status: Some(SentMessageStatus::Sent),
+        ..SentMessageFilter::default()
+    };
+    let sent = repository::sent_messages()
+        .find_history(filter)
+        .await?
+        .into_iter()
+        .filter_map(|message| {
+            let parts = encoding::analyze(&message.text).segments as u32;
+            message.sent_at.map(|sent_at| (sent_at.0, parts))
+        })
+        .collect();
+    Ok(sent)
+}
@@ -0,0 +1,186 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Local};
+use cron::Schedule;
+use sms_db::recurring_schedules::ScheduleSpec;
+
+const WEEKDAYS: [(&str, &str); 7] = [
+    ("MO", "Mon"),
+    ("TU", "Tue"),
+    ("WE", "Wed"),
+    ("TH", "Thu"),
+    ("FR", "Fri"),
+    ("SA", "Sat"),
+    ("SU", "Sun"),
+];
+
+// Cron crate counts weekdays from Sunday as 1
+const CRON_WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+pub fn parse_schedule(spec: &ScheduleSpec) -> Result<Schedule, String> {
+    let cron_expression = match spec {
+        ScheduleSpec::Cron(expression) => cron_with_seconds(expression)?,
+        ScheduleSpec::RRule(rule) => rrule_to_cron(rule)?,
+    };
+    Schedule::from_str(&cron_expression)
+        .map_err(|e| format!("Invalid schedule '{}', Reason: {}", cron_expression, e))
+}
+
+pub fn next_fire_times(
+    spec: &ScheduleSpec,
+    after: DateTime<Local>,
+    count: usize,
+) -> Result<Vec<DateTime<Local>>, String> {
+    Ok(parse_schedule(spec)?.after(&after).take(count).collect())
+}
+
+pub fn next_fire_time(
+    spec: &ScheduleSpec,
+    after: DateTime<Local>,
+) -> Result<DateTime<Local>, String> {
+    next_fire_times(spec, after, 1)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Schedule {:?} will never fire again", spec))
+}
+
+// Accepts classic 5 field cron (minute hour day month weekday) as well as 6 and 7 field
+// expressions with seconds (and years) understood by cron crate.
+fn cron_with_seconds(expression: &str) -> Result<String, String> {
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    match fields.len() {
+        5 => Ok(format!(
+            "0 {} {}",
+            fields[..4].join(" "),
+            weekday_names(fields[4])?
+        )),
+        6 | 7 => Ok(expression.trim().to_string()),
+        count => Err(format!(
+            "Invalid cron expression '{}', expected 5 fields but got {}",
+            expression, count
+        )),
+    }
+}
+
+// Supports subset of RFC 5545 RRULE that can be expressed as cron:
+// FREQ (HOURLY, DAILY, WEEKLY, MONTHLY, YEARLY), BYMONTH, BYMONTHDAY, BYDAY, BYHOUR, BYMINUTE
+fn rrule_to_cron(rule: &str) -> Result<String, String> {
+    let rule = rule.trim().trim_start_matches("RRULE:");
+    let mut freq = None;
+    let mut by_month = None;
+    let mut by_month_day = None;
+    let mut by_day = None;
+    let mut by_hour = None;
+    let mut by_minute = None;
+    for part in rule.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid RRULE part '{}', expected KEY=VALUE", part))?;
+        match key.to_uppercase().as_str() {
+            "FREQ" => freq = Some(value.to_uppercase()),
+            "INTERVAL" if value == "1" => {}
+            "BYMONTH" => by_month = Some(numbers(key, value, 1, 12)?),
+            "BYMONTHDAY" => by_month_day = Some(numbers(key, value, 1, 31)?),
+            "BYDAY" => by_day = Some(weekdays(value)?),
+            "BYHOUR" => by_hour = Some(numbers(key, value, 0, 23)?),
+            "BYMINUTE" => by_minute = Some(numbers(key, value, 0, 59)?),
+            _ => return Err(format!("Unsupported RRULE part '{}'", part)),
+        }
+    }
+    let freq = freq.ok_or_else(|| "RRULE is missing FREQ".to_string())?;
+    let (hour, day, month) = match freq.as_str() {
+        "HOURLY" => ("*", "*", "*"),
+        "DAILY" | "WEEKLY" => ("0", "*", "*"),
+        "MONTHLY" => ("0", if by_day.is_some() { "*" } else { "1" }, "*"),
+        "YEARLY" => ("0", "*", "*"),
+        _ => return Err(format!("Unsupported RRULE FREQ '{}'", freq)),
+    };
+    if freq == "WEEKLY" && by_day.is_none() {
+        return Err("RRULE with FREQ=WEEKLY requires BYDAY".to_string());
+    }
+    // Date of yearly rule would come from DTSTART, which is not supported
+    if freq == "YEARLY" && by_day.is_none() && (by_month.is_none() || by_month_day.is_none()) {
+        return Err(
+            "RRULE with FREQ=YEARLY requires BYDAY, or BYMONTH with BYMONTHDAY".to_string(),
+        );
+    }
+    Ok(format!(
+        "0 {} {} {} {} {}",
+        by_minute.unwrap_or_else(|| "0".to_string()),
+        by_hour.unwrap_or_else(|| hour.to_string()),
+        by_month_day.unwrap_or_else(|| day.to_string()),
+        by_month.unwrap_or_else(|| month.to_string()),
+        by_day.unwrap_or_else(|| "*".to_string()),
+    ))
+}
+
+fn numbers(key: &str, value: &str, min: u32, max: u32) -> Result<String, String> {
+    for number in value.split(',') {
+        match number.parse::<u32>() {
+            Ok(n) if (min..=max).contains(&n) => {}
+            _ => {
+                return Err(format!(
+                    "Invalid {} value '{}', expected numbers from {} to {}",
+                    key, number, min, max
+                ))
+            }
+        }
+    }
+    Ok(value.to_string())
+}
+
+fn weekdays(value: &str) -> Result<String, String> {
+    value
+        .split(',')
+        .map(|day| {
+            WEEKDAYS
+                .iter()
+                .find(|(rrule_day, _)| rrule_day.eq_ignore_ascii_case(day))
+                .map(|(_, cron_day)| cron_day.to_string())
+                .ok_or_else(|| format!("Invalid BYDAY value '{}'", day))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|days| days.join(","))
+}
+
+// Classic cron counts weekdays from 0 as Sunday (7 is Sunday as well), so numeric days,
+// ranges and steps are rewritten to names.
+fn weekday_names(field: &str) -> Result<String, String> {
+    let invalid = || {
+        format!(
+            "Invalid cron weekday '{}', expected days from 0 to 7",
+            field
+        )
+    };
+    let day = |value: &str| match value.parse::<usize>() {
+        Ok(day) if day <= 7 => Ok(day),
+        _ => Err(invalid()),
+    };
+    let mut names: Vec<&str> = vec![];
+    for item in field.split(',') {
+        if !item.chars().any(|c| c.is_ascii_digit()) {
+            names.push(item);
+            continue;
+        }
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().map_err(|_| invalid())?),
+            None => (item, 1),
+        };
+        let (first, last) = match range.split_once('-') {
+            Some((first, last)) => (day(first)?, day(last)?),
+            None if range == "*" => (0, 6),
+            None if item.contains('/') => (day(range)?, 6),
+            None => (day(range)?, day(range)?),
+        };
+        if first > last || step == 0 {
+            return Err(invalid());
+        }
+        for day in (first..=last).step_by(step) {
+            let name = CRON_WEEKDAYS[day % 7];
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    Ok(names.join(","))
+}
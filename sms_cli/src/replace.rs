@@ -1,41 +1,357 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use sms_db::{groups::Group, repository};
+use prettytable::{row, Table};
+use sms_db::{contacts::Contact, groups::Group, repository, templates::Template};
 
-use crate::args_parser::ImportCommads;
+use crate::{
+    args_parser::{CsvColumnsArgs, FileFormat, ImportCommads},
+    contacts_csv::{self, ContactRow},
+    data_files::{self, GroupMemberRow, GroupRow, TemplateRow},
+    vcard::{self, VCard},
+};
 
 pub async fn manage_imports(import_commands: ImportCommads) -> Result<String, String> {
     match import_commands {
         ImportCommads::ReplaceContacts {
             source_csv,
             group_name,
-        } => handle_replace_contacts(source_csv, group_name).await,
+            columns,
+        } => handle_replace_contacts(source_csv, group_name, columns).await,
+        ImportCommads::MergeContacts {
+            source_csv,
+            columns,
+            remove_missing,
+            dry_run,
+        } => handle_merge_contacts(source_csv, columns, remove_missing, dry_run).await,
+        ImportCommads::Templates { source } => handle_import_templates(source).await,
+        ImportCommads::Groups { source } => handle_import_groups(source).await,
+        ImportCommads::Vcard { source, dry_run } => handle_import_vcard(source, dry_run).await,
     }
 }
 
 async fn handle_replace_contacts(
     source_csv: PathBuf,
     group_name: String,
+    columns: CsvColumnsArgs,
+) -> Result<String, String> {
+    ensure_file_exists(&source_csv)?;
+    let rows = read_contact_rows(&source_csv, &columns)?;
+
+    let group_id = Group::id_from_name(&group_name);
+    let groups = repository::groups();
+    let current_contacts = groups
+        .find_group_details(&group_id)
+        .await?
+        .ok_or_else(|| format!("Group {} not found", group_name))?
+        .contacts;
+
+    let mut removed = vec![];
+    let mut unchanged_rows = vec![];
+    for contact in current_contacts {
+        let same_row = rows.iter().enumerate().position(|(index, row)| {
+            !unchanged_rows.contains(&index) && is_same_contact(row, &contact)
+        });
+        match same_row {
+            Some(index) => unchanged_rows.push(index),
+            None => removed.push(contact.id),
+        }
+    }
+    let added: Vec<Contact> = rows
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !unchanged_rows.contains(index))
+        .map(|(_, row)| {
+            Contact::new(
+                row.first_name,
+                row.surname_name,
+                row.phone,
+                row.contact_name,
+            )
+        })
+        .collect();
+
+    let summary = format!(
+        "Contacts of group {} replaced: {} added, {} removed, {} unchanged",
+        group_name,
+        added.len(),
+        removed.len(),
+        unchanged_rows.len()
+    );
+    groups.replace_contacts(&group_id, removed, added).await?;
+    Ok(summary)
+}
+
+async fn handle_merge_contacts(
+    source_csv: PathBuf,
+    columns: CsvColumnsArgs,
+    remove_missing: bool,
+    dry_run: bool,
 ) -> Result<String, String> {
+    ensure_file_exists(&source_csv)?;
+    let rows = read_contact_rows(&source_csv, &columns)?;
+    merge_contact_rows(rows, remove_missing, dry_run).await
+}
+
+async fn merge_contact_rows(
+    rows: Vec<ContactRow>,
+    remove_missing: bool,
+    dry_run: bool,
+) -> Result<String, String> {
+    let contacts = repository::contacts();
+    let mut current_contacts: Vec<Option<Contact>> =
+        contacts.get_all().await?.into_iter().map(Some).collect();
+
+    let mut diff = Table::new();
+    diff.add_row(row!["Action", "Contact Name", "Changes"]);
+    let mut added = vec![];
+    let mut updated = vec![];
+    let mut unchanged = 0;
+    for row in rows {
+        let Some(current) = take_matching_contact(&mut current_contacts, &row) else {
+            let contact = Contact::new(
+                row.first_name,
+                row.surname_name,
+                row.phone,
+                row.contact_name,
+            );
+            diff.add_row(row![Fg->"add", contact.contact_name, describe_contact(&contact)]);
+            added.push(contact);
+            continue;
+        };
+        let contact_name = row
+            .contact_name
+            .unwrap_or_else(|| current.contact_name.clone());
+        let merged = Contact::new_with_id(
+            current.id.clone(),
+            row.first_name,
+            row.surname_name,
+            row.phone,
+            Some(contact_name),
+        );
+        let changes = describe_changes(&current, &merged);
+        if changes.is_empty() {
+            unchanged += 1;
+            continue;
+        }
+        diff.add_row(row![Fy->"update", current.contact_name, changes.join("\n")]);
+        updated.push(merged);
+    }
+    let removed: Vec<Contact> = current_contacts.into_iter().flatten().collect();
+    if remove_missing {
+        for contact in &removed {
+            diff.add_row(row![Fr->"remove", contact.contact_name, describe_contact(contact)]);
+        }
+    }
+    diff.printstd();
+
+    let summary = format!(
+        "{} added, {} updated, {} {}, {} unchanged",
+        added.len(),
+        updated.len(),
+        removed.len(),
+        if remove_missing { "removed" } else { "kept" },
+        unchanged
+    );
+    if dry_run {
+        return Ok(format!("Dry run, nothing was saved: {}", summary));
+    }
+    let removed = if remove_missing {
+        removed.into_iter().map(|c| c.id).collect()
+    } else {
+        vec![]
+    };
+    contacts.merge_contacts(added, updated, removed).await?;
+    Ok(format!("Contacts merged: {}", summary))
+}
+
+// Matches by phone first and falls back to contact name.
+// Matched contact is taken out, so it can't be matched again by another row.
+pub fn take_matching_contact(
+    current_contacts: &mut [Option<Contact>],
+    row: &ContactRow,
+) -> Option<Contact> {
+    let index = current_contacts
+        .iter()
+        .position(|c| matches!(c, Some(c) if c.phone == row.phone))
+        .or_else(|| {
+            let contact_name = row.contact_name.as_ref()?;
+            current_contacts
+                .iter()
+                .position(|c| matches!(c, Some(c) if &c.contact_name == contact_name))
+        })?;
+    current_contacts[index].take()
+}
+
+async fn handle_import_templates(source: PathBuf) -> Result<String, String> {
+    ensure_file_exists(&source)?;
+    let rows: Vec<TemplateRow> = data_files::read_rows(&source)?;
+    let templates = repository::templates();
+    let mut created = 0;
+    let mut updated = 0;
+    for row in rows {
+        let mut template = Template::new(row.name, row.text);
+        template.transliterate = row.transliterate;
+        if templates.get(&template.id).await?.is_some() {
+            templates.update(template).await?;
+            updated += 1;
+        } else {
+            templates.create(template).await?;
+            created += 1;
+        }
+    }
+    Ok(format!(
+        "Templates imported: {} created, {} updated",
+        created, updated
+    ))
+}
+
+async fn handle_import_groups(source: PathBuf) -> Result<String, String> {
+    ensure_file_exists(&source)?;
+    let rows = data_files::read_groups(&source)?;
+    import_group_rows(rows).await
+}
+
+async fn handle_import_vcard(source: PathBuf, dry_run: bool) -> Result<String, String> {
+    ensure_file_exists(&source)?;
+    let content = std::fs::read_to_string(&source)
+        .map_err(|e| format!("Could not read file {}, Reason: {}", source.display(), e))?;
+    let (cards, cards_without_phone): (Vec<VCard>, Vec<VCard>) = vcard::parse(&content)?
+        .into_iter()
+        .partition(|card| !card.phone.is_empty());
+    let mut group_rows: Vec<GroupRow> = vec![];
+    let mut contact_rows = vec![];
+    for card in cards {
+        let contact_name = card
+            .contact_name
+            .clone()
+            .unwrap_or_else(|| format!("{} {}", card.first_name, card.surname_name));
+        for category in card.categories {
+            let member = GroupMemberRow {
+                contact_name: contact_name.clone(),
+                phone: card.phone.clone(),
+            };
+            match group_rows.iter_mut().find(|g| g.name == category) {
+                Some(group) => group.members.push(member),
+                None => group_rows.push(GroupRow {
+                    name: category,
+                    members: vec![member],
+                }),
+            }
+        }
+        contact_rows.push(ContactRow {
+            first_name: card.first_name,
+            surname_name: card.surname_name,
+            phone: card.phone,
+            contact_name: Some(contact_name),
+        });
+    }
+    let mut summary = merge_contact_rows(contact_rows, false, dry_run).await?;
+    if !dry_run {
+        summary = format!("{}\n{}", summary, import_group_rows(group_rows).await?);
+    }
+    if !cards_without_phone.is_empty() {
+        summary = format!(
+            "{}\nSkipped {} cards without phone number",
+            summary,
+            cards_without_phone.len()
+        );
+    }
+    Ok(summary)
+}
+
+async fn import_group_rows(rows: Vec<GroupRow>) -> Result<String, String> {
+    let groups = repository::groups();
+    let mut created = 0;
+    let mut assigned = 0;
+    let mut missing = vec![];
+    for row in rows {
+        let group_id = Group::id_from_name(&row.name);
+        let current_members = match groups.find_group_details(&group_id).await? {
+            Some(details) => details.contacts,
+            None => {
+                groups.create(Group::new(row.name.clone())).await?;
+                created += 1;
+                vec![]
+            }
+        };
+        for member in row.members {
+            let candidates = repository::contacts()
+                .find_all_by_phone(&member.phone)
+                .await?;
+            let contact = candidates
+                .iter()
+                .find(|c| c.contact_name == member.contact_name)
+                .or_else(|| candidates.first());
+            let Some(contact) = contact else {
+                missing.push(format!("{} ({})", member.contact_name, member.phone));
+                continue;
+            };
+            if current_members.iter().any(|c| c.id == contact.id) {
+                continue;
+            }
+            groups.assign_contact(&contact.id, &group_id).await?;
+            assigned += 1;
+        }
+    }
+    let summary = format!(
+        "Groups imported: {} created, {} contacts assigned",
+        created, assigned
+    );
+    if missing.is_empty() {
+        Ok(summary)
+    } else {
+        Ok(format!(
+            "{}, contacts not found: {}",
+            summary,
+            missing.join(", ")
+        ))
+    }
+}
+
+fn read_contact_rows(source: &Path, columns: &CsvColumnsArgs) -> Result<Vec<ContactRow>, String> {
+    match data_files::format_of(source)? {
+        FileFormat::Csv => contacts_csv::read_contacts(source, columns),
+        FileFormat::Json => data_files::read_rows(source),
+    }
+}
+
+fn ensure_file_exists(source_csv: &Path) -> Result<(), String> {
     if !source_csv.exists() {
         return Err(format!(
             "File {} does not exist",
             source_csv.to_string_lossy()
         ));
     }
+    Ok(())
+}
 
-    //TODO: pare contacts from csv
-    // start trancaction
-    // delete all old contacts
-    // save all new contacts
-    // assign all new contacts to group
-    // commit transaction
-    let group_id = Group::id_from_name(&group_name);
-    let group = repository::groups()
-        .get(&group_id)
-        .await?
-        .ok_or_else(|| format!("Group {} not found", group_name))?;
+fn describe_contact(contact: &Contact) -> String {
+    format!(
+        "{} {}, {}",
+        contact.first_name, contact.surname_name, contact.phone
+    )
+}
 
-    Ok("".into())
+pub fn describe_changes(current: &Contact, merged: &Contact) -> Vec<String> {
+    [
+        ("first name", &current.first_name, &merged.first_name),
+        ("surname", &current.surname_name, &merged.surname_name),
+        ("phone", &current.phone, &merged.phone),
+        ("contact name", &current.contact_name, &merged.contact_name),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, old, new)| format!("{}: {} -> {}", field, old, new))
+    .collect()
 }
 
+fn is_same_contact(row: &ContactRow, contact: &Contact) -> bool {
+    let contact_name = row
+        .contact_name
+        .clone()
+        .unwrap_or_else(|| format!("{} {}", row.first_name, row.surname_name));
+    row.first_name == contact.first_name
+        && row.surname_name == contact.surname_name
+        && row.phone == contact.phone
+        && contact_name == contact.contact_name
+}
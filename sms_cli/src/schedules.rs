@@ -0,0 +1,162 @@
+use chrono::Local;
+use prettytable::row;
+use sms_db::{
+    groups::Group,
+    recurring_schedules::{RecurringSchedule, ScheduleSpec},
+    repository,
+    templates::Template,
+};
+
+use crate::{
+    args_parser::{ScheduleCommands, ScheduleExpressionArgs},
+    local_time, recurrence,
+};
+
+pub async fn manage_schedules(cmd: ScheduleCommands) -> Result<String, String> {
+    match cmd {
+        ScheduleCommands::Add {
+            name,
+            expression,
+            template,
+            group_name,
+        } => handle_add_schedule(name, expression, template, group_name).await,
+        ScheduleCommands::List => handle_list_schedules().await,
+        ScheduleCommands::Pause { name } => handle_pause_schedule(name).await,
+        ScheduleCommands::Resume { name } => handle_resume_schedule(name).await,
+        ScheduleCommands::Delete { name } => handle_delete_schedule(name).await,
+        ScheduleCommands::Preview { expression, number } => {
+            handle_preview_schedule(expression, number)
+        }
+    }
+}
+
+async fn handle_add_schedule(
+    name: String,
+    expression: ScheduleExpressionArgs,
+    template: String,
+    group_name: String,
+) -> Result<String, String> {
+    let spec = schedule_spec(expression);
+    let next_run_at = recurrence::next_fire_time(&spec, Local::now())?;
+    let template_id = Template::id_from_name(&template);
+    repository::templates()
+        .get(&template_id)
+        .await?
+        .ok_or_else(|| format!("Template {} not found", template))?;
+    let group_id = Group::id_from_name(&group_name);
+    repository::groups()
+        .get(&group_id)
+        .await?
+        .ok_or_else(|| format!("Group {} not found", group_name))?;
+    repository::recurring_schedules()
+        .create(RecurringSchedule::new(
+            name,
+            spec,
+            template_id,
+            group_id,
+            local_time::to_datetime(next_run_at),
+        ))
+        .await
+        .map(|schedule| {
+            format!(
+                "Schedule created successfully, next run at {}",
+                local_time::format(&schedule.next_run_at)
+            )
+        })
+}
+
+async fn handle_list_schedules() -> Result<String, String> {
+    repository::recurring_schedules()
+        .get_all()
+        .await
+        .map(render_schedules_table)
+}
+
+async fn handle_pause_schedule(name: String) -> Result<String, String> {
+    let schedules = repository::recurring_schedules();
+    let mut schedule = find_schedule(&name).await?;
+    schedule.paused = true;
+    schedules
+        .update(schedule)
+        .await
+        .map(|_| "Schedule paused successfully".to_string())
+}
+
+async fn handle_resume_schedule(name: String) -> Result<String, String> {
+    let schedules = repository::recurring_schedules();
+    let mut schedule = find_schedule(&name).await?;
+    let next_run_at = recurrence::next_fire_time(&schedule.spec, Local::now())?;
+    schedule.paused = false;
+    schedule.next_run_at = local_time::to_datetime(next_run_at);
+    schedules.update(schedule).await.map(|_| {
+        format!(
+            "Schedule resumed successfully, next run at {}",
+            next_run_at.format("%Y-%m-%d %H:%M")
+        )
+    })
+}
+
+async fn handle_delete_schedule(name: String) -> Result<String, String> {
+    repository::recurring_schedules()
+        .delete(&RecurringSchedule::id_from_name(&name))
+        .await
+        .map(|_| "Schedule deleted successfully".to_string())
+}
+
+fn handle_preview_schedule(
+    expression: ScheduleExpressionArgs,
+    number: usize,
+) -> Result<String, String> {
+    let fire_times = recurrence::next_fire_times(&schedule_spec(expression), Local::now(), number)?;
+    let mut table = prettytable::Table::new();
+    table.add_row(row!["#", "Fire time"]);
+    for (index, fire_time) in fire_times.into_iter().enumerate() {
+        table.add_row(row![index, fire_time.format("%Y-%m-%d %H:%M (%a)")]);
+    }
+    Ok(table.to_string())
+}
+
+async fn find_schedule(name: &str) -> Result<RecurringSchedule, String> {
+    repository::recurring_schedules()
+        .get(&RecurringSchedule::id_from_name(name))
+        .await?
+        .ok_or_else(|| format!("Schedule {} not found", name))
+}
+
+fn schedule_spec(expression: ScheduleExpressionArgs) -> ScheduleSpec {
+    if let Some(cron) = expression.cron {
+        return ScheduleSpec::Cron(cron);
+    }
+    if let Some(rrule) = expression.rrule {
+        return ScheduleSpec::RRule(rrule);
+    }
+    panic!("Invalid state, no schedule expression were specified")
+}
+
+fn render_schedules_table(schedules: Vec<RecurringSchedule>) -> String {
+    let mut table = prettytable::Table::new();
+    table.add_row(row![
+        "Name", "Schedule", "Template", "Group", "Paused", "Next run", "Last run", "Error"
+    ]);
+    for schedule in schedules {
+        let spec = match &schedule.spec {
+            ScheduleSpec::Cron(cron) => format!("cron: {}", cron),
+            ScheduleSpec::RRule(rrule) => format!("rrule: {}", rrule),
+        };
+        table.add_row(row![
+            schedule.name,
+            spec,
+            schedule.template.id.to_raw(),
+            schedule.group.id.to_raw(),
+            schedule.paused,
+            local_time::format(&schedule.next_run_at),
+            schedule
+                .last_run_at
+                .as_ref()
+                .map(local_time::format)
+                .unwrap_or_default(),
+            schedule.error.unwrap_or_default()
+        ]);
+    }
+    table.to_string()
+}
@@ -1,45 +1,341 @@
-use sms_api::SmsError;
+use std::time::{Duration, Instant};
+
+use prettytable::row;
+use sms_api::{
+    encoding::{self, SmsParts},
+    transliteration, RecipientResult, RecipientStatus, SmsError, SmsService,
+};
 use sms_config::config::SmsApiConf;
-use sms_db::{groups::Group, templates::Template};
+use sms_db::{
+    campaigns::{Campaign, CampaignRecipient, RecipientState},
+    contacts::Contact,
+    groups::Group,
+    repository,
+    sent_messages::{DeliveryStatus, SentMessage},
+    templates::Template,
+    Thing,
+};
+
+use crate::{
+    args_parser::{SendSmsArgs, SmsMessageArgs, SmsTargetArgs},
+    delivery, rate_limit, template_vars,
+};
 
-use crate::args_parser::{SendSmsArgs, SmsMessageArgs, SmsTargetArgs};
+const DELIVERY_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(300);
+const SKIPPED_REASON: &str = "Not attempted because modem failed on previous recipient";
+
+struct Recipient {
+    phone: String,
+    contact: Option<Contact>,
+}
 
 pub async fn send_sms(
     send_args: SendSmsArgs,
     sms_api_config: &SmsApiConf,
 ) -> Result<String, String> {
-    let numbers = get_recipient_numbers(send_args.to).await?;
-    let message = get_message_to_send(send_args.message).await?;
-    println!(
-        "Sending sms to {} number of people with message '{}'",
-        numbers.len(),
-        message
-    );
+    if let Some(at) = send_args.at {
+        return crate::jobs::schedule_sms(send_args, at).await;
+    }
+    // Waiting for delivery requests reports even when they are disabled in config
+    let sms_api_config = &SmsApiConf {
+        delivery_reports: sms_api_config.delivery_reports || send_args.wait_delivery,
+        ..sms_api_config.clone()
+    };
+    let group = send_args.to.group_name.as_deref().map(Group::id_from_name);
+    let template_name = send_args.message.template.clone();
+    let recipients = get_recipients(send_args.to).await?;
+    let (text, template_transliterate) = get_message_to_send(send_args.message).await?;
+    let transliterate = send_args
+        .transliterate
+        .or(template_transliterate)
+        .unwrap_or(sms_api_config.transliterate);
+    // Plain text is sent as typed, unless values of variables are given for it
+    let messages = if template_name.is_some() || !send_args.vars.is_empty() {
+        render_messages(&text, recipients, &send_args.vars)?
+    } else {
+        vec![(text, recipients)]
+    };
+    let recipients = messages
+        .into_iter()
+        .flat_map(|(message, recipients)| {
+            let message = if transliterate {
+                transliterate_message(message)
+            } else {
+                message
+            };
+            recipients.into_iter().map(move |r| {
+                CampaignRecipient::new(r.phone, r.contact.map(|c| c.id), message.clone())
+            })
+        })
+        .collect();
+
+    let mut campaign = repository::campaigns()
+        .create(Campaign::new(group, template_name, recipients))
+        .await?;
+    println!("Started campaign {}", campaign.id.id);
+    let (results, awaiting_delivery) =
+        dispatch(&mut campaign, RecipientState::Pending, sms_api_config).await?;
+    println!("{}", render_summary_table(&results));
+    let summary = summarize_results(&results);
+    if summary.is_ok() && send_args.wait_delivery {
+        return wait_for_delivery(&awaiting_delivery, sms_api_config).await;
+    }
+    summary.map_err(|e| with_campaign_hint(e, &campaign))
+}
+
+// Continues campaign by sending message only to its recipients in given state
+pub async fn resume_campaign(
+    id: &str,
+    state: RecipientState,
+    sms_api_config: &SmsApiConf,
+) -> Result<String, String> {
+    let mut campaign = repository::campaigns()
+        .get(&Campaign::id_from_str(id))
+        .await?
+        .ok_or_else(|| format!("Could not find campaign with id '{}'", id))?;
+    if campaign.count(state) == 0 {
+        let state = match state {
+            RecipientState::Pending => "pending",
+            RecipientState::Sent => "sent",
+            RecipientState::Failed => "failed",
+        };
+        return Ok(format!("Campaign {} has no {} recipients", id, state));
+    }
+    let (results, _) = dispatch(&mut campaign, state, sms_api_config).await?;
+    println!("{}", render_summary_table(&results));
+    summarize_results(&results).map_err(|e| with_campaign_hint(e, &campaign))
+}
+
+fn with_campaign_hint(error: String, campaign: &Campaign) -> String {
+    format!(
+        "{}, continue with `sms campaign resume {}` or `sms campaign retry-failed {}`",
+        error, campaign.id.id, campaign.id.id
+    )
+}
+
+// Sends message to recipients of campaign in given state one by one. Campaign is saved
+// after every recipient, so interrupted send can be resumed without sending twice.
+// Returns ids of messages awaiting delivery.
+async fn dispatch(
+    campaign: &mut Campaign,
+    state: RecipientState,
+    sms_api_config: &SmsApiConf,
+) -> Result<(Vec<RecipientResult>, Vec<Thing>), String> {
+    let sent = rate_limit::load_sent_history(sms_api_config).await?;
+    let service = sms_api::create_service_with_history(sms_api_config, sent)
+        .map_err(|e| format!("Could not send sms, Reason: {}", e))?;
+    let mut results = vec![];
+    let mut awaiting_delivery = vec![];
+    let mut modem_failed = false;
+    for (message, recipients) in campaign.messages_in_state(state) {
+        println!(
+            "Sending sms to {} number of people with message '{}'",
+            recipients.len(),
+            message
+        );
+        println!(
+            "{}",
+            describe_cost(
+                &encoding::analyze(&message),
+                recipients.len(),
+                sms_api_config.price_per_sms
+            )
+        );
+        for recipient in recipients {
+            // Skipped recipient stays pending in campaign and gets history row when resumed
+            let result = if modem_failed {
+                RecipientResult {
+                    phone: recipient.phone.clone(),
+                    status: RecipientStatus::Skipped,
+                    provider: None,
+                    reference: None,
+                }
+            } else {
+                let sent_message = record_pending(
+                    &recipient,
+                    campaign.group.clone(),
+                    campaign.template_name.clone(),
+                    sms_api_config,
+                )
+                .await?;
+                let result = send(service.as_ref(), &message, &recipient.phone).await;
+                if let Some(id) = record_result(sent_message, sms_api_config, &result).await? {
+                    awaiting_delivery.push(id);
+                }
+                result
+            };
+            let (state, error) = match &result.status {
+                RecipientStatus::Sent => (RecipientState::Sent, None),
+                RecipientStatus::Failed(e) => {
+                    modem_failed |= e.affects_all_recipients();
+                    (RecipientState::Failed, Some(e.to_string()))
+                }
+                RecipientStatus::Skipped => (RecipientState::Pending, None),
+            };
+            campaign.set_state(&recipient.phone, &message, state, error);
+            repository::campaigns().update(campaign.clone()).await?;
+            results.push(result);
+        }
+    }
+    Ok((results, awaiting_delivery))
+}
 
-    send(&message, numbers, sms_api_config)
-        .await
-        .map(|_| "Message sent".to_string())
-        .map_err(|e| format!("Could not send message, Reason: {:?}", e))
+pub fn render_summary_table(results: &[RecipientResult]) -> String {
+    let mut table = prettytable::Table::new();
+    table.add_row(row!["To", "Status", "Reason"]);
+    for result in results {
+        let (status, reason) = match &result.status {
+            RecipientStatus::Sent => ("Sent", String::new()),
+            RecipientStatus::Failed(e) => ("Failed", e.to_string()),
+            RecipientStatus::Skipped => ("Skipped", SKIPPED_REASON.to_string()),
+        };
+        table.add_row(row![result.phone, status, reason]);
+    }
+    table.to_string()
 }
 
-async fn get_recipient_numbers(target_args: SmsTargetArgs) -> Result<Vec<String>, String> {
+// Error lists every recipient the message was not sent to, so it can be stored by daemon
+pub fn summarize_results(results: &[RecipientResult]) -> Result<String, String> {
+    let not_sent: Vec<String> = results
+        .iter()
+        .filter_map(|result| match &result.status {
+            RecipientStatus::Sent => None,
+            RecipientStatus::Failed(e) => Some(format!("{}: {}", result.phone, e)),
+            RecipientStatus::Skipped => Some(format!("{}: {}", result.phone, SKIPPED_REASON)),
+        })
+        .collect();
+    if not_sent.is_empty() {
+        return Ok(format!("Message sent to {} recipient(s)", results.len()));
+    }
+    Err(format!(
+        "Message not sent to {} of {} recipient(s), {}",
+        not_sent.len(),
+        results.len(),
+        not_sent.join(", ")
+    ))
+}
+
+async fn wait_for_delivery(ids: &[Thing], sms_api_config: &SmsApiConf) -> Result<String, String> {
+    println!("Message sent, waiting for delivery reports");
+    let started = Instant::now();
+    loop {
+        delivery::sync_delivery_reports(sms_api_config).await?;
+        let (delivered, failed, pending) = count_deliveries(ids).await?;
+        let summary = format!(
+            "delivered: {}, failed: {}, pending: {}",
+            delivered, failed, pending
+        );
+        if failed == 0 && pending == 0 {
+            return Ok(format!("Message delivered to all recipients, {}", summary));
+        }
+        if pending == 0 || started.elapsed() >= DELIVERY_TIMEOUT {
+            return Err(format!(
+                "Message not delivered to all recipients, {}",
+                summary
+            ));
+        }
+        tokio::time::sleep(DELIVERY_CHECK_INTERVAL).await;
+    }
+}
+
+async fn count_deliveries(ids: &[Thing]) -> Result<(usize, usize, usize), String> {
+    let sent_messages = repository::sent_messages();
+    let (mut delivered, mut failed, mut pending) = (0, 0, 0);
+    for id in ids {
+        match sent_messages.get(id).await?.and_then(|m| m.delivery) {
+            Some(DeliveryStatus::Delivered) => delivered += 1,
+            Some(DeliveryStatus::Failed) => failed += 1,
+            _ => pending += 1,
+        }
+    }
+    Ok((delivered, failed, pending))
+}
+
+// Renders message for every recipient before anything is sent, so missing variable fails
+// whole send. Recipients with identical rendered text share one message.
+fn render_messages(
+    text: &str,
+    recipients: Vec<Recipient>,
+    vars: &[(String, String)],
+) -> Result<Vec<(String, Vec<Recipient>)>, String> {
+    let mut messages: Vec<(String, Vec<Recipient>)> = vec![];
+    for recipient in recipients {
+        let values = template_vars::recipient_values(recipient.contact.as_ref(), vars);
+        let message = template_vars::render(text, &values).map_err(|e| {
+            format!(
+                "Could not render message for {}, Reason: {}",
+                recipient.phone, e
+            )
+        })?;
+        match messages.iter_mut().find(|(m, _)| *m == message) {
+            Some((_, recipients)) => recipients.push(recipient),
+            None => messages.push((message, vec![recipient])),
+        }
+    }
+    Ok(messages)
+}
+
+fn transliterate_message(message: String) -> String {
+    let transliterated = transliteration::transliterate(&message);
+    if transliterated != message {
+        println!("Message transliterated to GSM-7: '{}'", transliterated);
+    }
+    transliterated
+}
+
+fn describe_cost(parts: &SmsParts, recipients: usize, price_per_sms: Option<f64>) -> String {
+    let total = parts.segments * recipients;
+    let mut description = format!(
+        "Message is encoded as {} and takes {} sms part(s), {} sms in total",
+        parts.encoding, parts.segments, total
+    );
+    if let Some(price) = price_per_sms {
+        description.push_str(&format!(", estimated cost {:.2}", price * total as f64));
+    }
+    if !parts.ucs2_characters.is_empty() {
+        let characters: Vec<String> = parts
+            .ucs2_characters
+            .iter()
+            .map(|c| format!("'{}'", c))
+            .collect();
+        description.push_str(&format!(
+            "\nUCS-2 is required because of characters: {}",
+            characters.join(", ")
+        ));
+    }
+    description
+}
+
+async fn get_recipients(target_args: SmsTargetArgs) -> Result<Vec<Recipient>, String> {
     if let Some(contact) = target_args.contact_name {
-        return sms_db::repository::contacts()
+        return repository::contacts()
             .find_all_by_contact_name(&contact)
             .await
-            .map(|contacts| contacts.into_iter().map(|c| c.phone).collect());
+            .map(|contacts| {
+                contacts
+                    .into_iter()
+                    .map(|c| Recipient {
+                        phone: c.phone.clone(),
+                        contact: Some(c),
+                    })
+                    .collect()
+            });
     }
     if let Some(phone) = target_args.number {
-        return Ok(vec![phone]);
+        return Ok(vec![Recipient {
+            phone,
+            contact: None,
+        }]);
     }
     if let Some(group) = target_args.group_name {
-        return find_all_group_numbers(group).await;
+        return find_all_group_recipients(group).await;
     }
     panic!("Invalid state, no target were specified")
 }
 
-async fn find_all_group_numbers(group_name: String) -> Result<Vec<String>, String> {
-    sms_db::repository::groups()
+async fn find_all_group_recipients(group_name: String) -> Result<Vec<Recipient>, String> {
+    repository::groups()
         .find_group_details(&Group::id_from_name(&group_name))
         .await?
         .ok_or_else(|| format!("Group {} not found", group_name))
@@ -47,38 +343,99 @@ async fn find_all_group_numbers(group_name: String) -> Result<Vec<String>, Strin
             group_details
                 .contacts
                 .into_iter()
-                .map(|c| c.phone)
+                .map(|c| Recipient {
+                    phone: c.phone.clone(),
+                    contact: Some(c),
+                })
                 .collect()
         })
 }
 
-async fn get_message_to_send(args: SmsMessageArgs) -> Result<String, String> {
+// Returns message text with transliteration setting of template
+async fn get_message_to_send(args: SmsMessageArgs) -> Result<(String, Option<bool>), String> {
     if let Some(plain) = args.plain {
-        return Ok(plain);
+        return Ok((plain, None));
     }
     if let Some(template) = args.template {
-        return sms_db::repository::templates()
+        return repository::templates()
             .get(&Template::id_from_name(&template))
             .await?
-            .map(|t| t.text)
+            .map(|t| (t.text, t.transliterate))
             .ok_or_else(|| format!("Template {} not found", template));
     }
     panic!("Invalid state, no message were specified")
 }
 
-async fn send(
-    message: &str,
-    numbers: Vec<String>,
+// Error of whole send is reported as failure of the recipient
+async fn send(service: &dyn SmsService, message: &str, phone: &str) -> RecipientResult {
+    let failed = |error| RecipientResult {
+        phone: phone.to_string(),
+        status: RecipientStatus::Failed(error),
+        provider: None,
+        reference: None,
+    };
+    match service.send_sms(message, &[phone]).await {
+        Ok(report) => report.recipients.into_iter().next().unwrap_or_else(|| {
+            failed(SmsError::UnknownError(
+                "Service didn't report result of send".to_string(),
+            ))
+        }),
+        Err(e) => failed(e),
+    }
+}
+
+// Message is stored before sending, so interrupted send is visible in history
+async fn record_pending(
+    recipient: &CampaignRecipient,
+    group: Option<Thing>,
+    template_name: Option<String>,
     sms_api_config: &SmsApiConf,
-) -> Result<(), SmsError> {
-    sms_api::create_service(sms_api_config)?
-        .send_sms(
-            message,
-            numbers
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<&str>>()
-                .as_slice(),
-        )
-        .await
+) -> Result<SentMessage, String> {
+    let contact = match &recipient.contact {
+        Some(contact) => Some(contact.clone()),
+        None => repository::contacts()
+            .find_all_by_phone(&recipient.phone)
+            .await?
+            .into_iter()
+            .next()
+            .map(|c| c.id),
+    };
+    let sent_message = SentMessage::new(
+        recipient.phone.clone(),
+        contact,
+        group,
+        recipient.text.clone(),
+        template_name,
+        sms_api_config.provider.description(),
+    );
+    repository::sent_messages().create(sent_message).await
+}
+
+// Returns id of message when it awaits delivery report
+async fn record_result(
+    mut sent_message: SentMessage,
+    sms_api_config: &SmsApiConf,
+    result: &RecipientResult,
+) -> Result<Option<Thing>, String> {
+    if let Some(provider) = &result.provider {
+        sent_message.provider = provider.clone();
+    }
+    sent_message.reference = result.reference;
+    match &result.status {
+        RecipientStatus::Sent => sent_message.mark_sent(),
+        RecipientStatus::Failed(e) => sent_message.mark_failed(e.to_string()),
+        // Message was not attempted, recipient stays pending in campaign
+        RecipientStatus::Skipped => {
+            repository::sent_messages().delete(&sent_message.id).await?;
+            return Ok(None);
+        }
+    }
+    let awaits_delivery =
+        matches!(result.status, RecipientStatus::Sent) && sms_api_config.delivery_reports;
+    if awaits_delivery {
+        sent_message.delivery = Some(DeliveryStatus::Pending);
+    }
+    let id = sent_message.id.clone();
+    repository::sent_messages().update(sent_message).await?;
+    Ok(awaits_delivery.then_some(id))
 }
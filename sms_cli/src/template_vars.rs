@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use chrono::Local;
+use sms_db::contacts::Contact;
+
+pub const CONTACT_VARIABLES: [&str; 4] = ["first_name", "surname_name", "contact_name", "phone"];
+pub const BUILTIN_VARIABLES: [&str; 2] = ["date", "time"];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+// Splits text like `Hi {{first_name}}!` into text and variable segments.
+pub fn parse(text: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = vec![];
+    let mut rest = text;
+    while !rest.is_empty() {
+        let open = rest.find("{{");
+        let close = rest.find("}}");
+        match (open, close) {
+            (None, None) => {
+                segments.push(Segment::Text(rest));
+                break;
+            }
+            (Some(open), Some(close)) if open < close => {
+                if open > 0 {
+                    segments.push(Segment::Text(&rest[..open]));
+                }
+                let name = rest[open + 2..close].trim();
+                if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(format!("Invalid placeholder '{}'", &rest[open..close + 2]));
+                }
+                segments.push(Segment::Variable(name));
+                rest = &rest[close + 2..];
+            }
+            (_, Some(close)) => {
+                return Err(format!(
+                    "Unbalanced braces, '}}}}' at position {} was never opened",
+                    text.len() - rest.len() + close
+                ))
+            }
+            (Some(open), None) => {
+                return Err(format!(
+                    "Unbalanced braces, '{{{{' at position {} is never closed",
+                    text.len() - rest.len() + open
+                ))
+            }
+        }
+    }
+    Ok(segments)
+}
+
+pub fn variables(text: &str) -> Result<Vec<String>, String> {
+    let mut variables: Vec<String> = vec![];
+    for segment in parse(text)? {
+        if let Segment::Variable(name) = segment {
+            if !variables.iter().any(|v| v == name) {
+                variables.push(name.to_string());
+            }
+        }
+    }
+    Ok(variables)
+}
+
+pub fn render(text: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    let mut rendered = String::with_capacity(text.len());
+    for segment in parse(text)? {
+        match segment {
+            Segment::Text(text) => rendered.push_str(text),
+            Segment::Variable(name) => rendered.push_str(
+                values
+                    .get(name)
+                    .ok_or_else(|| format!("Missing value for variable '{{{{{}}}}}'", name))?,
+            ),
+        }
+    }
+    Ok(rendered)
+}
+
+// Values available for one recipient, `--var` values take precedence over contact fields.
+pub fn recipient_values(
+    contact: Option<&Contact>,
+    vars: &[(String, String)],
+) -> HashMap<String, String> {
+    let now = Local::now();
+    let mut values = HashMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+    ]);
+    if let Some(contact) = contact {
+        values.insert("first_name".to_string(), contact.first_name.clone());
+        values.insert("surname_name".to_string(), contact.surname_name.clone());
+        values.insert("contact_name".to_string(), contact.contact_name.clone());
+        values.insert("phone".to_string(), contact.phone.clone());
+    }
+    values.extend(vars.iter().cloned());
+    values
+}
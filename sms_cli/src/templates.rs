@@ -1,21 +1,51 @@
 use prettytable::row;
+use sms_api::{encoding, transliteration};
+use sms_config::config::SmsConfig;
 use sms_db::{repository, templates::Template};
 
-use crate::args_parser::TemplatesCommands;
+use crate::{
+    args_parser::{TemplatePreviewContactArgs, TemplatesCommands},
+    template_vars,
+};
 
-pub async fn manage_templates(cmd: TemplatesCommands) -> Result<String, String> {
+pub async fn manage_templates(
+    cmd: TemplatesCommands,
+    config: &SmsConfig,
+) -> Result<String, String> {
     match cmd {
-        TemplatesCommands::Create { name, text } => handle_create_template(name, text).await,
+        TemplatesCommands::Create {
+            name,
+            text,
+            transliterate,
+        } => handle_create_template(name, text, transliterate).await,
         TemplatesCommands::Delete { name } => handle_delete_template(name).await,
         TemplatesCommands::Get { name } => handle_get_template(name).await,
-        TemplatesCommands::Update { name, text } => handle_update_template(name, text).await,
+        TemplatesCommands::Update {
+            name,
+            text,
+            transliterate,
+        } => handle_update_template(name, text, transliterate).await,
         TemplatesCommands::List => handle_list_templates().await,
+        TemplatesCommands::Preview {
+            name,
+            contact,
+            vars,
+        } => handle_preview_template(name, contact, vars, config.sms_api.transliterate).await,
+        TemplatesCommands::Check { max_segments } => {
+            handle_check_templates(max_segments.unwrap_or(config.templates.max_segments)).await
+        }
     }
 }
 
-async fn handle_create_template(name: String, text: String) -> Result<String, String> {
+async fn handle_create_template(
+    name: String,
+    text: String,
+    transliterate: Option<bool>,
+) -> Result<String, String> {
+    let mut template = Template::new(name, text);
+    template.transliterate = transliterate;
     repository::templates()
-        .create(Template::new(name, text))
+        .create(template)
         .await
         .map(|_| "Template created successfully".to_string())
 }
@@ -44,16 +74,108 @@ async fn handle_list_templates() -> Result<String, String> {
 
 fn render_templates_table(templates: Vec<Template>) -> String {
     let mut table = prettytable::Table::new();
-    table.add_row(row!["name", "Text"]);
+    table.add_row(row!["name", "Text", "Transliterate"]);
     for template in templates {
-        table.add_row(row![template.name, template.text]);
+        table.add_row(row![
+            template.name,
+            template.text,
+            template
+                .transliterate
+                .map(|t| t.to_string())
+                .unwrap_or_default()
+        ]);
     }
     table.to_string()
 }
 
-async fn handle_update_template(name: String, text: String) -> Result<String, String> {
-    repository::templates()
-        .update(Template::new(name, text))
+// Transliteration setting is kept unless it is given
+async fn handle_update_template(
+    name: String,
+    text: String,
+    transliterate: Option<bool>,
+) -> Result<String, String> {
+    let templates = repository::templates();
+    let current = templates
+        .get(&Template::id_from_name(&name))
+        .await?
+        .ok_or_else(|| format!("Template with name {} not found", name))?;
+    let mut template = Template::new(name, text);
+    template.transliterate = transliterate.or(current.transliterate);
+    templates
+        .update(template)
         .await
         .map(|_| "Template updated successfully".to_string())
 }
+
+async fn handle_preview_template(
+    name: String,
+    contact: TemplatePreviewContactArgs,
+    vars: Vec<(String, String)>,
+    default_transliterate: bool,
+) -> Result<String, String> {
+    let template = repository::templates()
+        .get(&Template::id_from_name(&name))
+        .await?
+        .ok_or_else(|| format!("Template with name {} not found", name))?;
+    let contact = repository::contacts()
+        .find_exactly_one_by_contact_name(&contact.contact_name, contact.index)
+        .await?;
+    let values = template_vars::recipient_values(Some(&contact), &vars);
+    let mut rendered = template_vars::render(&template.text, &values)?;
+    if template.transliterate.unwrap_or(default_transliterate) {
+        rendered = transliteration::transliterate(&rendered);
+    }
+    Ok(format!(
+        "Message to {} ({}), {} sms part(s):\n{}",
+        contact.contact_name,
+        contact.phone,
+        encoding::analyze(&rendered).segments,
+        rendered
+    ))
+}
+
+async fn handle_check_templates(max_segments: usize) -> Result<String, String> {
+    let templates = repository::templates().get_all().await?;
+    let templates_count = templates.len();
+    let mut table = prettytable::Table::new();
+    table.add_row(row!["name", "Problems"]);
+    let mut invalid = 0;
+    for template in templates {
+        let problems = check_template(&template.text, max_segments);
+        if problems.is_empty() {
+            table.add_row(row![template.name, "OK"]);
+            continue;
+        }
+        invalid += 1;
+        table.add_row(row![template.name, Fr->problems.join("\n")]);
+    }
+    Ok(format!(
+        "{}{} of {} templates have problems",
+        table, invalid, templates_count
+    ))
+}
+
+pub fn check_template(text: &str, max_segments: usize) -> Vec<String> {
+    let mut problems = vec![];
+    match template_vars::variables(text) {
+        Ok(variables) => problems.extend(
+            variables
+                .into_iter()
+                .filter(|v| {
+                    !template_vars::CONTACT_VARIABLES.contains(&v.as_str())
+                        && !template_vars::BUILTIN_VARIABLES.contains(&v.as_str())
+                })
+                .map(|v| format!("Unknown placeholder '{{{{{}}}}}', it requires --var", v)),
+        ),
+        Err(e) => problems.push(e),
+    }
+    // Placeholders are counted as written, values usually have similar length.
+    let parts = encoding::analyze(text);
+    if parts.segments > max_segments {
+        problems.push(format!(
+            "Text needs {} sms parts in {}, budget is {}",
+            parts.segments, parts.encoding, max_segments
+        ));
+    }
+    problems
+}
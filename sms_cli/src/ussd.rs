@@ -0,0 +1,64 @@
+use sms_api::SmsError;
+use sms_config::config::SmsApiConf;
+use sms_db::{balances::Balance, repository};
+
+pub async fn send_ussd(
+    code: Option<String>,
+    sms_api_config: &SmsApiConf,
+) -> Result<String, String> {
+    let balance_code = sms_api_config.balance_ussd.as_deref();
+    let code = code
+        .or_else(|| balance_code.map(str::to_string))
+        .ok_or("No USSD code given and balance_ussd is not configured")?;
+    let reply = run_ussd(&code, sms_api_config)
+        .await
+        .map_err(|e| format!("Could not send USSD, Reason: {}", e))?;
+    if balance_code != Some(code.as_str()) {
+        return Ok(reply);
+    }
+    match parse_balance(&reply) {
+        Some(amount) => {
+            store_balance(Balance::new(amount, reply.clone())).await?;
+            Ok(reply)
+        }
+        None => Ok(format!(
+            "{}\nBalance not stored, no amount found in reply",
+            reply
+        )),
+    }
+}
+
+pub async fn run_ussd(code: &str, sms_api_config: &SmsApiConf) -> Result<String, SmsError> {
+    sms_api::create_service(sms_api_config)?
+        .send_ussd(code)
+        .await
+}
+
+// Prefers number with two decimal places, so dates and numbers of packages are skipped
+pub fn parse_balance(reply: &str) -> Option<f64> {
+    let numbers: Vec<&str> = reply
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .map(|number| number.trim_matches(['.', ',']))
+        .filter(|number| !number.is_empty())
+        .collect();
+    numbers
+        .iter()
+        .find_map(|number| match number.split_once(['.', ',']) {
+            Some((units, cents))
+                if cents.len() == 2 && cents.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                format!("{}.{}", units, cents).parse().ok()
+            }
+            _ => None,
+        })
+        .or_else(|| numbers.iter().find_map(|number| number.parse().ok()))
+}
+
+async fn store_balance(balance: Balance) -> Result<(), String> {
+    let balances = repository::balances();
+    if balances.get(&balance.id).await?.is_some() {
+        balances.update(balance).await
+    } else {
+        balances.create(balance).await.map(|_| ())
+    }
+}
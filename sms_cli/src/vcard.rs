@@ -0,0 +1,176 @@
+use crate::args_parser::VCardVersion;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VCard {
+    pub first_name: String,
+    pub surname_name: String,
+    pub phone: String,
+    pub contact_name: Option<String>,
+    pub categories: Vec<String>,
+}
+
+pub fn parse(content: &str) -> Result<Vec<VCard>, String> {
+    let mut cards = vec![];
+    let mut current: Option<VCard> = None;
+    let mut phone_is_preferred = false;
+    for (line_number, line) in unfold(content).into_iter().enumerate() {
+        let (name, params, value) = split_property(&line)
+            .ok_or_else(|| format!("Invalid vCard line {}: '{}'", line_number + 1, line))?;
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some(VCard::default());
+                phone_is_preferred = false;
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") => {
+                cards.push(current.take().expect("current card exists"));
+            }
+            ("N", Some(card)) => {
+                let mut parts = split_unescaped(value, ';').into_iter();
+                card.surname_name = parts.next().unwrap_or_default();
+                card.first_name = parts.next().unwrap_or_default();
+            }
+            ("FN", Some(card)) => card.contact_name = Some(unescape(value)),
+            ("TEL", Some(card)) => {
+                let is_cell = params
+                    .iter()
+                    .any(|p| p.contains("CELL") || p.contains("PREF"));
+                if card.phone.is_empty() || (is_cell && !phone_is_preferred) {
+                    card.phone = value
+                        .trim_start_matches("tel:")
+                        .chars()
+                        .filter(|c| !c.is_whitespace() && *c != '-')
+                        .collect();
+                    phone_is_preferred = is_cell;
+                }
+            }
+            ("CATEGORIES", Some(card)) => card.categories.extend(
+                split_unescaped(value, ',')
+                    .into_iter()
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty()),
+            ),
+            ("BEGIN", Some(_)) | ("END", None) => {
+                return Err(format!(
+                    "Unexpected {} in vCard line {}",
+                    name,
+                    line_number + 1
+                ))
+            }
+            _ => {}
+        }
+    }
+    if current.is_some() {
+        return Err("vCard is missing END:VCARD".to_string());
+    }
+    for card in &mut cards {
+        if card.first_name.is_empty() && card.surname_name.is_empty() {
+            let full_name = card.contact_name.clone().unwrap_or_default();
+            let (first_name, surname_name) = full_name.split_once(' ').unwrap_or((&full_name, ""));
+            card.first_name = first_name.to_string();
+            card.surname_name = surname_name.to_string();
+        }
+    }
+    Ok(cards)
+}
+
+pub fn write(cards: &[VCard], version: VCardVersion) -> String {
+    let mut output = String::new();
+    for card in cards {
+        let contact_name = card
+            .contact_name
+            .clone()
+            .unwrap_or_else(|| format!("{} {}", card.first_name, card.surname_name));
+        output.push_str("BEGIN:VCARD\r\n");
+        match version {
+            VCardVersion::V3 => output.push_str("VERSION:3.0\r\n"),
+            VCardVersion::V4 => output.push_str("VERSION:4.0\r\n"),
+        }
+        output.push_str(&format!(
+            "N:{};{};;;\r\n",
+            escape(&card.surname_name),
+            escape(&card.first_name)
+        ));
+        output.push_str(&format!("FN:{}\r\n", escape(&contact_name)));
+        match version {
+            VCardVersion::V3 => output.push_str(&format!("TEL;TYPE=CELL:{}\r\n", card.phone)),
+            VCardVersion::V4 => {
+                output.push_str(&format!("TEL;TYPE=cell;VALUE=uri:tel:{}\r\n", card.phone))
+            }
+        }
+        if !card.categories.is_empty() {
+            let categories: Vec<String> = card.categories.iter().map(|c| escape(c)).collect();
+            output.push_str(&format!("CATEGORIES:{}\r\n", categories.join(",")));
+        }
+        output.push_str("END:VCARD\r\n");
+    }
+    output
+}
+
+// Long lines are folded by inserting line break followed by space or tab.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// Splits `item1.TEL;TYPE=CELL:123` into ("TEL", ["TYPE=CELL"], "123")
+fn split_property(line: &str) -> Option<(String, Vec<String>, &str)> {
+    let (head, value) = line.split_once(':')?;
+    let mut head = head.split(';');
+    let name = head.next()?;
+    let name = name.rsplit('.').next().unwrap_or(name).to_uppercase();
+    let params = head.map(|p| p.to_uppercase()).collect();
+    Some((name, params, value))
+}
+
+fn split_unescaped(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    current.push('\\');
+                    current.push(escaped);
+                }
+            }
+            c if c == separator => parts.push(unescape(&std::mem::take(&mut current))),
+            c => current.push(c),
+        }
+    }
+    parts.push(unescape(&current));
+    parts
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(escaped) => result.push(escaped),
+            None => {}
+        }
+    }
+    result
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use sms_api::sms_mock_api;
+use sms_cli::sms_send;
+use sms_config::config::{RetryConf, SmsApiConf};
+use sms_db::{
+    campaigns::{Campaign, CampaignRecipient, RecipientState},
+    repository,
+    sent_messages::{SentMessageFilter, SentMessageStatus},
+};
+
+fn campaign() -> Campaign {
+    Campaign::new(
+        None,
+        None,
+        vec![
+            CampaignRecipient::new("111".to_string(), None, "Hello Anna".to_string()),
+            CampaignRecipient::new("222".to_string(), None, "Hello".to_string()),
+            CampaignRecipient::new("333".to_string(), None, "Hello".to_string()),
+        ],
+    )
+}
+
+#[test]
+fn should_group_recipients_in_state_by_text() {
+    // given
+    let mut campaign = campaign();
+    campaign.set_state("222", "Hello", RecipientState::Sent, None);
+
+    // when
+    let messages = campaign.messages_in_state(RecipientState::Pending);
+
+    // then
+    let phones: Vec<(String, Vec<String>)> = messages
+        .into_iter()
+        .map(|(text, recipients)| (text, recipients.into_iter().map(|r| r.phone).collect()))
+        .collect();
+    assert_eq!(
+        phones,
+        vec![
+            ("Hello Anna".to_string(), vec!["111".to_string()]),
+            ("Hello".to_string(), vec!["333".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn should_not_change_state_of_recipient_message_was_sent_to() {
+    // given
+    let mut campaign = campaign();
+    campaign.set_state("111", "Hello Anna", RecipientState::Sent, None);
+
+    // when
+    campaign.set_state(
+        "111",
+        "Hello Anna",
+        RecipientState::Failed,
+        Some("Modem error".to_string()),
+    );
+    campaign.set_state(
+        "333",
+        "Hello",
+        RecipientState::Failed,
+        Some("Modem error".to_string()),
+    );
+
+    // then
+    assert_eq!(campaign.recipients[0].state, RecipientState::Sent);
+    assert_eq!(campaign.recipients[0].error, None);
+    assert_eq!(campaign.recipients[2].state, RecipientState::Failed);
+    assert_eq!(
+        campaign.recipients[2].error,
+        Some("Modem error".to_string())
+    );
+    assert_eq!(campaign.count(RecipientState::Pending), 1);
+}
+
+// Recipient 111 got the message, 222 is pending and sending to 333 failed
+async fn stored_campaign() -> Campaign {
+    let mut campaign = Campaign::new(
+        None,
+        None,
+        vec![
+            CampaignRecipient::new("111".to_string(), None, "Hello".to_string()),
+            CampaignRecipient::new("222".to_string(), None, "Hello".to_string()),
+            CampaignRecipient::new("333".to_string(), None, "Hello".to_string()),
+        ],
+    );
+    campaign.set_state("111", "Hello", RecipientState::Sent, None);
+    campaign.set_state(
+        "333",
+        "Hello",
+        RecipientState::Failed,
+        Some("Modem error".to_string()),
+    );
+    repository::campaigns().create(campaign).await.unwrap()
+}
+
+async fn stored_states(campaign: &Campaign) -> Vec<RecipientState> {
+    repository::campaigns()
+        .get(&campaign.id)
+        .await
+        .unwrap()
+        .expect("stored campaign")
+        .recipients
+        .into_iter()
+        .map(|r| r.state)
+        .collect()
+}
+
+#[test]
+fn should_resume_campaign_only_for_pending_recipients() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let campaign = stored_campaign().await;
+
+        // when
+        let result = sms_send::resume_campaign(
+            &campaign.id.id.to_raw(),
+            RecipientState::Pending,
+            &common::at_serial_config(&modem.device),
+        )
+        .await;
+
+        // then
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(
+            modem.sent_sms(),
+            vec![("222".to_string(), "Hello".to_string())]
+        );
+        assert_eq!(
+            stored_states(&campaign).await,
+            vec![
+                RecipientState::Sent,
+                RecipientState::Sent,
+                RecipientState::Failed
+            ]
+        );
+    });
+}
+
+#[test]
+fn should_retry_campaign_only_for_failed_recipients() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let campaign = stored_campaign().await;
+
+        // when
+        let result = sms_send::resume_campaign(
+            &campaign.id.id.to_raw(),
+            RecipientState::Failed,
+            &common::at_serial_config(&modem.device),
+        )
+        .await;
+
+        // then
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(
+            modem.sent_sms(),
+            vec![("333".to_string(), "Hello".to_string())]
+        );
+        assert_eq!(
+            stored_states(&campaign).await,
+            vec![
+                RecipientState::Sent,
+                RecipientState::Pending,
+                RecipientState::Sent
+            ]
+        );
+    });
+}
+
+#[test]
+fn should_store_failure_of_resumed_recipient() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_rejecting_sms_to("222");
+        let campaign = stored_campaign().await;
+
+        // when
+        let result = sms_send::resume_campaign(
+            &campaign.id.id.to_raw(),
+            RecipientState::Failed,
+            &common::at_serial_config(&modem.device),
+        )
+        .await;
+        let retried = sms_send::resume_campaign(
+            &campaign.id.id.to_raw(),
+            RecipientState::Pending,
+            &common::at_serial_config(&modem.device),
+        )
+        .await;
+
+        // then
+        assert!(result.is_ok(), "{:?}", result);
+        assert!(retried.is_err(), "{:?}", retried);
+        assert_eq!(
+            stored_states(&campaign).await,
+            vec![
+                RecipientState::Sent,
+                RecipientState::Failed,
+                RecipientState::Sent
+            ]
+        );
+    });
+}
+
+#[test]
+fn should_not_record_history_of_recipients_skipped_after_modem_failure() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_busy_once();
+        let campaign = repository::campaigns()
+            .create(Campaign::new(
+                None,
+                None,
+                vec![
+                    CampaignRecipient::new("111".to_string(), None, "Hello".to_string()),
+                    CampaignRecipient::new("222".to_string(), None, "Hello".to_string()),
+                ],
+            ))
+            .await
+            .unwrap();
+        let config = SmsApiConf {
+            retry: RetryConf {
+                max_attempts: 1,
+                ..RetryConf::default()
+            },
+            ..common::at_serial_config(&modem.device)
+        };
+
+        // when
+        let result =
+            sms_send::resume_campaign(&campaign.id.id.to_raw(), RecipientState::Pending, &config)
+                .await;
+
+        // then
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(
+            stored_states(&campaign).await,
+            vec![RecipientState::Failed, RecipientState::Pending]
+        );
+        let history = repository::sent_messages()
+            .find_history(SentMessageFilter::default())
+            .await
+            .unwrap();
+        let history: Vec<(String, SentMessageStatus)> = history
+            .into_iter()
+            .map(|message| (message.phone, message.status))
+            .collect();
+        assert_eq!(
+            history,
+            vec![("111".to_string(), SentMessageStatus::Failed)]
+        );
+    });
+}
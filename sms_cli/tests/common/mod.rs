@@ -0,0 +1,52 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use sms_config::config::{SmsApiConf, SmsApiProvider, SmsConfig, SmsDbConfig};
+use sms_db::repository;
+
+// Not every test sends through fake modem
+#[allow(dead_code)]
+pub fn at_serial_config(device: &str) -> SmsApiConf {
+    SmsApiConf {
+        provider: SmsApiProvider::AtSerial {
+            device: device.to_string(),
+            baud_rate: 115200,
+            pin: None,
+        },
+        ..SmsApiConf::default()
+    }
+}
+
+static DB_LOCK: Mutex<()> = Mutex::new(());
+static DB_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// Repository is global, so tests using it run one at a time, each on empty storage
+pub fn with_db(test: impl Future<Output = ()>) {
+    let _lock = DB_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let storage = std::env::temp_dir().join(format!(
+        "sms_cli_test_{}_{}",
+        std::process::id(),
+        DB_COUNT.fetch_add(1, Ordering::SeqCst)
+    ));
+    let config = SmsConfig {
+        db: SmsDbConfig {
+            storage_path: storage.to_string_lossy().to_string(),
+        },
+        ..SmsConfig::default()
+    };
+    // Left open by previous test if it panicked
+    repository::close();
+    let runtime = tokio::runtime::Runtime::new().expect("test runtime");
+    runtime.block_on(async {
+        repository::init(&config).await.expect("test db");
+        test.await;
+        repository::close();
+    });
+    drop(runtime);
+    let _ = std::fs::remove_dir_all(storage);
+}
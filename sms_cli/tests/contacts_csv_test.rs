@@ -0,0 +1,30 @@
+use sms_cli::{args_parser::CsvColumnsArgs, contacts_csv};
+
+#[test]
+fn should_read_contacts_using_custom_header_mapping() {
+    // given
+    let source_csv = std::env::temp_dir().join("sms_cli_contacts_csv_test.csv");
+    std::fs::write(
+        &source_csv,
+        "Name;Surname;Mobile;Nick\nAnna;Nowak;123456789;\nJan; Kowalski ;987654321;Johnny\n",
+    )
+    .expect("write test csv");
+    let columns = CsvColumnsArgs {
+        first_name_column: "Name".to_string(),
+        surname_column: "Surname".to_string(),
+        phone_column: "Mobile".to_string(),
+        contact_name_column: "Nick".to_string(),
+        delimiter: ';',
+    };
+
+    // when
+    let rows = contacts_csv::read_contacts(&source_csv, &columns).expect("read contacts");
+
+    // then
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].first_name, "Anna");
+    assert_eq!(rows[0].contact_name, None);
+    assert_eq!(rows[1].surname_name, "Kowalski");
+    assert_eq!(rows[1].phone, "987654321");
+    assert_eq!(rows[1].contact_name, Some("Johnny".to_string()));
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use chrono::{Duration, Local};
+use clap::Parser;
+use sms_api::sms_mock_api;
+use sms_cli::{args_parser::Cli, daemon, local_time};
+use sms_db::{
+    groups::Group,
+    recurring_schedules::{RecurringSchedule, ScheduleSpec},
+    repository,
+    scheduled_jobs::{JobMessage, JobStatus, JobTarget, ScheduledJob},
+    templates::Template,
+};
+
+fn job(phone: &str, minutes_from_now: i64) -> ScheduledJob {
+    ScheduledJob::new(
+        JobTarget::Number(phone.to_string()),
+        JobMessage::Plain("Hello world".to_string()),
+        vec![],
+        local_time::to_datetime(Local::now() + Duration::minutes(minutes_from_now)),
+    )
+}
+
+async fn job_status(job: &ScheduledJob) -> JobStatus {
+    repository::scheduled_jobs()
+        .get(&job.id)
+        .await
+        .unwrap()
+        .expect("stored job")
+        .status
+}
+
+#[test]
+fn should_reject_zero_daemon_interval() {
+    // when
+    let result = Cli::try_parse_from(["sms", "daemon", "--interval", "0"]);
+
+    // then
+    assert!(result.is_err());
+}
+
+#[test]
+fn should_find_only_scheduled_jobs_that_are_due() {
+    common::with_db(async {
+        // given
+        let jobs = repository::scheduled_jobs();
+        let due = jobs.create(job("111", -1)).await.unwrap();
+        jobs.create(job("222", 60)).await.unwrap();
+        let cancelled = jobs.create(job("333", -1)).await.unwrap();
+        jobs.change_status(
+            &cancelled.id,
+            JobStatus::Scheduled,
+            JobStatus::Cancelled,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // when
+        let found = jobs.find_due_jobs().await.unwrap();
+
+        // then
+        let ids: Vec<_> = found.into_iter().map(|job| job.id).collect();
+        assert_eq!(ids, vec![due.id]);
+    });
+}
+
+#[test]
+fn should_send_due_job_on_tick() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let jobs = repository::scheduled_jobs();
+        let due = jobs.create(job("111222333", -1)).await.unwrap();
+        let later = jobs.create(job("444555666", 60)).await.unwrap();
+
+        // when
+        daemon::run_tick(&common::at_serial_config(&modem.device)).await;
+
+        // then
+        assert_eq!(job_status(&due).await, JobStatus::Sent);
+        assert_eq!(job_status(&later).await, JobStatus::Scheduled);
+        assert_eq!(
+            modem.sent_sms(),
+            vec![("111222333".to_string(), "Hello world".to_string())]
+        );
+    });
+}
+
+#[test]
+fn should_fail_interrupted_job_on_tick_without_sending() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let jobs = repository::scheduled_jobs();
+        let interrupted = jobs.create(job("111222333", -1)).await.unwrap();
+        jobs.change_status(
+            &interrupted.id,
+            JobStatus::Scheduled,
+            JobStatus::Dispatching,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // when
+        daemon::run_tick(&common::at_serial_config(&modem.device)).await;
+
+        // then
+        assert_eq!(job_status(&interrupted).await, JobStatus::Failed);
+        assert!(modem.sent_sms().is_empty());
+    });
+}
+
+#[test]
+fn should_pause_broken_schedule_and_run_the_others_on_tick() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let schedules = repository::recurring_schedules();
+        let schedule = |name: &str, spec: &str, minutes_ago: i64| {
+            RecurringSchedule::new(
+                name.to_string(),
+                ScheduleSpec::Cron(spec.to_string()),
+                Template::id_from_name("greeting"),
+                Group::id_from_name("friends"),
+                local_time::to_datetime(Local::now() - Duration::minutes(minutes_ago)),
+            )
+        };
+        // Broken schedule is due first
+        let broken = schedules
+            .create(schedule("broken", "every day", 2))
+            .await
+            .unwrap();
+        let daily = schedules
+            .create(schedule("daily", "0 9 * * *", 1))
+            .await
+            .unwrap();
+
+        // when
+        daemon::run_tick(&common::at_serial_config(&modem.device)).await;
+
+        // then
+        let broken = schedules
+            .get(&broken.id)
+            .await
+            .unwrap()
+            .expect("stored schedule");
+        assert!(broken.paused);
+        assert!(broken.error.is_some());
+        let daily = schedules
+            .get(&daily.id)
+            .await
+            .unwrap()
+            .expect("stored schedule");
+        assert!(daily.last_run_at.is_some());
+    });
+}
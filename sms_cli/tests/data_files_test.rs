@@ -0,0 +1,46 @@
+use sms_cli::{
+    args_parser::FileFormat,
+    data_files::{self, GroupMemberRow, GroupRow},
+};
+
+#[test]
+fn should_round_trip_groups_through_csv_and_json() {
+    for format in [FileFormat::Csv, FileFormat::Json] {
+        // given
+        let path = std::env::temp_dir().join(format!(
+            "sms_cli_groups_test.{}",
+            data_files::extension(format)
+        ));
+        let groups = vec![
+            GroupRow {
+                name: "family".to_string(),
+                members: vec![
+                    GroupMemberRow {
+                        contact_name: "Anna Nowak".to_string(),
+                        phone: "123456789".to_string(),
+                    },
+                    GroupMemberRow {
+                        contact_name: "Jan Kowalski".to_string(),
+                        phone: "987654321".to_string(),
+                    },
+                ],
+            },
+            GroupRow {
+                name: "empty".to_string(),
+                members: vec![],
+            },
+        ];
+
+        // when
+        data_files::write_groups(&path, format, &groups).expect("write groups");
+        let read_groups = data_files::read_groups(&path).expect("read groups");
+
+        // then
+        assert_eq!(read_groups.len(), 2);
+        assert_eq!(read_groups[0].name, "family");
+        assert_eq!(read_groups[0].members.len(), 2);
+        assert_eq!(read_groups[0].members[1].phone, "987654321");
+        assert_eq!(read_groups[1].name, "empty");
+        assert!(read_groups[1].members.is_empty());
+    }
+}
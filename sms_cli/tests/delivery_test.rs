@@ -0,0 +1,106 @@
+use chrono::NaiveDate;
+use sms_api::DeliveryReport;
+use sms_cli::delivery::apply_reports;
+use sms_db::sent_messages::{DeliveryStatus, SentMessage};
+
+fn awaiting_message(phone: &str, text: &str) -> SentMessage {
+    let mut message = SentMessage::new(
+        phone.to_string(),
+        None,
+        None,
+        text.to_string(),
+        None,
+        "AtSerial".to_string(),
+    );
+    message.mark_sent();
+    message.delivery = Some(DeliveryStatus::Pending);
+    message
+}
+
+fn report(id: i64, phone: &str, status: sms_api::DeliveryStatus) -> DeliveryReport {
+    DeliveryReport {
+        id,
+        phone: phone.to_string(),
+        status,
+        time: NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(10, 0, 5)
+            .unwrap(),
+        provider: None,
+        reference: None,
+    }
+}
+
+#[test]
+fn should_match_reports_to_oldest_message_of_same_phone() {
+    // given
+    let mut awaiting = vec![
+        awaiting_message("123456789", "First"),
+        awaiting_message("987654321", "Other"),
+        awaiting_message("123456789", "Second"),
+    ];
+    let reports = vec![
+        report(1, "+48123456789", sms_api::DeliveryStatus::Delivered),
+        report(2, "+48987654321", sms_api::DeliveryStatus::Pending),
+        report(3, "+48123456789", sms_api::DeliveryStatus::Failed),
+    ];
+
+    // when
+    let updated = apply_reports(&mut awaiting, &reports);
+
+    // then
+    let updated: Vec<(usize, &str, Option<DeliveryStatus>, bool)> = updated
+        .iter()
+        .map(|(i, m)| (*i, m.text.as_str(), m.delivery, m.delivered_at.is_some()))
+        .collect();
+    assert_eq!(
+        updated,
+        vec![
+            (0, "First", Some(DeliveryStatus::Delivered), true),
+            (2, "Second", Some(DeliveryStatus::Failed), false),
+        ]
+    );
+    assert_eq!(awaiting.len(), 1);
+    assert_eq!(awaiting[0].text, "Other");
+}
+
+#[test]
+fn should_ignore_reports_of_unknown_messages() {
+    // given
+    let mut awaiting = vec![awaiting_message("123456789", "First")];
+    let reports = vec![report(
+        1,
+        "+48555666777",
+        sms_api::DeliveryStatus::Delivered,
+    )];
+
+    // when
+    let updated = apply_reports(&mut awaiting, &reports);
+
+    // then
+    assert!(updated.is_empty());
+    assert_eq!(awaiting.len(), 1);
+}
+
+#[test]
+fn should_match_report_to_message_with_same_reference() {
+    // given
+    let mut first = awaiting_message("123456789", "First");
+    first.reference = Some(5);
+    let mut second = awaiting_message("123456789", "Second");
+    second.reference = Some(6);
+    let mut awaiting = vec![first, second];
+    let reports = vec![DeliveryReport {
+        reference: Some(6),
+        ..report(1, "+48123456789", sms_api::DeliveryStatus::Delivered)
+    }];
+
+    // when
+    let updated = apply_reports(&mut awaiting, &reports);
+
+    // then
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated[0].1.text, "Second");
+    assert_eq!(awaiting.len(), 1);
+    assert_eq!(awaiting[0].text, "First");
+}
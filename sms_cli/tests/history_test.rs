@@ -0,0 +1,135 @@
+mod common;
+
+use sms_api::sms_mock_api;
+use sms_cli::{
+    args_parser::{HistoryArgs, SendSmsArgs, SmsMessageArgs, SmsTargetArgs},
+    history, sms_send,
+};
+use sms_db::{
+    contacts::Contact,
+    repository,
+    sent_messages::{SentMessageFilter, SentMessageStatus},
+};
+
+fn send_args(number: &str) -> SendSmsArgs {
+    SendSmsArgs {
+        to: SmsTargetArgs {
+            number: Some(number.to_string()),
+            contact_name: None,
+            group_name: None,
+        },
+        message: SmsMessageArgs {
+            plain: Some("Hello world".to_string()),
+            template: None,
+        },
+        at: None,
+        vars: vec![],
+        transliterate: None,
+        wait_delivery: false,
+    }
+}
+
+#[test]
+fn should_record_sent_message_with_contact_of_recipient() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let anna = repository::contacts()
+            .create(Contact::new(
+                "Anna".to_string(),
+                "Nowak".to_string(),
+                "111222333".to_string(),
+                None,
+            ))
+            .await
+            .unwrap();
+
+        // when
+        sms_send::send_sms(
+            send_args("111222333"),
+            &common::at_serial_config(&modem.device),
+        )
+        .await
+        .unwrap();
+
+        // then
+        let history = repository::sent_messages()
+            .find_history(SentMessageFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, SentMessageStatus::Sent);
+        assert_eq!(history[0].contact, Some(anna.id));
+        assert_eq!(history[0].text, "Hello world");
+        assert_eq!(history[0].provider, format!("AtSerial {}", modem.device));
+        assert!(history[0].sent_at.is_some());
+    });
+}
+
+#[test]
+fn should_record_reason_of_failed_send() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_rejecting_sms_to("111222333");
+
+        // when
+        let result = sms_send::send_sms(
+            send_args("111222333"),
+            &common::at_serial_config(&modem.device),
+        )
+        .await;
+
+        // then
+        assert!(result.is_err());
+        let failed = repository::sent_messages()
+            .find_history(SentMessageFilter {
+                status: Some(SentMessageStatus::Failed),
+                ..SentMessageFilter::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].phone, "111222333");
+        assert!(failed[0].error.is_some());
+        assert!(failed[0].sent_at.is_none());
+    });
+}
+
+#[test]
+fn should_show_history_of_contact() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let config = common::at_serial_config(&modem.device);
+        repository::contacts()
+            .create(Contact::new(
+                "Anna".to_string(),
+                "Nowak".to_string(),
+                "111222333".to_string(),
+                None,
+            ))
+            .await
+            .unwrap();
+        sms_send::send_sms(send_args("111222333"), &config)
+            .await
+            .unwrap();
+        sms_send::send_sms(send_args("444555666"), &config)
+            .await
+            .unwrap();
+
+        // when
+        let table = history::show_history(HistoryArgs {
+            contact_name: Some("Anna Nowak".to_string()),
+            group_name: None,
+            from: None,
+            to: None,
+            status: None,
+        })
+        .await
+        .unwrap();
+
+        // then
+        assert!(table.contains("Anna Nowak (111222333)"), "{}", table);
+        assert!(!table.contains("444555666"), "{}", table);
+    });
+}
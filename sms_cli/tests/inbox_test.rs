@@ -0,0 +1,39 @@
+use sms_api::sms_mock_api::{self, mockito};
+use sms_config::config::SmsApiConf;
+
+#[tokio::test]
+async fn should_read_only_received_sms_from_inbox() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::reading_inbox_is_successful(&mut server).await;
+    let sms_api_config = SmsApiConf {
+        provider: sms_config::config::SmsApiProvider::Alcatel {
+            url: server.url(),
+            retry_count: 3,
+            retry_delay: 50,
+            username: None,
+            password: None,
+            pin: None,
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    };
+
+    // when
+    let inbox = sms_cli::inbox::read_inbox(&sms_api_config)
+        .await
+        .expect("read_inbox_successfully")
+        .messages;
+
+    // then
+    assert_eq!(inbox.len(), 1);
+    assert_eq!(inbox[0].phone, "123456789");
+    assert_eq!(inbox[0].text, "See you tomorrow");
+    assert!(inbox[0].unread);
+    mock_handler.assert_called();
+}
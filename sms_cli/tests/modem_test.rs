@@ -0,0 +1,148 @@
+use sms_api::sms_mock_api::{self, mockito};
+use sms_config::config::{RateLimitConf, SmsApiConf};
+use sms_db::balances::Balance;
+
+#[tokio::test]
+async fn should_show_modem_status_as_json() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_status_is_successful(&mut server).await;
+    let sms_api_config = SmsApiConf {
+        provider: sms_config::config::SmsApiProvider::Alcatel {
+            url: server.url(),
+            retry_count: 3,
+            retry_delay: 50,
+            username: None,
+            password: None,
+            pin: None,
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    };
+
+    // when
+    let output = sms_cli::modem::show_status(&sms_api_config, None, vec![], true)
+        .await
+        .expect("show_status_successfully");
+
+    // then
+    let status: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(status["operator"], "Orange PL");
+    assert_eq!(status["signal_bars"], 3);
+    assert_eq!(status["roaming"], false);
+    assert_eq!(status["sms_storage"]["used"], 12);
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_unlock_sim_with_pin_from_config() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_pin_required(&mut server).await;
+    let sms_api_config = SmsApiConf {
+        provider: sms_config::config::SmsApiProvider::Alcatel {
+            url: server.url(),
+            retry_count: 3,
+            retry_delay: 50,
+            username: None,
+            password: None,
+            pin: Some(sms_mock_api::SIM_PIN.to_string()),
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    };
+
+    // when
+    let output = sms_cli::modem::unlock(&sms_api_config, None).await;
+
+    // then
+    assert_eq!(output, Ok("SIM unlocked".to_string()));
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_show_stored_balance_in_modem_status() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_status_is_successful(&mut server).await;
+    let sms_api_config = SmsApiConf {
+        provider: sms_config::config::SmsApiProvider::Alcatel {
+            url: server.url(),
+            retry_count: 3,
+            retry_delay: 50,
+            username: None,
+            password: None,
+            pin: None,
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    };
+    let balance = Balance::new(12.5, sms_mock_api::USSD_BALANCE_REPLY.to_string());
+
+    // when
+    let output = sms_cli::modem::show_status(&sms_api_config, Some(&balance), vec![], true)
+        .await
+        .expect("show_status_successfully");
+
+    // then
+    let status: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(status["balance"]["amount"], 12.5);
+    mock_handler.assert_called();
+}
+
+#[tokio::test]
+async fn should_show_rate_budget_in_modem_status() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_status_is_successful(&mut server).await;
+    let sms_api_config = SmsApiConf {
+        provider: sms_config::config::SmsApiProvider::Alcatel {
+            url: server.url(),
+            retry_count: 3,
+            retry_delay: 50,
+            username: None,
+            password: None,
+            pin: None,
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: RateLimitConf {
+            per_minute: Some(5),
+            daily_cap: Some(500),
+            ..RateLimitConf::default()
+        },
+        backup_providers: Default::default(),
+    };
+    let sent = vec![(chrono::Utc::now(), 2)];
+
+    // when
+    let output = sms_cli::modem::show_status(&sms_api_config, None, sent, true)
+        .await
+        .expect("show_status_successfully");
+
+    // then
+    let status: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(status["rate_budget"]["per_minute"]["remaining"], 3);
+    assert_eq!(status["rate_budget"]["today"]["remaining"], 498);
+    assert_eq!(status["rate_budget"]["today"]["limit"], 500);
+    assert_eq!(status["rate_budget"]["per_hour"], serde_json::Value::Null);
+    mock_handler.assert_called();
+}
@@ -0,0 +1,113 @@
+use chrono::{Local, TimeZone};
+use sms_cli::recurrence;
+use sms_db::recurring_schedules::ScheduleSpec;
+
+#[test]
+fn should_preview_next_fire_times_of_cron_expression() {
+    // given
+    let spec = ScheduleSpec::Cron("0 9 * * Mon".to_string());
+    let after = Local.with_ymd_and_hms(2026, 11, 4, 12, 0, 0).unwrap();
+
+    // when
+    let fire_times = recurrence::next_fire_times(&spec, after, 3).expect("valid cron");
+
+    // then
+    let expected: Vec<_> = [9, 16, 23]
+        .into_iter()
+        .map(|day| Local.with_ymd_and_hms(2026, 11, day, 9, 0, 0).unwrap())
+        .collect();
+    assert_eq!(fire_times, expected);
+}
+
+#[test]
+fn should_fire_rrule_same_as_equivalent_cron() {
+    // given
+    let rrule = ScheduleSpec::RRule("FREQ=WEEKLY;BYDAY=MO,FR;BYHOUR=9;BYMINUTE=30".to_string());
+    let cron = ScheduleSpec::Cron("30 9 * * Mon,Fri".to_string());
+    let after = Local.with_ymd_and_hms(2026, 11, 4, 12, 0, 0).unwrap();
+
+    // when
+    let rrule_times = recurrence::next_fire_times(&rrule, after, 5).expect("valid rrule");
+    let cron_times = recurrence::next_fire_times(&cron, after, 5).expect("valid cron");
+
+    // then
+    assert_eq!(rrule_times, cron_times);
+}
+
+#[test]
+fn should_reject_invalid_expressions() {
+    assert!(recurrence::parse_schedule(&ScheduleSpec::Cron("0 9 * *".to_string())).is_err());
+    assert!(recurrence::parse_schedule(&ScheduleSpec::Cron("0 9 * * 8".to_string())).is_err());
+    assert!(recurrence::parse_schedule(&ScheduleSpec::RRule("FREQ=WEEKLY".to_string())).is_err());
+    assert!(
+        recurrence::parse_schedule(&ScheduleSpec::RRule("FREQ=DAILY;INTERVAL=2".to_string()))
+            .is_err()
+    );
+}
+
+#[test]
+fn should_count_numeric_cron_weekdays_from_sunday_as_zero() {
+    // given
+    let after = Local.with_ymd_and_hms(2026, 11, 4, 12, 0, 0).unwrap();
+    let equivalents = [
+        ("0 9 * * 1-5", "0 9 * * Mon-Fri"),
+        ("0 9 * * 0", "0 9 * * Sun"),
+        ("0 9 * * 7", "0 9 * * Sun"),
+        ("0 9 * * 5,6,0", "0 9 * * Fri,Sat,Sun"),
+        ("0 9 * * */2", "0 9 * * Sun,Tue,Thu,Sat"),
+    ];
+
+    for (numeric, named) in equivalents {
+        // when
+        let numeric_times =
+            recurrence::next_fire_times(&ScheduleSpec::Cron(numeric.to_string()), after, 10)
+                .expect("valid cron");
+        let named_times =
+            recurrence::next_fire_times(&ScheduleSpec::Cron(named.to_string()), after, 10)
+                .expect("valid cron");
+
+        // then
+        assert_eq!(numeric_times, named_times, "{}", numeric);
+    }
+}
+
+#[test]
+fn should_fire_yearly_rrule_on_its_date() {
+    // given
+    let spec = ScheduleSpec::RRule("FREQ=YEARLY;BYMONTH=3;BYMONTHDAY=15;BYHOUR=9".to_string());
+    let after = Local.with_ymd_and_hms(2026, 11, 4, 12, 0, 0).unwrap();
+
+    // when
+    let fire_times = recurrence::next_fire_times(&spec, after, 2).expect("valid rrule");
+
+    // then
+    let expected: Vec<_> = [2027, 2028]
+        .into_iter()
+        .map(|year| Local.with_ymd_and_hms(year, 3, 15, 9, 0, 0).unwrap())
+        .collect();
+    assert_eq!(fire_times, expected);
+}
+
+#[test]
+fn should_fire_yearly_rrule_with_byday_in_every_month() {
+    // given
+    let rrule = ScheduleSpec::RRule("FREQ=YEARLY;BYDAY=MO;BYHOUR=9".to_string());
+    let cron = ScheduleSpec::Cron("0 9 * * Mon".to_string());
+    let after = Local.with_ymd_and_hms(2026, 11, 4, 12, 0, 0).unwrap();
+
+    // when
+    let rrule_times = recurrence::next_fire_times(&rrule, after, 10).expect("valid rrule");
+    let cron_times = recurrence::next_fire_times(&cron, after, 10).expect("valid cron");
+
+    // then
+    assert_eq!(rrule_times, cron_times);
+}
+
+#[test]
+fn should_reject_yearly_rrule_without_date() {
+    assert!(recurrence::parse_schedule(&ScheduleSpec::RRule("FREQ=YEARLY".to_string())).is_err());
+    assert!(
+        recurrence::parse_schedule(&ScheduleSpec::RRule("FREQ=YEARLY;BYMONTH=3".to_string()))
+            .is_err()
+    );
+}
@@ -0,0 +1,223 @@
+mod common;
+
+use sms_cli::{
+    args_parser::{CsvColumnsArgs, ImportCommads},
+    contacts_csv::ContactRow,
+    replace,
+};
+use sms_db::{contacts::Contact, groups::Group, repository};
+
+fn contact(first_name: &str, phone: &str) -> Contact {
+    Contact::new(
+        first_name.to_string(),
+        "Nowak".to_string(),
+        phone.to_string(),
+        None,
+    )
+}
+
+async fn group_members(group: &Group) -> Vec<String> {
+    let mut members: Vec<String> = repository::groups()
+        .find_group_details(&group.id)
+        .await
+        .unwrap()
+        .expect("stored group")
+        .contacts
+        .into_iter()
+        .map(|c| c.first_name)
+        .collect();
+    members.sort();
+    members
+}
+
+#[test]
+fn should_keep_contact_of_other_group_when_replacing_contacts() {
+    common::with_db(async {
+        // given
+        let groups = repository::groups();
+        let family = groups
+            .create(Group::new("family".to_string()))
+            .await
+            .unwrap();
+        let friends = groups
+            .create(Group::new("friends".to_string()))
+            .await
+            .unwrap();
+        let anna = repository::contacts()
+            .create(contact("Anna", "111"))
+            .await
+            .unwrap();
+        let bob = repository::contacts()
+            .create(contact("Bob", "222"))
+            .await
+            .unwrap();
+        groups.assign_contact(&anna.id, &family.id).await.unwrap();
+        groups.assign_contact(&anna.id, &friends.id).await.unwrap();
+        groups.assign_contact(&bob.id, &family.id).await.unwrap();
+
+        // when
+        groups
+            .replace_contacts(
+                &family.id,
+                vec![anna.id.clone(), bob.id.clone()],
+                vec![contact("Carol", "333")],
+            )
+            .await
+            .unwrap();
+
+        // then
+        assert_eq!(group_members(&family).await, vec!["Carol"]);
+        assert_eq!(group_members(&friends).await, vec!["Anna"]);
+        let mut contacts: Vec<String> = repository::contacts()
+            .get_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.first_name)
+            .collect();
+        contacts.sort();
+        assert_eq!(contacts, vec!["Anna", "Carol"]);
+    });
+}
+
+fn row(first_name: &str, phone: &str, contact_name: Option<&str>) -> ContactRow {
+    ContactRow {
+        first_name: first_name.to_string(),
+        surname_name: "Nowak".to_string(),
+        phone: phone.to_string(),
+        contact_name: contact_name.map(str::to_string),
+    }
+}
+
+#[test]
+fn should_match_contact_by_phone_before_contact_name() {
+    // given
+    let anna = contact("Anna", "111");
+    let bob = contact("Bob", "222");
+    let bob_id = bob.id.clone();
+    let mut current = vec![Some(anna), Some(bob)];
+
+    // when
+    let matched =
+        replace::take_matching_contact(&mut current, &row("Bob", "222", Some("Anna Nowak")));
+
+    // then
+    assert_eq!(matched.map(|c| c.id), Some(bob_id));
+    assert!(current[1].is_none());
+}
+
+#[test]
+fn should_match_contact_by_contact_name_when_phone_changed() {
+    // given
+    let anna = contact("Anna", "111");
+    let anna_id = anna.id.clone();
+    let mut current = vec![Some(anna)];
+
+    // when
+    let matched =
+        replace::take_matching_contact(&mut current, &row("Anna", "999", Some("Anna Nowak")));
+    let matched_again =
+        replace::take_matching_contact(&mut current, &row("Anna", "999", Some("Anna Nowak")));
+
+    // then
+    assert_eq!(matched.map(|c| c.id), Some(anna_id));
+    assert!(matched_again.is_none());
+}
+
+#[test]
+fn should_not_match_new_contact() {
+    // given
+    let mut current = vec![Some(contact("Anna", "111"))];
+
+    // when
+    let matched = replace::take_matching_contact(&mut current, &row("Carol", "333", None));
+
+    // then
+    assert!(matched.is_none());
+    assert!(current[0].is_some());
+}
+
+#[test]
+fn should_describe_changed_fields_of_contact() {
+    // given
+    let current = contact("Anna", "111");
+    let merged = Contact::new_with_id(
+        current.id.clone(),
+        "Anna".to_string(),
+        "Kowalska".to_string(),
+        "999".to_string(),
+        Some("Anna Nowak".to_string()),
+    );
+
+    // when
+    let changes = replace::describe_changes(&current, &merged);
+
+    // then
+    assert_eq!(
+        changes,
+        vec!["surname: Nowak -> Kowalska", "phone: 111 -> 999"]
+    );
+}
+
+#[test]
+fn should_merge_contacts_matched_by_phone_or_name() {
+    common::with_db(async {
+        // given
+        let contacts = repository::contacts();
+        contacts.create(contact("Anna", "111")).await.unwrap();
+        contacts
+            .create(Contact::new(
+                "Bob".to_string(),
+                "Nowak".to_string(),
+                "222".to_string(),
+                Some("Bobby".to_string()),
+            ))
+            .await
+            .unwrap();
+        let rows = vec![
+            row("Anne", "111", None),
+            row("Bob", "999", Some("Bobby")),
+            row("Carol", "333", None),
+        ];
+        let source = std::env::temp_dir().join(format!("sms_merge_{}.json", std::process::id()));
+        std::fs::write(&source, serde_json::to_string(&rows).unwrap()).unwrap();
+
+        // when
+        let result = replace::manage_imports(ImportCommads::MergeContacts {
+            source_csv: source.clone(),
+            columns: CsvColumnsArgs {
+                first_name_column: "first_name".to_string(),
+                surname_column: "surname_name".to_string(),
+                phone_column: "phone".to_string(),
+                contact_name_column: "contact_name".to_string(),
+                delimiter: ',',
+            },
+            remove_missing: false,
+            dry_run: false,
+        })
+        .await;
+        std::fs::remove_file(source).unwrap();
+
+        // then
+        assert_eq!(
+            result,
+            Ok("Contacts merged: 1 added, 2 updated, 0 kept, 0 unchanged".to_string())
+        );
+        let mut merged: Vec<(String, String)> = contacts
+            .get_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.contact_name, c.phone))
+            .collect();
+        merged.sort();
+        assert_eq!(
+            merged,
+            vec![
+                ("Anna Nowak".to_string(), "111".to_string()),
+                ("Bobby".to_string(), "999".to_string()),
+                ("Carol Nowak".to_string(), "333".to_string()),
+            ]
+        );
+    });
+}
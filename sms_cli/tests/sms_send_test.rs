@@ -1,72 +1,190 @@
-use sms_api::sms_mock_api::{self, mockito};
+mod common;
+
+use sms_api::{
+    sms_mock_api::{self, mockito},
+    RecipientResult, RecipientStatus, SmsError,
+};
 use sms_cli::args_parser::{SendSmsArgs, SmsMessageArgs, SmsTargetArgs};
-use sms_config::config::SmsApiConf;
+use sms_config::config::{SmsApiConf, SmsApiProvider};
+
+#[test]
+fn should_send_sms_successfully() {
+    common::with_db(async {
+        // given
+        let mut server = mockito::Server::new_async().await;
+        let mock_handler = sms_mock_api::sending_sms_is_successful(&mut server).await;
+        let send_args = SendSmsArgs {
+            to: SmsTargetArgs {
+                number: Some("123456789".to_string()),
+                contact_name: None,
+                group_name: None,
+            },
+            message: SmsMessageArgs {
+                plain: Some("Hello world".to_string()),
+                template: None,
+            },
+            at: None,
+            vars: vec![],
+            transliterate: None,
+            wait_delivery: false,
+        };
+        let sms_api_config = SmsApiConf {
+            provider: SmsApiProvider::Alcatel {
+                url: server.url(),
+                retry_count: 3,
+                retry_delay: 50,
+                username: None,
+                password: None,
+                pin: None,
+            },
+            ..SmsApiConf::default()
+        };
+
+        // when
+        sms_cli::sms_send::send_sms(send_args, &sms_api_config)
+            .await
+            .expect("send_sms_successfully");
+
+        // then
+        mock_handler.assert_called();
+    });
+}
+
+#[test]
+fn should_fail_when_sending_sms() {
+    common::with_db(async {
+        // given
+        let mut server = mockito::Server::new_async().await;
+        let mock_handler = sms_mock_api::sending_sms_failure(&mut server).await;
+        let send_args = SendSmsArgs {
+            to: SmsTargetArgs {
+                number: Some("123456789".to_string()),
+                contact_name: None,
+                group_name: None,
+            },
+            message: SmsMessageArgs {
+                plain: Some("Hello world".to_string()),
+                template: None,
+            },
+            at: None,
+            vars: vec![],
+            transliterate: None,
+            wait_delivery: false,
+        };
+        let sms_api_config = SmsApiConf {
+            provider: SmsApiProvider::Alcatel {
+                url: server.url(),
+                retry_count: sms_mock_api::MAX_RETRIES,
+                retry_delay: 50,
+                username: None,
+                password: None,
+                pin: None,
+            },
+            ..SmsApiConf::default()
+        };
+
+        // when
+        let result = sms_cli::sms_send::send_sms(send_args, &sms_api_config).await;
+
+        // then
+        assert!(matches!(
+            result,
+            Err(output) if output.contains(
+                "Service didn't confirmed successful send")
+        ));
+        mock_handler.assert_called();
+    });
+}
 
-#[tokio::test]
-async fn should_send_sms_successfully() {
+#[test]
+fn should_summarize_recipients_message_was_not_sent_to() {
     // given
-    let mut server = mockito::Server::new_async().await;
-    let mock_handler = sms_mock_api::sending_sms_is_successful(&mut server).await;
-    let send_args = SendSmsArgs {
-        to: SmsTargetArgs {
-            number: Some("123456789".to_string()),
-            contact_name: None,
-            group_name: None,
+    let results = vec![
+        RecipientResult {
+            phone: "123456789".to_string(),
+            status: RecipientStatus::Sent,
+            provider: None,
+            reference: None,
         },
-        message: SmsMessageArgs {
-            plain: Some("Hello world".to_string()),
-            template: None,
+        RecipientResult {
+            phone: "987654321".to_string(),
+            status: RecipientStatus::Failed(SmsError::ModemError("+CMS ERROR: 500".to_string())),
+            provider: None,
+            reference: None,
         },
-    };
-    let sms_api_config = SmsApiConf {
-        provider: sms_config::config::SmsApiProvider::Alcatel {
-            url: server.url(),
-            retry_count: 3,
-            retry_delay: 50,
+        RecipientResult {
+            phone: "555666777".to_string(),
+            status: RecipientStatus::Skipped,
+            provider: None,
+            reference: None,
         },
-    };
+    ];
 
     // when
-    sms_cli::sms_send::send_sms(send_args, &sms_api_config)
-        .await
-        .expect("send_sms_successfully");
+    let table = sms_cli::sms_send::render_summary_table(&results);
+    let summary = sms_cli::sms_send::summarize_results(&results);
 
     // then
-    mock_handler.assert_called();
+    assert!(table.contains("| 123456789 | Sent"));
+    assert!(table.contains("| 987654321 | Failed  | Modem error: +CMS ERROR: 500"));
+    assert!(table.contains("| 555666777 | Skipped |"));
+    assert_eq!(
+        summary,
+        Err(
+            "Message not sent to 2 of 3 recipient(s), 987654321: Modem error: +CMS ERROR: 500, \
+            555666777: Not attempted because modem failed on previous recipient"
+                .to_string()
+        )
+    );
 }
 
-#[tokio::test]
-async fn should_fail_when_sending_sms() {
+#[test]
+fn should_summarize_message_sent_to_all_recipients() {
     // given
-    let mut server = mockito::Server::new_async().await;
-    let mock_handler = sms_mock_api::sending_sms_failure(&mut server).await;
-    let send_args = SendSmsArgs {
-        to: SmsTargetArgs {
-            number: Some("123456789".to_string()),
-            contact_name: None,
-            group_name: None,
-        },
-        message: SmsMessageArgs {
-            plain: Some("Hello world".to_string()),
-            template: None,
-        },
-    };
-    let sms_api_config = SmsApiConf {
-        provider: sms_config::config::SmsApiProvider::Alcatel {
-            url: server.url(),
-            retry_count: sms_mock_api::MAX_RETRIES,
-            retry_delay: 50,
-        },
-    };
+    let results = vec![RecipientResult {
+        phone: "123456789".to_string(),
+        status: RecipientStatus::Sent,
+        provider: None,
+        reference: None,
+    }];
 
     // when
-    let result = sms_cli::sms_send::send_sms(send_args, &sms_api_config).await;
+    let summary = sms_cli::sms_send::summarize_results(&results);
 
     // then
-    assert!(matches!(
-        result,
-        Err(output) if output.contains(
-            "Service didn't confirmed successful send")
-    ));
-    mock_handler.assert_called();
+    assert_eq!(summary, Ok("Message sent to 1 recipient(s)".to_string()));
+}
+
+#[test]
+fn should_send_plain_text_with_braces_unchanged() {
+    common::with_db(async {
+        // given
+        let modem = sms_mock_api::at_modem_is_working();
+        let send_args = SendSmsArgs {
+            to: SmsTargetArgs {
+                number: Some("123456789".to_string()),
+                contact_name: None,
+                group_name: None,
+            },
+            message: SmsMessageArgs {
+                plain: Some("Code is }}{{".to_string()),
+                template: None,
+            },
+            at: None,
+            vars: vec![],
+            transliterate: None,
+            wait_delivery: false,
+        };
+        let sms_api_config = common::at_serial_config(&modem.device);
+
+        // when
+        let result = sms_cli::sms_send::send_sms(send_args, &sms_api_config).await;
+
+        // then
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(
+            modem.sent_sms(),
+            vec![("123456789".to_string(), "Code is }}{{".to_string())]
+        );
+    });
 }
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use sms_cli::template_vars;
+
+#[test]
+fn should_render_variables() {
+    // given
+    let values = HashMap::from([
+        ("first_name".to_string(), "Jan".to_string()),
+        ("date".to_string(), "2024-01-31".to_string()),
+    ]);
+
+    // when
+    let rendered = template_vars::render("Hi {{first_name}}, see you {{ date }}!", &values);
+
+    // then
+    assert_eq!(rendered, Ok("Hi Jan, see you 2024-01-31!".to_string()));
+}
+
+#[test]
+fn should_fail_when_variable_has_no_value() {
+    // given
+    let values = HashMap::from([("first_name".to_string(), "Jan".to_string())]);
+
+    // when
+    let rendered = template_vars::render("Hi {{first_name}}, code {{code}}", &values);
+
+    // then
+    assert!(matches!(rendered, Err(e) if e.contains("{{code}}")));
+}
+
+#[test]
+fn should_detect_unbalanced_braces() {
+    // when
+    let unclosed = template_vars::variables("Hi {{first_name");
+    let unopened = template_vars::variables("Hi first_name}} {{phone}}");
+
+    // then
+    assert!(matches!(unclosed, Err(e) if e.contains("never closed")));
+    assert!(matches!(unopened, Err(e) if e.contains("never opened")));
+}
+
+#[test]
+fn should_list_unique_variables() {
+    // when
+    let variables = template_vars::variables("{{phone}} {single} {{code}} {{phone}}");
+
+    // then
+    assert_eq!(variables, Ok(vec!["phone".to_string(), "code".to_string()]));
+}
@@ -0,0 +1,27 @@
+use sms_cli::templates;
+
+#[test]
+fn should_accept_valid_template() {
+    // when
+    let problems = templates::check_template("Hi {{first_name}}, see you {{date}}", 1);
+
+    // then
+    assert!(problems.is_empty());
+}
+
+#[test]
+fn should_report_template_problems() {
+    // given
+    let long_text = "Lorem ipsum ".repeat(20);
+
+    // when
+    let unknown = templates::check_template("Hi {{first_name}}, code {{code}}", 1);
+    let unbalanced = templates::check_template("Hi {{first_name", 1);
+    let too_long = templates::check_template(&long_text, 1);
+
+    // then
+    assert_eq!(unknown.len(), 1);
+    assert!(unknown[0].contains("{{code}}"));
+    assert!(matches!(&unbalanced[..], [problem] if problem.contains("never closed")));
+    assert!(matches!(&too_long[..], [problem] if problem.contains("2 sms parts")));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use clap::Parser;
+use sms_cli::{
+    args_parser::{Cli, Commands, TemplatePreviewContactArgs, TemplatesCommands},
+    templates,
+};
+use sms_config::config::{SmsApiConf, SmsConfig};
+use sms_db::{contacts::Contact, repository, templates::Template};
+
+#[test]
+fn should_parse_bare_transliterate_flag_of_template() {
+    // when
+    let cli = Cli::parse_from([
+        "sms",
+        "templates",
+        "update",
+        "greeting",
+        "Hi",
+        "--transliterate",
+    ]);
+
+    // then
+    assert!(matches!(
+        cli.command,
+        Commands::Templates(TemplatesCommands::Update {
+            transliterate: Some(true),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn should_keep_transliteration_when_only_text_is_updated() {
+    common::with_db(async {
+        // given
+        let config = SmsConfig::default();
+        let create = TemplatesCommands::Create {
+            name: "greeting".to_string(),
+            text: "Cześć".to_string(),
+            transliterate: Some(true),
+        };
+        templates::manage_templates(create, &config).await.unwrap();
+
+        // when
+        let update = TemplatesCommands::Update {
+            name: "greeting".to_string(),
+            text: "Dzień dobry".to_string(),
+            transliterate: None,
+        };
+        templates::manage_templates(update, &config).await.unwrap();
+
+        // then
+        let template = repository::templates()
+            .get(&Template::id_from_name("greeting"))
+            .await
+            .unwrap()
+            .expect("stored template");
+        assert_eq!(template.text, "Dzień dobry");
+        assert_eq!(template.transliterate, Some(true));
+    });
+}
+
+#[test]
+fn should_preview_template_transliterated_by_default_of_config() {
+    common::with_db(async {
+        // given
+        let config = SmsConfig {
+            sms_api: SmsApiConf {
+                transliterate: true,
+                ..SmsApiConf::default()
+            },
+            ..SmsConfig::default()
+        };
+        repository::contacts()
+            .create(Contact::new(
+                "Anna".to_string(),
+                "Nowak".to_string(),
+                "111222333".to_string(),
+                None,
+            ))
+            .await
+            .unwrap();
+        repository::templates()
+            .create(Template::new(
+                "greeting".to_string(),
+                "Cześć {{first_name}}".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        // when
+        let preview = TemplatesCommands::Preview {
+            name: "greeting".to_string(),
+            contact: TemplatePreviewContactArgs {
+                contact_name: "Anna Nowak".to_string(),
+                index: None,
+            },
+            vars: vec![],
+        };
+        let result = templates::manage_templates(preview, &config).await;
+
+        // then
+        let rendered = result.unwrap();
+        assert!(rendered.ends_with("Czesc Anna"), "{}", rendered);
+    });
+}
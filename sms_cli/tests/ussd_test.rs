@@ -0,0 +1,61 @@
+use sms_api::sms_mock_api::{self, mockito};
+use sms_cli::ussd::parse_balance;
+use sms_config::config::SmsApiConf;
+
+#[test]
+fn should_parse_balance_with_decimal_comma() {
+    // when
+    let balance = parse_balance(sms_mock_api::USSD_BALANCE_REPLY);
+
+    // then
+    assert_eq!(balance, Some(12.5));
+}
+
+#[test]
+fn should_skip_dates_and_numbers_without_cents() {
+    // when
+    let balance = parse_balance("Pakiet 1: wazny do 31.12.2026. Stan konta 7.05 PLN.");
+
+    // then
+    assert_eq!(balance, Some(7.05));
+}
+
+#[test]
+fn should_not_find_balance_in_reply_without_numbers() {
+    // when
+    let balance = parse_balance("Unknown application");
+
+    // then
+    assert_eq!(balance, None);
+}
+
+#[tokio::test]
+async fn should_return_ussd_reply() {
+    // given
+    let mut server = mockito::Server::new_async().await;
+    let mock_handler = sms_mock_api::alcatel_ussd_is_successful(&mut server).await;
+    let sms_api_config = SmsApiConf {
+        provider: sms_config::config::SmsApiProvider::Alcatel {
+            url: server.url(),
+            retry_count: 3,
+            retry_delay: 50,
+            username: None,
+            password: None,
+            pin: None,
+        },
+        price_per_sms: None,
+        transliterate: false,
+        balance_ussd: None,
+        delivery_reports: false,
+        retry: Default::default(),
+        rate_limit: Default::default(),
+        backup_providers: Default::default(),
+    };
+
+    // when
+    let reply = sms_cli::ussd::run_ussd(sms_mock_api::USSD_BALANCE_CODE, &sms_api_config).await;
+
+    // then
+    assert_eq!(reply.unwrap(), sms_mock_api::USSD_BALANCE_REPLY);
+    mock_handler.assert_called();
+}
@@ -0,0 +1,56 @@
+use sms_cli::{args_parser::VCardVersion, vcard};
+
+#[test]
+fn should_parse_vcard_3_and_4() {
+    // given
+    let content = "BEGIN:VCARD\r\n\
+        VERSION:3.0\r\n\
+        N:Nowak;Anna;;;\r\n\
+        FN:Ania\r\n\
+        TEL;TYPE=HOME:22 123 45 67\r\n\
+        TEL;TYPE=CELL:+48 123-456-789\r\n\
+        CATEGORIES:family,work\r\n\
+        END:VCARD\r\n\
+        BEGIN:VCARD\r\n\
+        VERSION:4.0\r\n\
+        FN:Jan Kowalski\r\n\
+        item1.TEL;VALUE=uri;TYPE=cell:tel:+48987654\r\n \
+        321\r\n\
+        CATEGORIES:football\\, tuesday\r\n\
+        END:VCARD\r\n";
+
+    // when
+    let cards = vcard::parse(content).expect("valid vcard");
+
+    // then
+    assert_eq!(cards.len(), 2);
+    assert_eq!(cards[0].first_name, "Anna");
+    assert_eq!(cards[0].surname_name, "Nowak");
+    assert_eq!(cards[0].contact_name, Some("Ania".to_string()));
+    assert_eq!(cards[0].phone, "+48123456789");
+    assert_eq!(cards[0].categories, vec!["family", "work"]);
+    assert_eq!(cards[1].first_name, "Jan");
+    assert_eq!(cards[1].surname_name, "Kowalski");
+    assert_eq!(cards[1].phone, "+48987654321");
+    assert_eq!(cards[1].categories, vec!["football, tuesday"]);
+}
+
+#[test]
+fn should_read_written_vcards() {
+    for version in [VCardVersion::V3, VCardVersion::V4] {
+        // given
+        let cards = vec![vcard::VCard {
+            first_name: "Anna".to_string(),
+            surname_name: "Nowak; Kowalska".to_string(),
+            phone: "+48123456789".to_string(),
+            contact_name: Some("Ania".to_string()),
+            categories: vec!["family".to_string(), "work".to_string()],
+        }];
+
+        // when
+        let parsed = vcard::parse(&vcard::write(&cards, version)).expect("valid vcard");
+
+        // then
+        assert_eq!(parsed, cards);
+    }
+}
@@ -6,6 +6,8 @@ pub struct SmsConfig {
     pub db: SmsDbConfig,
     #[serde(default)]
     pub sms_api: SmsApiConf,
+    #[serde(default)]
+    pub templates: TemplatesConfig,
 }
 
 #[derive(Deserialize, Debug)]
@@ -21,13 +23,110 @@ impl Default for SmsDbConfig {
     }
 }
 
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Debug)]
+pub struct TemplatesConfig {
+    #[serde(default = "default_max_segments")]
+    pub max_segments: usize,
+}
+
+impl Default for TemplatesConfig {
+    fn default() -> Self {
+        Self {
+            max_segments: default_max_segments(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
 pub struct SmsApiConf {
     #[serde(default)]
     pub provider: SmsApiProvider,
+    // Tried in order when sending through previous provider fails with retryable error
+    #[serde(default)]
+    pub backup_providers: Vec<SmsApiProvider>,
+    #[serde(default)]
+    pub price_per_sms: Option<f64>,
+    #[serde(default)]
+    pub transliterate: bool,
+    // USSD code answered with prepaid balance, e.g. "*101#"
+    #[serde(default)]
+    pub balance_ussd: Option<String>,
+    // Ask network to confirm that handset received message, not supported by every provider
+    #[serde(default)]
+    pub delivery_reports: bool,
+    #[serde(default)]
+    pub retry: RetryConf,
+    #[serde(default)]
+    pub rate_limit: RateLimitConf,
 }
 
-#[derive(Deserialize, Default, Debug)]
+// Pace of sending, limits count sms parts and missing limit is not applied
+#[derive(Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct RateLimitConf {
+    #[serde(default)]
+    pub per_minute: Option<u32>,
+    #[serde(default)]
+    pub per_hour: Option<u32>,
+    #[serde(default)]
+    pub daily_cap: Option<u32>,
+    // Milliseconds between two sms
+    #[serde(default)]
+    pub min_spacing: u64,
+}
+
+impl SmsApiConf {
+    // Primary provider first, then backups in configured order
+    pub fn providers(&self) -> Vec<&SmsApiProvider> {
+        std::iter::once(&self.provider)
+            .chain(&self.backup_providers)
+            .collect()
+    }
+}
+
+impl RateLimitConf {
+    pub fn is_limited(&self) -> bool {
+        self.per_minute.is_some()
+            || self.per_hour.is_some()
+            || self.daily_cap.is_some()
+            || self.min_spacing > 0
+    }
+}
+
+// Repeating of requests failed because modem was busy or not reachable, delays are in
+// milliseconds
+#[derive(Deserialize, Debug, Clone)]
+pub struct RetryConf {
+    // Includes the first attempt, 1 disables retries
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_initial_delay")]
+    pub initial_delay: u64,
+    #[serde(default = "default_max_delay")]
+    pub max_delay: u64,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    // Fraction of delay randomly added or subtracted
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+    // No retry is started after this time since the first attempt
+    #[serde(default = "default_max_elapsed")]
+    pub max_elapsed: u64,
+}
+
+impl Default for RetryConf {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            initial_delay: default_initial_delay(),
+            max_delay: default_max_delay(),
+            multiplier: default_multiplier(),
+            jitter: default_jitter(),
+            max_elapsed: default_max_elapsed(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
 #[serde(tag = "type", rename_all = "PascalCase")]
 pub enum SmsApiProvider {
     #[default]
@@ -39,16 +138,109 @@ pub enum SmsApiProvider {
         retry_count: usize,
         #[serde(default = "default_retry_delay")]
         retry_delay: u64,
+        // Newer firmwares reject requests until user logs in
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        // Used to unlock SIM automatically when modem rebooted
+        #[serde(default)]
+        pin: Option<String>,
     },
+    AtSerial {
+        device: String,
+        #[serde(default = "default_baud_rate")]
+        baud_rate: u32,
+        #[serde(default)]
+        pin: Option<String>,
+    },
+    HuaweiHilink {
+        #[serde(default = "default_hilink_url")]
+        url: String,
+        // Login is needed only when web interface of the stick is password protected
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        #[serde(default)]
+        pin: Option<String>,
+    },
+}
+
+impl SmsApiProvider {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SmsApiProvider::Void => "Void",
+            SmsApiProvider::Alcatel { .. } => "Alcatel",
+            SmsApiProvider::AtSerial { .. } => "AtSerial",
+            SmsApiProvider::HuaweiHilink { .. } => "HuaweiHilink",
+        }
+    }
+
+    // Tells apart providers of the same type, e.g. two serial modems
+    pub fn description(&self) -> String {
+        match self {
+            SmsApiProvider::Void => self.name().to_string(),
+            SmsApiProvider::Alcatel { url, .. } | SmsApiProvider::HuaweiHilink { url, .. } => {
+                format!("{} {}", self.name(), url)
+            }
+            SmsApiProvider::AtSerial { device, .. } => format!("{} {}", self.name(), device),
+        }
+    }
+
+    pub fn pin(&self) -> Option<&str> {
+        match self {
+            SmsApiProvider::Void => None,
+            SmsApiProvider::Alcatel { pin, .. }
+            | SmsApiProvider::AtSerial { pin, .. }
+            | SmsApiProvider::HuaweiHilink { pin, .. } => pin.as_deref(),
+        }
+    }
 }
 
 fn default_alcatel_url() -> String {
     "http://192.168.1.1".to_string()
 }
 
+fn default_hilink_url() -> String {
+    "http://192.168.8.1".to_string()
+}
+
 fn default_retry_count() -> usize {
     3
 }
 fn default_retry_delay() -> u64 {
     500
 }
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_initial_delay() -> u64 {
+    1000
+}
+
+fn default_max_delay() -> u64 {
+    30000
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+fn default_jitter() -> f64 {
+    0.2
+}
+
+fn default_max_elapsed() -> u64 {
+    60000
+}
+
+fn default_baud_rate() -> u32 {
+    115200
+}
+
+fn default_max_segments() -> usize {
+    2
+}
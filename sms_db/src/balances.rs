@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+use crate::sms_repository::RecordEntity;
+
+const BALANCE_TABLE: &str = "balance";
+// Only the last checked balance of the SIM is kept
+const CURRENT_BALANCE_ID: &str = "current";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Balance {
+    pub id: Thing,
+    pub amount: f64,
+    pub reply: String,
+    pub checked_at: Datetime,
+}
+
+impl Balance {
+    pub fn current_id() -> Thing {
+        Self::id_from_str(CURRENT_BALANCE_ID)
+    }
+
+    pub fn new(amount: f64, reply: String) -> Self {
+        Self {
+            id: Self::current_id(),
+            amount,
+            reply,
+            checked_at: Datetime::default(),
+        }
+    }
+}
+
+impl RecordEntity for Balance {
+    fn table_name() -> &'static str {
+        BALANCE_TABLE
+    }
+
+    fn id(&self) -> &Thing {
+        &self.id
+    }
+}
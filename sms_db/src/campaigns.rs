@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+use crate::sms_repository::RecordEntity;
+
+const CAMPAIGN_TABLE: &str = "campaign";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecipientState {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignRecipient {
+    pub phone: String,
+    pub contact: Option<Thing>,
+    // Rendered and transliterated text, so resumed campaign sends exactly the same message
+    pub text: String,
+    pub state: RecipientState,
+    pub error: Option<String>,
+}
+
+impl CampaignRecipient {
+    pub fn new(phone: String, contact: Option<Thing>, text: String) -> Self {
+        Self {
+            phone,
+            contact,
+            text,
+            state: RecipientState::Pending,
+            error: None,
+        }
+    }
+}
+
+// Single run of `sms send`, keeps state of every recipient so it can be resumed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    pub id: Thing,
+    pub group: Option<Thing>,
+    pub template_name: Option<String>,
+    pub created_at: Datetime,
+    pub recipients: Vec<CampaignRecipient>,
+}
+
+impl RecordEntity for Campaign {
+    fn table_name() -> &'static str {
+        CAMPAIGN_TABLE
+    }
+
+    fn id(&self) -> &Thing {
+        &self.id
+    }
+}
+
+impl Campaign {
+    pub fn new(
+        group: Option<Thing>,
+        template_name: Option<String>,
+        recipients: Vec<CampaignRecipient>,
+    ) -> Self {
+        Self {
+            id: Self::random_id(),
+            group,
+            template_name,
+            created_at: Datetime::default(),
+            recipients,
+        }
+    }
+
+    // Recipients in given state grouped by text, in order they were added
+    pub fn messages_in_state(
+        &self,
+        state: RecipientState,
+    ) -> Vec<(String, Vec<CampaignRecipient>)> {
+        let mut messages: Vec<(String, Vec<CampaignRecipient>)> = vec![];
+        for recipient in self.recipients.iter().filter(|r| r.state == state) {
+            match messages
+                .iter_mut()
+                .find(|(text, _)| *text == recipient.text)
+            {
+                Some((_, recipients)) => recipients.push(recipient.clone()),
+                None => messages.push((recipient.text.clone(), vec![recipient.clone()])),
+            }
+        }
+        messages
+    }
+
+    // Recipients the message was already sent to are never changed back
+    pub fn set_state(
+        &mut self,
+        phone: &str,
+        text: &str,
+        state: RecipientState,
+        error: Option<String>,
+    ) {
+        for recipient in self.recipients.iter_mut() {
+            if recipient.phone == phone
+                && recipient.text == text
+                && recipient.state != RecipientState::Sent
+            {
+                recipient.state = state;
+                recipient.error = error.clone();
+            }
+        }
+    }
+
+    pub fn count(&self, state: RecipientState) -> usize {
+        self.recipients.iter().filter(|r| r.state == state).count()
+    }
+}
@@ -61,7 +61,7 @@ impl Contact {
     pub fn update(&mut self) {}
 }
 
-impl<'a> SmsRepository<'a, Contact> {
+impl SmsRepository<Contact> {
     pub async fn find_by_contact_name(
         &self,
         contact_name: &str,
@@ -76,6 +76,10 @@ impl<'a> SmsRepository<'a, Contact> {
         self.find_by_field("contact_name", contact_name).await
     }
 
+    pub async fn find_all_by_phone(&self, phone: &str) -> Result<Vec<Contact>, String> {
+        self.find_by_field("phone", phone).await
+    }
+
     pub async fn find_exactly_one_by_contact_name(
         &self,
         contact_name: &str,
@@ -105,6 +109,36 @@ impl<'a> SmsRepository<'a, Contact> {
         })
     }
 
+    pub async fn merge_contacts(
+        &self,
+        added: Vec<Contact>,
+        updated: Vec<Contact>,
+        removed: Vec<Thing>,
+    ) -> Result<(), String> {
+        let mut query = self
+            .db
+            .query("BEGIN TRANSACTION")
+            .query("DELETE $removed")
+            .query("INSERT INTO contact $added")
+            .bind(("removed", removed))
+            .bind(("added", added));
+        for (index, contact) in updated.into_iter().enumerate() {
+            query = query
+                .query(format!(
+                    "UPDATE $updated_id_{0} CONTENT $updated_{0}",
+                    index
+                ))
+                .bind((format!("updated_id_{}", index), contact.id.clone()))
+                .bind((format!("updated_{}", index), contact));
+        }
+        query
+            .query("COMMIT TRANSACTION")
+            .await
+            .and_then(|response| response.check())
+            .map_err(|e| format!("Could not merge contacts. Reason: {}", e))?;
+        Ok(())
+    }
+
     pub async fn find_all_or_select_at_index(
         &self,
         contact_name: &str,
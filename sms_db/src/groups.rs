@@ -44,7 +44,7 @@ impl RecordEntity for Group {
     }
 }
 
-impl<'a> SmsRepository<'a, Group> {
+impl SmsRepository<Group> {
     pub async fn find_group_details(
         &self,
         group_id: &Thing,
@@ -73,6 +73,31 @@ impl<'a> SmsRepository<'a, Group> {
         Ok(())
     }
 
+    // Removed contacts are deleted only when they don't belong to any other group
+    pub async fn replace_contacts(
+        &self,
+        group_id: &Thing,
+        removed: Vec<Thing>,
+        added: Vec<Contact>,
+    ) -> Result<(), String> {
+        let added_ids: Vec<Thing> = added.iter().map(|c| c.id.clone()).collect();
+        self.db
+            .query("BEGIN TRANSACTION")
+            .query("DELETE group_assignment WHERE in INSIDE $removed AND out = $group_id")
+            .query("DELETE contact WHERE id INSIDE $removed AND array::len(->group_assignment) = 0")
+            .query("INSERT INTO contact $added")
+            .query("RELATE $added_ids->group_assignment->$group_id")
+            .query("COMMIT TRANSACTION")
+            .bind(("removed", removed))
+            .bind(("added", added))
+            .bind(("added_ids", added_ids))
+            .bind(("group_id", group_id))
+            .await
+            .and_then(|response| response.check())
+            .map_err(|e| format!("Could not replace group contacts. Reason: {}", e))?;
+        Ok(())
+    }
+
     pub async fn unassign_contact(
         &self,
         contact_id: &Thing,
@@ -1,5 +1,12 @@
+pub mod balances;
+pub mod campaigns;
 pub mod contacts;
 pub mod groups;
+pub mod recurring_schedules;
 pub mod repository;
+pub mod scheduled_jobs;
+pub mod sent_messages;
 pub mod sms_repository;
 pub mod templates;
+
+pub use surrealdb::sql::{Datetime, Thing};
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+use crate::sms_repository::{RecordEntity, SmsRepository};
+
+const RECURRING_SCHEDULE_TABLE: &str = "recurring_schedule";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleSpec {
+    Cron(String),
+    RRule(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecurringSchedule {
+    pub id: Thing,
+    pub name: String,
+    pub spec: ScheduleSpec,
+    pub template: Thing,
+    pub group: Thing,
+    pub paused: bool,
+    pub next_run_at: Datetime,
+    pub last_run_at: Option<Datetime>,
+    pub error: Option<String>,
+    pub created_at: Datetime,
+}
+
+impl RecordEntity for RecurringSchedule {
+    fn table_name() -> &'static str {
+        RECURRING_SCHEDULE_TABLE
+    }
+
+    fn id(&self) -> &Thing {
+        &self.id
+    }
+}
+
+impl RecurringSchedule {
+    pub fn id_from_name(name: &str) -> Thing {
+        Self::id_from_str(name)
+    }
+
+    pub fn new(
+        name: String,
+        spec: ScheduleSpec,
+        template: Thing,
+        group: Thing,
+        next_run_at: Datetime,
+    ) -> Self {
+        Self {
+            id: Self::id_from_name(&name),
+            name,
+            spec,
+            template,
+            group,
+            paused: false,
+            next_run_at,
+            last_run_at: None,
+            error: None,
+            created_at: Datetime::default(),
+        }
+    }
+}
+
+impl SmsRepository<RecurringSchedule> {
+    pub async fn find_due_schedules(&self) -> Result<Vec<RecurringSchedule>, String> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM type::table($table) WHERE paused = false AND next_run_at <= time::now() ORDER BY next_run_at")
+            .bind(("table", RECURRING_SCHEDULE_TABLE))
+            .await
+            .map_err(|e| format!("Could not find due schedules. Reason: {}", e))?;
+        result
+            .take(0)
+            .map_err(|e| format!("Could not find due schedules. Reason: {}", e))
+    }
+
+    pub async fn advance(
+        &self,
+        schedule: &RecurringSchedule,
+        next_run_at: Datetime,
+    ) -> Result<Option<RecurringSchedule>, String> {
+        let mut result = self
+            .db
+            .query("UPDATE $schedule_id SET next_run_at = $next_run_at, last_run_at = time::now() WHERE paused = false AND next_run_at = $expected_run_at RETURN AFTER")
+            .bind(("schedule_id", &schedule.id))
+            .bind(("next_run_at", next_run_at))
+            .bind(("expected_run_at", &schedule.next_run_at))
+            .await
+            .map_err(|e| format!("Could not advance schedule '{}'. Reason: {}", schedule.name, e))?;
+        let updated: Vec<RecurringSchedule> = result.take(0).map_err(|e| {
+            format!(
+                "Could not advance schedule '{}'. Reason: {}",
+                schedule.name, e
+            )
+        })?;
+        Ok(updated.into_iter().next())
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::OnceLock;
+use std::sync::Mutex;
 
 use sms_config::config::SmsConfig;
 use surrealdb::{
@@ -6,17 +6,41 @@ use surrealdb::{
     Surreal,
 };
 
-use crate::{contacts::Contact, groups::Group, sms_repository::SmsRepository, templates::Template};
+use crate::{
+    balances::Balance, campaigns::Campaign, contacts::Contact, groups::Group,
+    recurring_schedules::RecurringSchedule, scheduled_jobs::ScheduledJob,
+    sent_messages::SentMessage, sms_repository::SmsRepository, templates::Template,
+};
+
+pub fn balances() -> SmsRepository<Balance> {
+    SmsRepository::new(crate::repository::get())
+}
+
+pub fn campaigns() -> SmsRepository<Campaign> {
+    SmsRepository::new(crate::repository::get())
+}
+
+pub fn contacts() -> SmsRepository<Contact> {
+    SmsRepository::new(crate::repository::get())
+}
 
-pub fn contacts() -> SmsRepository<'static, Contact> {
+pub fn groups() -> SmsRepository<Group> {
     SmsRepository::new(crate::repository::get())
 }
 
-pub fn groups() -> SmsRepository<'static, Group> {
+pub fn templates() -> SmsRepository<Template> {
     SmsRepository::new(crate::repository::get())
 }
 
-pub fn templates() -> SmsRepository<'static, Template> {
+pub fn sent_messages() -> SmsRepository<SentMessage> {
+    SmsRepository::new(crate::repository::get())
+}
+
+pub fn scheduled_jobs() -> SmsRepository<ScheduledJob> {
+    SmsRepository::new(crate::repository::get())
+}
+
+pub fn recurring_schedules() -> SmsRepository<RecurringSchedule> {
     SmsRepository::new(crate::repository::get())
 }
 
@@ -26,9 +50,12 @@ pub enum RepositoryError {
     ConnectionError(String),
 }
 
-static DB: OnceLock<Surreal<Db>> = OnceLock::new();
+static DB: Mutex<Option<Surreal<Db>>> = Mutex::new(None);
 
 pub async fn init(config: &SmsConfig) -> Result<(), RepositoryError> {
+    if DB.lock().unwrap().is_some() {
+        return Err(RepositoryError::AlreadyInitialized);
+    }
     let db: Surreal<Db> = Surreal::init();
     db.connect::<RocksDb>(&config.db.storage_path)
         .await
@@ -39,12 +66,22 @@ pub async fn init(config: &SmsConfig) -> Result<(), RepositoryError> {
         .use_db("sms_db")
         .await
         .map_err(|e| RepositoryError::ConnectionError(format!("Could not use sms db {}", e)))?;
-    DB.set(db)
-        .map_err(|_| RepositoryError::AlreadyInitialized)?;
+    let mut current = DB.lock().unwrap();
+    if current.is_some() {
+        return Err(RepositoryError::AlreadyInitialized);
+    }
+    *current = Some(db);
     Ok(())
 }
 
-pub fn get() -> &'static Surreal<Db> {
-    DB.get()
+// Storage is unlocked once repositories still in use are dropped, so other processes can open it
+pub fn close() {
+    DB.lock().unwrap().take();
+}
+
+pub fn get() -> Surreal<Db> {
+    DB.lock()
+        .unwrap()
+        .clone()
         .expect("Db not initialized. Call init before this method!")
 }
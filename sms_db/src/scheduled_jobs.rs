@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+use crate::sms_repository::{RecordEntity, SmsRepository};
+
+const SCHEDULED_JOB_TABLE: &str = "scheduled_job";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobTarget {
+    Number(String),
+    Contact(String),
+    Group(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobMessage {
+    Plain(String),
+    Template(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Scheduled,
+    Dispatching,
+    Sent,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub id: Thing,
+    pub target: JobTarget,
+    pub message: JobMessage,
+    #[serde(default)]
+    pub vars: Vec<(String, String)>,
+    #[serde(default)]
+    pub transliterate: Option<bool>,
+    pub send_at: Datetime,
+    pub status: JobStatus,
+    pub created_at: Datetime,
+    pub error: Option<String>,
+}
+
+impl RecordEntity for ScheduledJob {
+    fn table_name() -> &'static str {
+        SCHEDULED_JOB_TABLE
+    }
+
+    fn id(&self) -> &Thing {
+        &self.id
+    }
+}
+
+impl ScheduledJob {
+    pub fn new(
+        target: JobTarget,
+        message: JobMessage,
+        vars: Vec<(String, String)>,
+        send_at: Datetime,
+    ) -> Self {
+        Self {
+            id: Self::random_id(),
+            target,
+            message,
+            vars,
+            transliterate: None,
+            send_at,
+            status: JobStatus::Scheduled,
+            created_at: Datetime::default(),
+            error: None,
+        }
+    }
+}
+
+impl SmsRepository<ScheduledJob> {
+    pub async fn find_due_jobs(&self) -> Result<Vec<ScheduledJob>, String> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM type::table($table) WHERE status = $status AND send_at <= time::now() ORDER BY send_at")
+            .bind(("table", SCHEDULED_JOB_TABLE))
+            .bind(("status", JobStatus::Scheduled))
+            .await
+            .map_err(|e| format!("Could not find due jobs. Reason: {}", e))?;
+        result
+            .take(0)
+            .map_err(|e| format!("Could not find due jobs. Reason: {}", e))
+    }
+
+    pub async fn find_by_status(&self, status: JobStatus) -> Result<Vec<ScheduledJob>, String> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM type::table($table) WHERE status = $status ORDER BY send_at")
+            .bind(("table", SCHEDULED_JOB_TABLE))
+            .bind(("status", status))
+            .await
+            .map_err(|e| format!("Could not find jobs by status. Reason: {}", e))?;
+        result
+            .take(0)
+            .map_err(|e| format!("Could not find jobs by status. Reason: {}", e))
+    }
+
+    pub async fn change_status(
+        &self,
+        job_id: &Thing,
+        from: JobStatus,
+        to: JobStatus,
+        error: Option<String>,
+    ) -> Result<Option<ScheduledJob>, String> {
+        let mut result = self
+            .db
+            .query(
+                "UPDATE $job_id SET status = $to, error = $error WHERE status = $from RETURN AFTER",
+            )
+            .bind(("job_id", job_id))
+            .bind(("from", from))
+            .bind(("to", to))
+            .bind(("error", error))
+            .await
+            .map_err(|e| format!("Could not change status of job '{}'. Reason: {}", job_id, e))?;
+        let updated: Vec<ScheduledJob> = result
+            .take(0)
+            .map_err(|e| format!("Could not change status of job '{}'. Reason: {}", job_id, e))?;
+        Ok(updated.into_iter().next())
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime, Thing};
+
+use crate::sms_repository::{RecordEntity, SmsRepository};
+
+const SENT_MESSAGE_TABLE: &str = "sent_message";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SentMessageStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SentMessage {
+    pub id: Thing,
+    pub phone: String,
+    pub contact: Option<Thing>,
+    pub group: Option<Thing>,
+    pub text: String,
+    pub template_name: Option<String>,
+    pub provider: String,
+    pub created_at: Datetime,
+    pub sent_at: Option<Datetime>,
+    pub status: SentMessageStatus,
+    pub error: Option<String>,
+    // Missing when delivery report was not requested
+    #[serde(default)]
+    pub delivery: Option<DeliveryStatus>,
+    #[serde(default)]
+    pub delivered_at: Option<Datetime>,
+    // Set by AT modem, delivery report of the message carries the same one
+    #[serde(default)]
+    pub reference: Option<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct SentMessageFilter {
+    pub contacts: Option<Vec<Thing>>,
+    pub group: Option<Thing>,
+    pub from: Option<Datetime>,
+    pub to: Option<Datetime>,
+    pub status: Option<SentMessageStatus>,
+}
+
+impl RecordEntity for SentMessage {
+    fn table_name() -> &'static str {
+        SENT_MESSAGE_TABLE
+    }
+
+    fn id(&self) -> &Thing {
+        &self.id
+    }
+}
+
+impl SentMessage {
+    pub fn new(
+        phone: String,
+        contact: Option<Thing>,
+        group: Option<Thing>,
+        text: String,
+        template_name: Option<String>,
+        provider: String,
+    ) -> Self {
+        Self {
+            id: Self::random_id(),
+            phone,
+            contact,
+            group,
+            text,
+            template_name,
+            provider,
+            created_at: Datetime::default(),
+            sent_at: None,
+            status: SentMessageStatus::Pending,
+            error: None,
+            delivery: None,
+            delivered_at: None,
+            reference: None,
+        }
+    }
+
+    pub fn mark_sent(&mut self) {
+        self.status = SentMessageStatus::Sent;
+        self.sent_at = Some(Datetime::default());
+        self.error = None;
+    }
+
+    pub fn mark_failed(&mut self, error: String) {
+        self.status = SentMessageStatus::Failed;
+        self.sent_at = None;
+        self.error = Some(error);
+    }
+}
+
+impl SmsRepository<SentMessage> {
+    // Oldest first, so reports are matched in order the messages were sent
+    pub async fn find_awaiting_delivery(&self) -> Result<Vec<SentMessage>, String> {
+        let mut result = self
+            .db
+            .query(
+                "SELECT * FROM type::table($table) WHERE delivery = $delivery ORDER BY sent_at ASC",
+            )
+            .bind(("table", SENT_MESSAGE_TABLE))
+            .bind(("delivery", DeliveryStatus::Pending))
+            .await
+            .map_err(|e| format!("Could not find messages awaiting delivery. Reason: {}", e))?;
+        result
+            .take(0)
+            .map_err(|e| format!("Could not find messages awaiting delivery. Reason: {}", e))
+    }
+
+    pub async fn find_history(
+        &self,
+        filter: SentMessageFilter,
+    ) -> Result<Vec<SentMessage>, String> {
+        let mut conditions = vec![];
+        if filter.contacts.is_some() {
+            conditions.push("contact IN $contacts");
+        }
+        if filter.group.is_some() {
+            conditions.push("group = $group");
+        }
+        if filter.from.is_some() {
+            conditions.push("created_at >= $from");
+        }
+        if filter.to.is_some() {
+            conditions.push("created_at < $to");
+        }
+        if filter.status.is_some() {
+            conditions.push("status = $status");
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let mut result = self
+            .db
+            .query(format!(
+                "SELECT * FROM type::table($table) {} ORDER BY created_at DESC",
+                where_clause
+            ))
+            .bind(("table", SENT_MESSAGE_TABLE))
+            .bind(("contacts", filter.contacts))
+            .bind(("group", filter.group))
+            .bind(("from", filter.from))
+            .bind(("to", filter.to))
+            .bind(("status", filter.status))
+            .await
+            .map_err(|e| format!("Could not find message history. Reason: {}", e))?;
+        result
+            .take(0)
+            .map_err(|e| format!("Could not find message history. Reason: {}", e))
+    }
+}
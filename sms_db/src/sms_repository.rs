@@ -29,18 +29,18 @@ struct AnyRecord {
     id: Thing,
 }
 
-pub struct SmsRepository<'a, T> {
-    pub db: &'a Surreal<Db>,
+pub struct SmsRepository<T> {
+    pub db: Surreal<Db>,
     phantom: PhantomData<T>,
 }
 
-impl<'a, T> SmsRepository<'a, T>
+impl<T> SmsRepository<T>
 where
     T: RecordEntity,
 {
-    pub fn new(db_ref: &'a Surreal<Db>) -> Self {
+    pub fn new(db: Surreal<Db>) -> Self {
         Self {
-            db: db_ref,
+            db,
             phantom: PhantomData,
         }
     }
@@ -10,6 +10,8 @@ pub struct Template {
     pub id: Thing,
     pub name: String,
     pub text: String,
+    #[serde(default)]
+    pub transliterate: Option<bool>,
 }
 
 impl Template {
@@ -22,6 +24,7 @@ impl Template {
             id: Self::id_from_name(&name),
             name,
             text,
+            transliterate: None,
         }
     }
 }